@@ -4,11 +4,10 @@ use std::time::Duration;
 use ketch_core::resource::AssetManager;
 use ketch_core::settings::Settings;
 use ketch_engine::EventHandler;
-use ketch_core::input::input_event::ElementState::Released;
-use ketch_core::input::input_event::ElementState::Pressed;
 use std::path::Path;
 
-use ketch_core::input::input_event::{InputEvent, KeyboardInput, VirtualKeyCode};
+use ketch_core::input::input_event::{InputEvent, KeyboardInput, VirtualKeyCode, GamepadButton};
+use ketch_core::input::input_map::{InputMap, Binding, AxisBinding, MouseAxis};
 use ketch_core::input::InputSystem;
 use ketch_core::resource::camera::Direction;
 use ketch_engine::Engine;
@@ -16,12 +15,16 @@ use ketch_core::resource::scene::Scene;
 use ketch_core::resource::camera::Camera;
 use ketch_core::resource::object::ObjectBuilder;
 
+use log::*;
+
+/// Path of the config file with key bindings, default bindings are used if it can't be loaded.
+const INPUT_CONFIG_PATH: &str = "example/data/input.ron";
+
 pub struct GameInput {
-    mouse_delta_changed: bool,
+    input_map: InputMap,
     camera_speed: f32,
     mouse_sensitivity: f32,
     mouse_delta: (f32, f32),
-    grab_and_hide_cursor: bool,
     up: bool,
     down: bool,
     left: bool,
@@ -30,12 +33,16 @@ pub struct GameInput {
 
 impl GameInput {
     pub fn new(camera_speed: f32, mouse_sensitivity: f32) -> Self {
+        let input_map = InputMap::load(INPUT_CONFIG_PATH).unwrap_or_else(|err| {
+            warn!("Couldn't load input config, using default bindings: {}", err);
+            GameInput::default_input_map()
+        });
+
         GameInput {
-            mouse_delta_changed: false,
+            input_map,
             camera_speed,
             mouse_sensitivity,
             mouse_delta: (0.0, 0.0),
-            grab_and_hide_cursor: false,
             up: false,
             down: false,
             left: false,
@@ -43,6 +50,23 @@ impl GameInput {
         }
     }
 
+    pub fn default_input_map() -> InputMap {
+        let mut input_map = InputMap::new();
+        input_map.bind_action("move_forward", Binding::Key(VirtualKeyCode::W));
+        input_map.bind_action("move_backward", Binding::Key(VirtualKeyCode::S));
+        input_map.bind_action("move_left", Binding::Key(VirtualKeyCode::A));
+        input_map.bind_action("move_right", Binding::Key(VirtualKeyCode::D));
+        input_map.bind_action("move_forward", Binding::GamepadButton(GamepadButton::DPadUp));
+        input_map.bind_action("move_backward", Binding::GamepadButton(GamepadButton::DPadDown));
+        input_map.bind_action("move_left", Binding::GamepadButton(GamepadButton::DPadLeft));
+        input_map.bind_action("move_right", Binding::GamepadButton(GamepadButton::DPadRight));
+        input_map.bind_action("grab_cursor", Binding::Key(VirtualKeyCode::G));
+        input_map.bind_action("release_cursor", Binding::Key(VirtualKeyCode::H));
+        input_map.bind_axis("look_x", AxisBinding::MouseMotion { axis: MouseAxis::X, scale: 1.0 });
+        input_map.bind_axis("look_y", AxisBinding::MouseMotion { axis: MouseAxis::Y, scale: 1.0 });
+        input_map
+    }
+
     pub fn up(&self) -> bool {
         self.up
     }
@@ -59,41 +83,29 @@ impl GameInput {
         self.right
     }
 
-    pub fn grab_and_hide_cursor(&self) -> bool {
-        self.grab_and_hide_cursor
-    }
-
-    pub fn update_input(&mut self, input_system: &mut InputSystem, input: Vec<InputEvent>) {
-        input.into_iter().for_each(|event| match event {
-            InputEvent::KeyboardInput { keycode, state } => match keycode {
-                VirtualKeyCode::W if state == Pressed => self.up = true,
-                VirtualKeyCode::S if state == Pressed => self.down = true,
-                VirtualKeyCode::A if state == Pressed => self.left = true,
-                VirtualKeyCode::D if state == Pressed => self.right = true,
-                VirtualKeyCode::W if state == Released => self.up = false,
-                VirtualKeyCode::S if state == Released => self.down = false,
-                VirtualKeyCode::A if state == Released => self.left = false,
-                VirtualKeyCode::D if state == Released => self.right = false,
-                VirtualKeyCode::G if state == Pressed => {
-                    input_system.grab_cursor(true);
-                    input_system.hide_cursor(true);
-                },
-                VirtualKeyCode::H if state == Pressed => {
-                    input_system.grab_cursor(false);
-                    input_system.hide_cursor(false);
-                },
-                _ => (),
-            },
-            InputEvent::MouseMotion { delta } => {
-                self.mouse_delta_changed = true;
-                self.mouse_delta = (delta.0 as f32, delta.1 as f32);
-            },
-            _ => (),
-        })
+    pub fn update_input(&mut self, input_system: &mut InputSystem) {
+        let state = input_system.state();
+        self.up = self.input_map.pressed(state, "move_forward");
+        self.down = self.input_map.pressed(state, "move_backward");
+        self.left = self.input_map.pressed(state, "move_left");
+        self.right = self.input_map.pressed(state, "move_right");
+        // input system sums mouse movement until the next fixed update, which moves the camera
+        self.mouse_delta = (self.input_map.axis(state, "look_x"), self.input_map.axis(state, "look_y"));
+        let grab_cursor = self.input_map.just_pressed(state, "grab_cursor");
+        let release_cursor = self.input_map.just_pressed(state, "release_cursor");
+
+        if grab_cursor {
+            input_system.grab_cursor(true);
+            input_system.hide_cursor(true);
+        }
+        if release_cursor {
+            input_system.grab_cursor(false);
+            input_system.hide_cursor(false);
+        }
     }
 
     pub fn update_camera(&mut self, camera: &mut Camera, elapsed_time: Duration) {
-        if self.mouse_delta_changed {
+        if self.mouse_delta != (0.0, 0.0) {
             let (x_delta, y_delta) = self.mouse_delta;
 
             let current_yaw = camera.yaw();
@@ -101,18 +113,18 @@ impl GameInput {
 
             let current_pitch = camera.pitch();
             camera.set_pitch(current_pitch + (-y_delta) * self.mouse_sensitivity);
-            self.mouse_delta_changed = false;
+            self.mouse_delta = (0.0, 0.0);
         }
-        if self.up {
+        if self.up() {
             camera.move_camera(Direction::Up, self.camera_speed * (elapsed_time.as_millis() as f32 / 1000.0));
         }
-        if self.down {
+        if self.down() {
             camera.move_camera(Direction::Down, self.camera_speed * (elapsed_time.as_millis() as f32 / 1000.0));
         }
-        if self.left {
+        if self.left() {
             camera.move_camera(Direction::Left, self.camera_speed * (elapsed_time.as_millis() as f32 / 1000.0));
         }
-        if self.right {
+        if self.right() {
             camera.move_camera(Direction::Right, self.camera_speed * (elapsed_time.as_millis() as f32 / 1000.0));
         }
     }
@@ -154,8 +166,8 @@ impl EventHandler for GameState {
         asset_manager.active_scene_mut().unwrap().add_object(light_obj);
         asset_manager.active_scene_mut().unwrap().set_light_position(-1.0, 1.0, 0.0);
     }
-    fn process_input(&mut self, input_system: &mut InputSystem, input_events: Vec<InputEvent>) {
-        self.input.update_input(input_system, input_events);
+    fn process_input(&mut self, input_system: &mut InputSystem, _input_events: Vec<InputEvent>) {
+        self.input.update_input(input_system);
     }
     fn update(&mut self, settings: &Settings, asset_manager: &mut AssetManager, elapsed_time: Duration) {
         for object in asset_manager.active_scene_mut().unwrap().objects_mut().iter_mut().filter(|x| x.name() == "test_object") {
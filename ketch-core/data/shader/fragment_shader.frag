@@ -2,45 +2,186 @@
 layout(location = 0) in vec2 o_tex_coord;
 layout(location = 1) in vec3 o_normal;
 layout(location = 2) in vec3 frag_position;
-layout(location = 3) in vec3 view_space_light_position;
+layout(location = 3) in vec4 light_space_position;
 
 ///outgoing final color
 layout(location = 0) out vec4 f_color;
 
+// color mixed into objects selected in the editor
+const vec3 HIGHLIGHT_COLOR = vec3(1.0, 0.6, 0.0);
+const float HIGHLIGHT_STRENGTH = 0.35;
+
+// has to match MAX_LIGHTS in renderer::shader
+const uint MAX_LIGHTS = 16;
+
+const uint POINT_LIGHT = 0;
+const uint DIRECTIONAL_LIGHT = 1;
+const uint SPOT_LIGHT = 2;
+
+struct Light {
+  vec3 position;
+  uint kind;
+  vec3 direction;
+  float intensity;
+  vec3 color;
+  float constant_attenuation;
+  float linear_attenuation;
+  float quadratic_attenuation;
+  float inner_cutoff;
+  float outer_cutoff;
+};
+
+layout(set = 0, binding = 0) uniform TransformationData {
+  mat4 view;
+  mat4 proj;
+  mat4 light_space;
+} u_main;
+
 layout(binding = 1) uniform LightData {
-  vec3 light_position;
-  vec3 light_color;
+  Light lights[MAX_LIGHTS];
+  uint light_count;
 } light_data;
 
+// diffuse map of the material or texture of the mesh
 layout(binding = 2) uniform sampler2D tex;
 
+// depth of the scene rendered from the main light
+layout(binding = 3) uniform sampler2D shadow_map;
+
+// material maps, when a material doesn't have a map the diffuse map is bound instead
+layout(binding = 4) uniform sampler2D specular_map;
+layout(binding = 5) uniform sampler2D normal_map;
+layout(binding = 6) uniform sampler2D emissive_map;
+
+// has to be the same in vertex and fragment shader
 layout(push_constant) uniform PushConstants {
+  vec3 base_color;
+  float ambient_strength;
+  vec3 emissive_color;
+  float specular_strength;
+  float shininess;
   bool light_source;
   bool uniform_scale;
+  bool receive_shadows;
+  bool has_specular_map;
+  bool has_normal_map;
+  bool has_emissive_map;
+  bool highlighted;
 } push_constants;
 
-const float AMBIENT_STRENGTH = 0.1;
-const float SPECULAR_STRENGTH = 0.5;
+// Returns how much the fragment is in shadow, from 0.0 (lit) to 1.0 (in shadow).
+// Shadow map is sampled in 3x3 area around the fragment (percentage-closer filtering).
+float calculate_shadow(vec3 norm, vec3 light_dir) {
+  vec3 projected = light_space_position.xyz / light_space_position.w;
+  if(projected.z > 1.0) {
+    return 0.0;
+  }
+
+  vec2 shadow_coord = projected.xy * 0.5 + 0.5;
+  float bias = max(0.005 * (1.0 - dot(norm, light_dir)), 0.0005);
+  vec2 texel_size = 1.0 / vec2(textureSize(shadow_map, 0));
+
+  float shadow = 0.0;
+  for(int x = -1; x <= 1; x++) {
+    for(int y = -1; y <= 1; y++) {
+      float closest_depth = texture(shadow_map, shadow_coord + vec2(x, y) * texel_size).r;
+      shadow += projected.z - bias > closest_depth ? 1.0 : 0.0;
+    }
+  }
+  return shadow / 9.0;
+}
+
+// Perturbs the normal using normal map. Tangent space is computed from screen space derivatives,
+// so meshes don't need tangents.
+vec3 apply_normal_map(vec3 norm) {
+  vec3 dp1 = dFdx(frag_position);
+  vec3 dp2 = dFdy(frag_position);
+  vec2 duv1 = dFdx(o_tex_coord);
+  vec2 duv2 = dFdy(o_tex_coord);
+
+  vec3 dp2perp = cross(dp2, norm);
+  vec3 dp1perp = cross(norm, dp1);
+  vec3 tangent = dp2perp * duv1.x + dp1perp * duv2.x;
+  vec3 bitangent = dp2perp * duv1.y + dp1perp * duv2.y;
+
+  float scale = inversesqrt(max(dot(tangent, tangent), dot(bitangent, bitangent)));
+  mat3 tbn = mat3(tangent * scale, bitangent * scale, norm);
+
+  vec3 map_normal = texture(normal_map, o_tex_coord).xyz * 2.0 - 1.0;
+  return normalize(tbn * map_normal);
+}
+
+vec3 calculate_light(Light light, vec3 norm, vec3 view_dir, float specular_factor, bool casts_shadows) {
+  vec3 light_color = light.color * light.intensity;
+  vec3 light_dir;
+  float attenuation = 1.0;
+
+  if(light.kind == DIRECTIONAL_LIGHT) {
+    light_dir = normalize(-(mat3(u_main.view) * light.direction));
+  } else {
+    vec3 view_space_light_position = vec3(u_main.view * vec4(light.position, 1.0)); // Transform world-space light position to view-space light position
+    float distance = length(view_space_light_position - frag_position);
+    light_dir = normalize(view_space_light_position - frag_position);
+    attenuation = 1.0 / (light.constant_attenuation + light.linear_attenuation * distance + light.quadratic_attenuation * distance * distance);
+
+    if(light.kind == SPOT_LIGHT) {
+      float theta = dot(light_dir, normalize(-(mat3(u_main.view) * light.direction)));
+      float epsilon = max(light.inner_cutoff - light.outer_cutoff, 0.0001);
+      attenuation *= clamp((theta - light.outer_cutoff) / epsilon, 0.0, 1.0);
+    }
+  }
+
+  // ambient
+  vec3 ambient = push_constants.ambient_strength * light_color;
+
+  // diffuse
+  float diff = max(dot(norm, light_dir), 0.0);
+  vec3 diffuse = diff * light_color;
+
+  // specular
+  vec3 reflect_dir = reflect(-light_dir, norm);
+  float spec = pow(max(dot(view_dir, reflect_dir), 0.0), push_constants.shininess);
+  vec3 specular = specular_factor * spec * light_color;
+
+  float shadow = casts_shadows ? calculate_shadow(norm, light_dir) : 0.0;
+
+  return (ambient + (1.0 - shadow) * (diffuse + specular)) * attenuation;
+}
 
 void main() {
   if(push_constants.light_source) {
-    f_color = vec4(light_data.light_color, 1.0);
+    // light source objects are drawn with the color of the main light
+    vec3 color = light_data.light_count > 0 ? light_data.lights[0].color : vec3(1.0);
+    f_color = vec4(color, 1.0);
   } else {
-    // ambient
-    vec3 ambient = AMBIENT_STRENGTH * light_data.light_color;
-
-    // diffuse
     vec3 norm = normalize(o_normal);
-    vec3 light_dir = normalize(view_space_light_position - frag_position);
-    float diff = max(dot(norm, light_dir), 0.0);
-    vec3 diffuse = diff * light_data.light_color;
-
-    // specular
+    if(push_constants.has_normal_map) {
+      norm = apply_normal_map(norm);
+    }
     vec3 view_dir = normalize(-frag_position); // the viewer is always at (0,0,0) in view-space, so viewDir is (0,0,0) - Position => -Position
-    vec3 reflect_dir = reflect(-light_dir, norm);  
-    float spec = pow(max(dot(view_dir, reflect_dir), 0.0), 32);
-    vec3 specular = SPECULAR_STRENGTH * spec * light_data.light_color; 
 
-    f_color = vec4((ambient + diffuse + specular) * vec3(texture(tex, o_tex_coord)), 1.0);
+    float specular_factor = push_constants.specular_strength;
+    if(push_constants.has_specular_map) {
+      specular_factor *= texture(specular_map, o_tex_coord).r;
+    }
+
+    vec3 result = vec3(0.0);
+    for(uint i = 0; i < min(light_data.light_count, MAX_LIGHTS); i++) {
+      // only the main light has a shadow map
+      bool casts_shadows = i == 0 && push_constants.receive_shadows;
+      result += calculate_light(light_data.lights[i], norm, view_dir, specular_factor, casts_shadows);
+    }
+
+    vec3 emission = push_constants.emissive_color;
+    if(push_constants.has_emissive_map) {
+      emission *= vec3(texture(emissive_map, o_tex_coord));
+    }
+
+    vec3 base_color = push_constants.base_color * vec3(texture(tex, o_tex_coord));
+    f_color = vec4(result * base_color + emission, 1.0);
+  }
+
+  if(push_constants.highlighted) {
+    f_color.rgb = mix(f_color.rgb, HIGHLIGHT_COLOR, HIGHLIGHT_STRENGTH);
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,5 @@
+#version 450
+
+// shadow pass writes only depth
+void main() {
+}
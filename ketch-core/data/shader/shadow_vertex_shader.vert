@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+// columns of per instance model matrix
+layout(location = 3) in vec4 model_0;
+layout(location = 4) in vec4 model_1;
+layout(location = 5) in vec4 model_2;
+layout(location = 6) in vec4 model_3;
+
+layout(push_constant) uniform PushConstants {
+  mat4 light_space;
+} push_constants;
+
+void main() {
+  mat4 model = mat4(model_0, model_1, model_2, model_3);
+  gl_Position = push_constants.light_space * model * vec4(position, 1.0);
+}
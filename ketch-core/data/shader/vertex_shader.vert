@@ -3,40 +3,52 @@
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec3 normal;
 layout(location = 2) in vec2 tex_coord;
+// columns of per instance model matrix
+layout(location = 3) in vec4 model_0;
+layout(location = 4) in vec4 model_1;
+layout(location = 5) in vec4 model_2;
+layout(location = 6) in vec4 model_3;
 
 layout(location = 0) out vec2 o_tex_coord;
 layout(location = 1) out vec3 o_normal;
 layout(location = 2) out vec3 frag_position;
-layout(location = 3) out vec3 view_space_light_position;
-
-layout(binding = 1) uniform LightData {
-  vec3 light_position;
-  vec3 light_color;
-} light_data;
+layout(location = 3) out vec4 light_space_position;
 
+// has to be the same in vertex and fragment shader
 layout(push_constant) uniform PushConstants {
+  vec3 base_color;
+  float ambient_strength;
+  vec3 emissive_color;
+  float specular_strength;
+  float shininess;
   bool light_source;
   bool uniform_scale;
+  bool receive_shadows;
+  bool has_specular_map;
+  bool has_normal_map;
+  bool has_emissive_map;
+  bool highlighted;
 } push_constants;
 
 //Global uniforms
 layout(set = 0, binding = 0) uniform TransformationData {
-  mat4 model;
   mat4 view;
   mat4 proj;
+  mat4 light_space;
 } u_main;
 
 void main() {
-  gl_Position = u_main.proj * u_main.view * u_main.model * vec4(position, 1.0);
+  mat4 model = mat4(model_0, model_1, model_2, model_3);
+  gl_Position = u_main.proj * u_main.view * model * vec4(position, 1.0);
 
   o_tex_coord = tex_coord;
 
   if(push_constants.uniform_scale) {
-    o_normal = mat3(u_main.view * u_main.model) * normal;
+    o_normal = mat3(u_main.view * model) * normal;
   } else {
-    o_normal = mat3(transpose(inverse(u_main.view * u_main.model))) * normal;
+    o_normal = mat3(transpose(inverse(u_main.view * model))) * normal;
   }
   
-  frag_position = vec3(u_main.view * u_main.model * vec4(position, 1.0));
-  view_space_light_position = vec3(u_main.view * vec4(light_data.light_position, 1.0)); // Transform world-space light position to view-space light position
+  frag_position = vec3(u_main.view * model * vec4(position, 1.0));
+  light_space_position = u_main.light_space * model * vec4(position, 1.0);
 }
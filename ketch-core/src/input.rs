@@ -1,10 +1,20 @@
 pub mod input_event;
+pub mod input_map;
+pub mod input_error;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod input_recording;
+pub mod input_state;
 
 use winit::Window;
 use vulkano::swapchain::Surface;
 use std::sync::Arc;
 use crate::settings::Settings;
-use crate::input::input_event::InputEvent;
+use crate::input::input_event::{InputEvent, VirtualKeyCode};
+#[cfg(feature = "gamepad")]
+use crate::input::gamepad::Gamepads;
+use crate::input::input_recording::{InputRecording, InputReplay};
+use crate::input::input_state::InputState;
 
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -19,6 +29,13 @@ use log::*;
 pub struct InputSystem {
     events_loop: EventsLoop,
     surface: Option<Arc<Surface<Window>>>,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+    pending_gamepad_events: Vec<InputEvent>,
+    tick: u64,
+    recording: Option<(u64, InputRecording)>,
+    replay: Option<(u64, InputReplay)>,
+    state: InputState,
 }
 
 impl InputSystem {
@@ -29,6 +46,13 @@ impl InputSystem {
         InputSystem {
             events_loop: events_loop,
             surface: None,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(),
+            pending_gamepad_events: Vec::new(),
+            tick: 0,
+            recording: None,
+            replay: None,
+            state: InputState::new(),
         }
     }
 
@@ -63,16 +87,123 @@ impl InputSystem {
         }
     }
 
-    /// Loads pending events
+    /// Returns an Option with a mutable reference to gamepads, None if they couldn't be initialized.
+    #[cfg(feature = "gamepad")]
+    pub fn gamepads_mut(&mut self) -> Option<&mut Gamepads> {
+        self.gamepads.as_mut()
+    }
+
+    /// Loads pending events. Gamepad events are stored until `take_gamepad_events` is called.
+    /// Gamepads are read only with `gamepad` feature enabled.
     pub fn fetch_pending_events(&mut self) -> Vec<Event> {
         let mut events = Vec::new();
 
+        #[cfg(feature = "gamepad")]
+        if let Some(gamepads) = self.gamepads.as_mut() {
+            self.pending_gamepad_events.extend(gamepads.fetch_pending_events());
+        }
+
         self.events_loop.poll_events(|input_event| {
             events.push(input_event);
         });
 
         events
     }
+
+    /// Returns gamepad events loaded since the last call.
+    pub fn take_gamepad_events(&mut self) -> Vec<InputEvent> {
+        std::mem::replace(&mut self.pending_gamepad_events, Vec::new())
+    }
+
+    /// Adds events passed to the game to the polled input state.
+    pub fn update_state(&mut self, input_events: &[InputEvent]) {
+        self.state.add_events(input_events);
+    }
+
+    /// Returns a reference to keyboard, mouse and gamepad state. Presses, releases and mouse movement
+    /// are kept until the end of the next fixed update, so every change is seen by exactly one update,
+    /// no matter how many frames are rendered between updates.
+    pub fn state(&self) -> &InputState {
+        &self.state
+    }
+
+    /// Returns true if the key is held down.
+    pub fn is_key_down(&self, keycode: VirtualKeyCode) -> bool {
+        self.state.is_key_down(keycode)
+    }
+
+    /// Returns true if the key was pressed since the last fixed update.
+    pub fn was_pressed(&self, keycode: VirtualKeyCode) -> bool {
+        self.state.was_pressed(keycode)
+    }
+
+    /// Returns true if the key was released since the last fixed update.
+    pub fn was_released(&self, keycode: VirtualKeyCode) -> bool {
+        self.state.was_released(keycode)
+    }
+
+    /// Returns mouse movement summed since the last fixed update.
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.state.mouse_delta()
+    }
+
+    /// Returns index of the next fixed update of the game.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Moves to the next fixed update, should be called after every update of the game.
+    /// Changes of input state seen by the update are cleared.
+    pub fn advance_tick(&mut self) {
+        self.tick += 1;
+        self.state.clear_changes();
+    }
+
+    /// Starts recording input events passed to `record_events`. Previous recording is discarded.
+    pub fn start_recording(&mut self) {
+        self.recording = Some((self.tick, InputRecording::new()));
+    }
+
+    /// Stops recording and returns recorded events.
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recording.take().map(|(_, recording)| recording)
+    }
+
+    /// Returns true if input is being recorded.
+    pub fn recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Records events of one frame, if recording was started.
+    pub fn record_events(&mut self, events: &[InputEvent]) {
+        if let Some((start_tick, recording)) = self.recording.as_mut() {
+            recording.record(self.tick - *start_tick, events.to_vec());
+        }
+    }
+
+    /// Starts replaying recorded events, which should be used in place of live input until replay finishes.
+    pub fn start_replay(&mut self, recording: InputRecording) {
+        self.replay = Some((self.tick, InputReplay::new(recording)));
+    }
+
+    /// Returns true if recorded events are being replayed.
+    pub fn replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Returns events of recorded frames which should be passed to the game before the next fixed update.
+    /// Replay stops after the last frame is returned.
+    pub fn replayed_frames(&mut self) -> Vec<Vec<InputEvent>> {
+        let (frames, finished) = match self.replay.as_mut() {
+            Some((start_tick, replay)) => (replay.frames_until(self.tick - *start_tick), replay.finished()),
+            None => return Vec::new(),
+        };
+        if finished {
+            info!("Input replay finished");
+            self.replay = None;
+        }
+        frames
+    }
 }
 
 /// Converts winit events to InputEvents
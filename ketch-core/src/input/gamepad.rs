@@ -0,0 +1,279 @@
+use crate::input::input_event::{InputEvent, ElementState, GamepadButton, GamepadStick, GamepadTrigger};
+
+use gilrs::{Gilrs, GilrsBuilder, Axis, Button, EventType};
+
+use std::collections::HashMap;
+
+use log::*;
+
+pub const DEFAULT_STICK_DEAD_ZONE: f32 = 0.15;
+pub const DEFAULT_TRIGGER_DEAD_ZONE: f32 = 0.05;
+/// Minimal d-pad axis value which presses d-pad button.
+const DPAD_AXIS_THRESHOLD: f32 = 0.5;
+
+/// Reads events of connected gamepads and converts them to InputEvents.
+/// Gamepads are identified by indices, which stay the same when a gamepad reconnects.
+pub struct Gamepads {
+    gilrs: Gilrs,
+    state: GamepadState,
+}
+
+impl Gamepads {
+    /// Returns None if gamepads aren't supported on the current platform.
+    pub fn new() -> Option<Self> {
+        // gilrs filters are disabled, so dead zones are applied only once
+        match GilrsBuilder::new().with_default_filters(false).build() {
+            Ok(gilrs) => {
+                for (id, gamepad) in gilrs.gamepads() {
+                    info!("Gamepad {} connected: {}", usize::from(id), gamepad.name());
+                }
+                Some(Gamepads { gilrs, state: GamepadState::new() })
+            },
+            Err(err) => {
+                error!("Couldn't initialize gamepads: {}", err);
+                None
+            },
+        }
+    }
+
+    pub fn set_stick_dead_zone(&mut self, dead_zone: f32) {
+        self.state.stick_dead_zone = dead_zone;
+    }
+
+    pub fn set_trigger_dead_zone(&mut self, dead_zone: f32) {
+        self.state.trigger_dead_zone = dead_zone;
+    }
+
+    /// Returns events received since the last call.
+    pub fn fetch_pending_events(&mut self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = self.gilrs.next_event() {
+            let gamepad = usize::from(event.id);
+            let input_event = match event.event {
+                EventType::Connected => {
+                    info!("Gamepad {} connected: {}", gamepad, self.gilrs.gamepad(event.id).name());
+                    Some(InputEvent::GamepadConnected { gamepad })
+                },
+                EventType::Disconnected => {
+                    info!("Gamepad {} disconnected", gamepad);
+                    self.state.reset(gamepad);
+                    Some(InputEvent::GamepadDisconnected { gamepad })
+                },
+                EventType::ButtonPressed(button, _) => to_gamepad_button(button).map(|button| {
+                    InputEvent::GamepadButton { gamepad, button, state: ElementState::Pressed }
+                }),
+                EventType::ButtonReleased(button, _) => to_gamepad_button(button).map(|button| {
+                    InputEvent::GamepadButton { gamepad, button, state: ElementState::Released }
+                }),
+                EventType::ButtonChanged(button, value, _) => self.state.button_changed(gamepad, button, value),
+                EventType::AxisChanged(axis @ Axis::DPadX, value, _) | EventType::AxisChanged(axis @ Axis::DPadY, value, _) => {
+                    events.extend(self.state.dpad_axis_changed(gamepad, axis, value));
+                    None
+                },
+                EventType::AxisChanged(axis, value, _) => self.state.axis_changed(gamepad, axis, value),
+                _ => None,
+            };
+            events.extend(input_event);
+        }
+        events
+    }
+}
+
+/// Last values of sticks and triggers, used to apply dead zones and skip changes hidden by them.
+/// Also keeps d-pad buttons pressed by d-pad axes.
+struct GamepadState {
+    sticks: HashMap<(usize, GamepadStick), (f32, f32)>,
+    triggers: HashMap<(usize, GamepadTrigger), f32>,
+    dpad_buttons: HashMap<(usize, Axis), GamepadButton>,
+    stick_dead_zone: f32,
+    trigger_dead_zone: f32,
+}
+
+impl GamepadState {
+    fn new() -> Self {
+        GamepadState {
+            sticks: HashMap::new(),
+            triggers: HashMap::new(),
+            dpad_buttons: HashMap::new(),
+            stick_dead_zone: DEFAULT_STICK_DEAD_ZONE,
+            trigger_dead_zone: DEFAULT_TRIGGER_DEAD_ZONE,
+        }
+    }
+
+    fn reset(&mut self, gamepad: usize) {
+        self.sticks.retain(|(stick_gamepad, _), _| *stick_gamepad != gamepad);
+        self.triggers.retain(|(trigger_gamepad, _), _| *trigger_gamepad != gamepad);
+        self.dpad_buttons.retain(|(dpad_gamepad, _), _| *dpad_gamepad != gamepad);
+    }
+
+    /// Returns event with both axes of the stick, as dead zone depends on both of them.
+    fn axis_changed(&mut self, gamepad: usize, axis: Axis, value: f32) -> Option<InputEvent> {
+        let stick = match axis {
+            Axis::LeftStickX | Axis::LeftStickY => GamepadStick::Left,
+            Axis::RightStickX | Axis::RightStickY => GamepadStick::Right,
+            _ => return None,
+        };
+
+        let raw = self.sticks.entry((gamepad, stick)).or_insert((0.0, 0.0));
+        let previous = apply_radial_dead_zone(*raw, self.stick_dead_zone);
+        match axis {
+            Axis::LeftStickX | Axis::RightStickX => raw.0 = value,
+            _ => raw.1 = value,
+        }
+
+        let (x, y) = apply_radial_dead_zone(*raw, self.stick_dead_zone);
+        if (x, y) == previous {
+            return None;
+        }
+        Some(InputEvent::GamepadStick { gamepad, stick, x, y })
+    }
+
+    /// Converts d-pad axis reported by some gamepads instead of d-pad buttons to d-pad button events.
+    /// Returns release of the previously pressed button followed by press of the new one.
+    fn dpad_axis_changed(&mut self, gamepad: usize, axis: Axis, value: f32) -> Vec<InputEvent> {
+        let button = match (axis, value) {
+            (Axis::DPadX, value) if value >= DPAD_AXIS_THRESHOLD => Some(GamepadButton::DPadRight),
+            (Axis::DPadX, value) if value <= -DPAD_AXIS_THRESHOLD => Some(GamepadButton::DPadLeft),
+            (Axis::DPadY, value) if value >= DPAD_AXIS_THRESHOLD => Some(GamepadButton::DPadUp),
+            (Axis::DPadY, value) if value <= -DPAD_AXIS_THRESHOLD => Some(GamepadButton::DPadDown),
+            _ => None,
+        };
+
+        let previous = match button {
+            Some(button) => self.dpad_buttons.insert((gamepad, axis), button),
+            None => self.dpad_buttons.remove(&(gamepad, axis)),
+        };
+        if previous == button {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        if let Some(button) = previous {
+            events.push(InputEvent::GamepadButton { gamepad, button, state: ElementState::Released });
+        }
+        if let Some(button) = button {
+            events.push(InputEvent::GamepadButton { gamepad, button, state: ElementState::Pressed });
+        }
+        events
+    }
+
+    fn button_changed(&mut self, gamepad: usize, button: Button, value: f32) -> Option<InputEvent> {
+        let trigger = match button {
+            Button::LeftTrigger2 => GamepadTrigger::Left,
+            Button::RightTrigger2 => GamepadTrigger::Right,
+            _ => return None,
+        };
+
+        let value = apply_dead_zone(value, self.trigger_dead_zone);
+        let previous = self.triggers.insert((gamepad, trigger), value);
+        if previous == Some(value) {
+            return None;
+        }
+        Some(InputEvent::GamepadTrigger { gamepad, trigger, value })
+    }
+}
+
+/// Converts gilrs button to gamepad button, unknown buttons don't have one.
+fn to_gamepad_button(button: Button) -> Option<GamepadButton> {
+    let button = match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::C => GamepadButton::C,
+        Button::Z => GamepadButton::Z,
+        Button::LeftTrigger => GamepadButton::LeftTrigger,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+        Button::RightTrigger => GamepadButton::RightTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger2,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftThumb,
+        Button::RightThumb => GamepadButton::RightThumb,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        Button::Unknown => return None,
+    };
+    Some(button)
+}
+
+/// Zeroes stick positions closer to the center than dead zone and rescales the rest,
+/// so values still start at 0 and end at 1. Whole stick position is used, so diagonals aren't snapped to axes.
+pub fn apply_radial_dead_zone(position: (f32, f32), dead_zone: f32) -> (f32, f32) {
+    let (x, y) = position;
+    let length = (x * x + y * y).sqrt();
+    if length <= dead_zone {
+        return (0.0, 0.0);
+    }
+    let scale = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) / length;
+    (x * scale, y * scale)
+}
+
+/// Zeroes values smaller than dead zone and rescales the rest to range from 0 to 1.
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        return 0.0;
+    }
+    value.signum() * ((value.abs() - dead_zone) / (1.0 - dead_zone)).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_stick_movement_is_ignored() {
+        assert_eq!((0.0, 0.0), apply_radial_dead_zone((0.1, -0.1), 0.15));
+        assert_eq!(0.0, apply_dead_zone(0.04, 0.05));
+    }
+
+    #[test]
+    fn values_outside_dead_zone_are_rescaled() {
+        let (x, y) = apply_radial_dead_zone((1.0, 0.0), 0.2);
+        assert!((x - 1.0).abs() < 0.0001 && y == 0.0);
+
+        let (x, _) = apply_radial_dead_zone((0.6, 0.0), 0.2);
+        assert!((x - 0.5).abs() < 0.0001);
+        assert!((apply_dead_zone(-0.6, 0.2) + 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn stick_event_has_both_axes_and_is_skipped_inside_dead_zone() {
+        let mut state = GamepadState::new();
+
+        assert!(state.axis_changed(0, Axis::LeftStickX, 0.1).is_none());
+        match state.axis_changed(0, Axis::LeftStickY, 1.0) {
+            Some(InputEvent::GamepadStick { gamepad: 0, stick: GamepadStick::Left, x, y }) => assert!(x > 0.0 && y > 0.9),
+            _ => panic!("moving stick outside of dead zone should send stick event"),
+        }
+        assert!(state.axis_changed(1, Axis::LeftZ, 1.0).is_none());
+    }
+
+    #[test]
+    fn gamepads_have_separate_trigger_values() {
+        let mut state = GamepadState::new();
+
+        assert!(state.button_changed(0, Button::LeftTrigger2, 0.5).is_some());
+        assert!(state.button_changed(0, Button::LeftTrigger2, 0.5).is_none());
+        assert!(state.button_changed(1, Button::LeftTrigger2, 0.5).is_some());
+        assert!(state.button_changed(1, Button::South, 1.0).is_none());
+    }
+
+    #[test]
+    fn dpad_axes_press_and_release_dpad_buttons() {
+        let mut state = GamepadState::new();
+        let button = |button, state| InputEvent::GamepadButton { gamepad: 0, button, state };
+
+        assert_eq!(vec![button(GamepadButton::DPadUp, ElementState::Pressed)], state.dpad_axis_changed(0, Axis::DPadY, 1.0));
+        assert!(state.dpad_axis_changed(0, Axis::DPadY, 1.0).is_empty());
+        assert_eq!(vec![button(GamepadButton::DPadLeft, ElementState::Pressed)], state.dpad_axis_changed(0, Axis::DPadX, -1.0));
+        assert_eq!(
+            vec![button(GamepadButton::DPadUp, ElementState::Released), button(GamepadButton::DPadDown, ElementState::Pressed)],
+            state.dpad_axis_changed(0, Axis::DPadY, -1.0)
+        );
+        assert_eq!(vec![button(GamepadButton::DPadLeft, ElementState::Released)], state.dpad_axis_changed(0, Axis::DPadX, 0.0));
+    }
+}
@@ -0,0 +1,26 @@
+use std::error::Error;
+use std::io;
+
+use quick_error::quick_error;
+
+quick_error! {
+    /// Error of loading or saving input files, like input maps and input recordings.
+    #[derive(Debug)]
+    pub enum InputFileError {
+        IoError(err: io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SerializationError(err: ron::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ParseError(err: ron::error::SpannedError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+    }
+}
@@ -1,17 +1,82 @@
-pub use winit::{Event, KeyboardInput, MouseScrollDelta, ElementState, MouseButton, AxisId, ButtonId, VirtualKeyCode};
+pub use winit::{Event, KeyboardInput, MouseScrollDelta, ElementState, MouseButton, AxisId, ButtonId, VirtualKeyCode, ModifiersState};
 pub use winit::dpi::LogicalPosition;
 pub use winit::WindowEvent;
 pub use winit::DeviceEvent;
 
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+/// Named gamepad buttons. Action buttons are named after their position, so they mean the same on every gamepad layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    C,
+    Z,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadTrigger {
+    Left,
+    Right,
+}
+
 /// Enum containing input events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum InputEvent {
-    KeyboardInput { state: ElementState, keycode: VirtualKeyCode },
+    /// Key press or release, with state of modifier keys at the time of the event.
+    KeyboardInput {
+        state: ElementState,
+        keycode: VirtualKeyCode,
+        #[serde(default)]
+        modifiers: ModifiersState,
+    },
+    /// Character typed by the user, already composed by the system from key presses or input method.
+    ReceivedCharacter(char),
+    /// Window gained (true) or lost (false) focus.
+    Focused(bool),
+    CursorEntered,
+    CursorLeft,
+    /// File is dragged over the window.
+    HoveredFile(PathBuf),
+    /// Dragged file left the window or dragging was cancelled.
+    HoveredFileCancelled,
+    DroppedFile(PathBuf),
     CursorMoved { x: f64, y: f64 },
     MouseMotion { delta: (f64, f64) },
     MouseWheel(MouseScrollDelta),
     MouseInput { button: MouseButton, state: ElementState },
     Motion { axis: AxisId, value: f64 },
     Button { button: ButtonId, state: ElementState },
+    GamepadConnected { gamepad: usize },
+    GamepadDisconnected { gamepad: usize },
+    GamepadButton { gamepad: usize, button: GamepadButton, state: ElementState },
+    /// Stick position with dead zone applied, both axes range from -1 to 1.
+    GamepadStick { gamepad: usize, stick: GamepadStick, x: f32, y: f32 },
+    /// Trigger value with dead zone applied, ranges from 0 to 1.
+    GamepadTrigger { gamepad: usize, trigger: GamepadTrigger, value: f32 },
 }
 
 /// Changes winit Events to InputEvents
@@ -22,13 +87,21 @@ pub fn to_input_event(event: Event) -> Option<InputEvent> {
                 KeyboardInput {
                     virtual_keycode: Some(key),
                     state,
+                    modifiers,
                     .. 
-                } => Some(InputEvent::KeyboardInput { state: state, keycode: key }),
+                } => Some(InputEvent::KeyboardInput { state: state, keycode: key, modifiers }),
                 _ => None,
             },
             WindowEvent::CursorMoved { position, .. } => Some(InputEvent::CursorMoved { x: position.x, y: position.y }),
             WindowEvent::MouseWheel { delta, .. } => Some(InputEvent::MouseWheel(delta)),
             WindowEvent::MouseInput { button, state, .. } => Some(InputEvent::MouseInput { button, state }),
+            WindowEvent::ReceivedCharacter(character) => Some(InputEvent::ReceivedCharacter(character)),
+            WindowEvent::Focused(focused) => Some(InputEvent::Focused(focused)),
+            WindowEvent::CursorEntered { .. } => Some(InputEvent::CursorEntered),
+            WindowEvent::CursorLeft { .. } => Some(InputEvent::CursorLeft),
+            WindowEvent::HoveredFile(path) => Some(InputEvent::HoveredFile(path)),
+            WindowEvent::HoveredFileCancelled => Some(InputEvent::HoveredFileCancelled),
+            WindowEvent::DroppedFile(path) => Some(InputEvent::DroppedFile(path)),
             _ => None,
         },
         Event::DeviceEvent { event, .. } => match event {
@@ -40,3 +113,50 @@ pub fn to_input_event(event: Event) -> Option<InputEvent> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::{WindowId, DeviceId};
+
+    fn window_event(event: WindowEvent) -> Event {
+        Event::WindowEvent { window_id: unsafe { WindowId::dummy() }, event }
+    }
+
+    fn device_id() -> DeviceId {
+        unsafe { DeviceId::dummy() }
+    }
+
+    #[test]
+    fn key_event_keeps_modifiers() {
+        let modifiers = ModifiersState { shift: true, ctrl: true, alt: false, logo: false };
+        let input = KeyboardInput { scancode: 0, state: ElementState::Pressed, virtual_keycode: Some(VirtualKeyCode::S), modifiers };
+
+        assert_eq!(Some(InputEvent::KeyboardInput { state: ElementState::Pressed, keycode: VirtualKeyCode::S, modifiers }),
+                   to_input_event(window_event(WindowEvent::KeyboardInput { device_id: device_id(), input })));
+    }
+
+    #[test]
+    fn key_event_without_keycode_is_dropped() {
+        let input = KeyboardInput { scancode: 0, state: ElementState::Pressed, virtual_keycode: None, modifiers: ModifiersState::default() };
+
+        assert_eq!(None, to_input_event(window_event(WindowEvent::KeyboardInput { device_id: device_id(), input })));
+    }
+
+    #[test]
+    fn text_and_focus_events_are_converted() {
+        assert_eq!(Some(InputEvent::ReceivedCharacter('ł')), to_input_event(window_event(WindowEvent::ReceivedCharacter('ł'))));
+        assert_eq!(Some(InputEvent::Focused(false)), to_input_event(window_event(WindowEvent::Focused(false))));
+        assert_eq!(Some(InputEvent::CursorEntered), to_input_event(window_event(WindowEvent::CursorEntered { device_id: device_id() })));
+        assert_eq!(Some(InputEvent::CursorLeft), to_input_event(window_event(WindowEvent::CursorLeft { device_id: device_id() })));
+    }
+
+    #[test]
+    fn file_drag_and_drop_events_are_converted() {
+        let path = PathBuf::from("data/crate.jpg");
+
+        assert_eq!(Some(InputEvent::HoveredFile(path.clone())), to_input_event(window_event(WindowEvent::HoveredFile(path.clone()))));
+        assert_eq!(Some(InputEvent::HoveredFileCancelled), to_input_event(window_event(WindowEvent::HoveredFileCancelled)));
+        assert_eq!(Some(InputEvent::DroppedFile(path.clone())), to_input_event(window_event(WindowEvent::DroppedFile(path))));
+    }
+}
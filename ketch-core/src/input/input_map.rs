@@ -0,0 +1,297 @@
+use crate::input::input_event::{VirtualKeyCode, MouseButton, AxisId, ButtonId, GamepadButton, GamepadStick, GamepadTrigger};
+use crate::input::input_error::InputFileError;
+use crate::input::input_state::InputState;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Key or button which can be bound to an action or axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    MouseButton(MouseButton),
+    /// Button of a device other than keyboard and mouse.
+    Button(ButtonId),
+    /// Button of any connected gamepad.
+    GamepadButton(GamepadButton),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MouseAxis {
+    X,
+    Y,
+}
+
+/// Source of axis value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// Gives -1 when negative binding is pressed, 1 when positive one is pressed and 0 when both or none are.
+    Buttons { negative: Binding, positive: Binding },
+    /// Last value reported by device axis, like a joystick, multiplied by scale.
+    Motion { axis: AxisId, scale: f32 },
+    /// Mouse movement along the axis in the last frame, multiplied by scale.
+    MouseMotion { axis: MouseAxis, scale: f32 },
+    /// Horizontal position of gamepad stick, multiplied by scale.
+    GamepadStickX { stick: GamepadStick, scale: f32 },
+    /// Vertical position of gamepad stick, multiplied by scale.
+    GamepadStickY { stick: GamepadStick, scale: f32 },
+    /// Value of gamepad trigger, multiplied by scale.
+    GamepadTrigger { trigger: GamepadTrigger, scale: f32 },
+}
+
+/// Binds named actions and axes to keys, buttons and device axes, so games don't depend on concrete keys
+/// and bindings can be changed in a config file.
+/// Bindings are checked against input state passed to queries, usually the one returned by `InputSystem::state`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputMap {
+    #[serde(default)]
+    actions: BTreeMap<String, Vec<Binding>>,
+    #[serde(default)]
+    axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl InputMap {
+    /// Creates input map without any bindings.
+    pub fn new() -> Self {
+        InputMap::default()
+    }
+
+    /// Loads bindings from RON config file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputFileError> {
+        let content = std::fs::read_to_string(path)?;
+        InputMap::from_ron(&content)
+    }
+
+    /// Saves bindings to RON config file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), InputFileError> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Serializes bindings to RON.
+    pub fn to_ron(&self) -> Result<String, InputFileError> {
+        Ok(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?)
+    }
+
+    /// Deserializes bindings from RON.
+    pub fn from_ron(content: &str) -> Result<Self, InputFileError> {
+        Ok(ron::de::from_str(content)?)
+    }
+
+    /// Binds action to key or button. Action can have many bindings, pressing any of them activates it.
+    pub fn bind_action(&mut self, action: &str, binding: Binding) {
+        self.actions.entry(action.to_string()).or_insert_with(Vec::new).push(binding);
+    }
+
+    /// Binds axis to buttons or device axis.
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        self.axes.entry(axis.to_string()).or_insert_with(Vec::new).push(binding);
+    }
+
+    /// Removes all bindings of the action.
+    pub fn unbind_action(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    /// Removes all bindings of the axis.
+    pub fn unbind_axis(&mut self, axis: &str) {
+        self.axes.remove(axis);
+    }
+
+    /// Returns bindings of the action.
+    pub fn action_bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(|bindings| bindings.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns bindings of the axis.
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map(|bindings| bindings.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns true if any binding of the action is held down.
+    pub fn pressed(&self, state: &InputState, action: &str) -> bool {
+        self.action_bindings(action).iter().any(|binding| binding_down(state, binding))
+    }
+
+    /// Returns true if any binding of the action was pressed since the last update of input state.
+    pub fn just_pressed(&self, state: &InputState, action: &str) -> bool {
+        self.action_bindings(action).iter().any(|binding| match binding {
+            Binding::Key(keycode) => state.was_pressed(*keycode),
+            Binding::MouseButton(button) => state.was_mouse_button_pressed(*button),
+            Binding::Button(button) => state.was_button_pressed(*button),
+            Binding::GamepadButton(button) => state.was_gamepad_button_pressed(*button),
+        })
+    }
+
+    /// Returns true if any binding of the action was released since the last update of input state.
+    pub fn just_released(&self, state: &InputState, action: &str) -> bool {
+        self.action_bindings(action).iter().any(|binding| match binding {
+            Binding::Key(keycode) => state.was_released(*keycode),
+            Binding::MouseButton(button) => state.was_mouse_button_released(*button),
+            Binding::Button(button) => state.was_button_released(*button),
+            Binding::GamepadButton(button) => state.was_gamepad_button_released(*button),
+        })
+    }
+
+    /// Returns value of the axis. When more bindings of the axis are active, the largest value by magnitude is returned.
+    pub fn axis(&self, state: &InputState, axis: &str) -> f32 {
+        largest_by_magnitude(self.axis_bindings(axis).iter().map(|binding| axis_binding_value(state, binding)))
+    }
+}
+
+fn binding_down(state: &InputState, binding: &Binding) -> bool {
+    match binding {
+        Binding::Key(keycode) => state.is_key_down(*keycode),
+        Binding::MouseButton(button) => state.is_mouse_button_down(*button),
+        Binding::Button(button) => state.is_button_down(*button),
+        Binding::GamepadButton(button) => state.is_gamepad_button_down(*button),
+    }
+}
+
+fn axis_binding_value(state: &InputState, binding: &AxisBinding) -> f32 {
+    match binding {
+        AxisBinding::Buttons { negative, positive } => {
+            let negative = if binding_down(state, negative) { 1.0 } else { 0.0 };
+            let positive = if binding_down(state, positive) { 1.0 } else { 0.0 };
+            positive - negative
+        },
+        AxisBinding::Motion { axis, scale } => state.motion(*axis) as f32 * scale,
+        AxisBinding::MouseMotion { axis: MouseAxis::X, scale } => state.mouse_delta().0 as f32 * scale,
+        AxisBinding::MouseMotion { axis: MouseAxis::Y, scale } => state.mouse_delta().1 as f32 * scale,
+        // any connected gamepad can control the axis
+        AxisBinding::GamepadStickX { stick, scale } => {
+            largest_by_magnitude(state.gamepad_stick_positions(*stick).map(|(x, _)| x)) * scale
+        },
+        AxisBinding::GamepadStickY { stick, scale } => {
+            largest_by_magnitude(state.gamepad_stick_positions(*stick).map(|(_, y)| y)) * scale
+        },
+        AxisBinding::GamepadTrigger { trigger, scale } => {
+            largest_by_magnitude(state.gamepad_trigger_values(*trigger)) * scale
+        },
+    }
+}
+
+fn largest_by_magnitude<I: Iterator<Item = f32>>(values: I) -> f32 {
+    values.fold(0.0, |largest, value| if value.abs() > largest.abs() { value } else { largest })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::input_event::{InputEvent, ElementState, ModifiersState};
+
+    fn key_event(keycode: VirtualKeyCode, state: ElementState) -> InputEvent {
+        InputEvent::KeyboardInput { keycode, state, modifiers: ModifiersState::default() }
+    }
+
+    fn movement_map() -> InputMap {
+        let mut input_map = InputMap::new();
+        input_map.bind_action("jump", Binding::Key(VirtualKeyCode::Space));
+        input_map.bind_action("jump", Binding::MouseButton(MouseButton::Right));
+        input_map.bind_axis("move_x", AxisBinding::Buttons { negative: Binding::Key(VirtualKeyCode::A), positive: Binding::Key(VirtualKeyCode::D) });
+        input_map.bind_axis("move_x", AxisBinding::Motion { axis: 0, scale: 1.0 });
+        input_map.bind_axis("look_x", AxisBinding::MouseMotion { axis: MouseAxis::X, scale: 0.5 });
+        input_map
+    }
+
+    #[test]
+    fn action_is_just_pressed_only_until_the_next_update() {
+        let input_map = movement_map();
+        let mut input_state = InputState::new();
+
+        input_state.update(&[key_event(VirtualKeyCode::Space, ElementState::Pressed)]);
+        assert!(input_map.pressed(&input_state, "jump") && input_map.just_pressed(&input_state, "jump"));
+
+        // repeated key press doesn't start a new press
+        input_state.update(&[key_event(VirtualKeyCode::Space, ElementState::Pressed)]);
+        assert!(input_map.pressed(&input_state, "jump") && !input_map.just_pressed(&input_state, "jump"));
+
+        input_state.update(&[key_event(VirtualKeyCode::Space, ElementState::Released)]);
+        assert!(!input_map.pressed(&input_state, "jump") && input_map.just_released(&input_state, "jump"));
+
+        input_state.update(&[]);
+        assert!(!input_map.just_released(&input_state, "jump"));
+    }
+
+    #[test]
+    fn any_binding_activates_action() {
+        let input_map = movement_map();
+        let mut input_state = InputState::new();
+
+        input_state.update(&[InputEvent::MouseInput { button: MouseButton::Right, state: ElementState::Pressed }]);
+        assert!(input_map.pressed(&input_state, "jump"));
+        assert!(!input_map.pressed(&input_state, "missing_action"));
+    }
+
+    #[test]
+    fn axis_uses_binding_with_largest_value() {
+        let input_map = movement_map();
+        let mut input_state = InputState::new();
+
+        input_state.update(&[key_event(VirtualKeyCode::A, ElementState::Pressed), InputEvent::Motion { axis: 0, value: 0.5 }]);
+        assert_eq!(-1.0, input_map.axis(&input_state, "move_x"));
+
+        input_state.update(&[key_event(VirtualKeyCode::D, ElementState::Pressed)]);
+        assert_eq!(0.5, input_map.axis(&input_state, "move_x"));
+    }
+
+    #[test]
+    fn mouse_motion_is_summed_until_the_next_update() {
+        let input_map = movement_map();
+        let mut input_state = InputState::new();
+
+        input_state.update(&[InputEvent::MouseMotion { delta: (2.0, 1.0) }, InputEvent::MouseMotion { delta: (4.0, 0.0) }]);
+        assert_eq!(3.0, input_map.axis(&input_state, "look_x"));
+
+        input_state.update(&[]);
+        assert_eq!(0.0, input_map.axis(&input_state, "look_x"));
+    }
+
+    #[test]
+    fn gamepad_bindings_use_any_gamepad() {
+        let mut input_map = movement_map();
+        let mut input_state = InputState::new();
+        input_map.bind_action("jump", Binding::GamepadButton(GamepadButton::South));
+        input_map.bind_axis("move_x", AxisBinding::GamepadStickX { stick: GamepadStick::Left, scale: 1.0 });
+
+        input_state.update(&[
+            InputEvent::GamepadButton { gamepad: 1, button: GamepadButton::South, state: ElementState::Pressed },
+            InputEvent::GamepadStick { gamepad: 0, stick: GamepadStick::Left, x: 0.2, y: 0.0 },
+            InputEvent::GamepadStick { gamepad: 1, stick: GamepadStick::Left, x: -0.8, y: 0.0 },
+        ]);
+        assert!(input_map.just_pressed(&input_state, "jump"));
+        assert_eq!(-0.8, input_map.axis(&input_state, "move_x"));
+
+        input_state.update(&[InputEvent::GamepadDisconnected { gamepad: 1 }]);
+        assert_eq!(0.2, input_map.axis(&input_state, "move_x"));
+    }
+
+    #[test]
+    fn disconnecting_gamepad_releases_its_buttons() {
+        let mut input_map = movement_map();
+        let mut input_state = InputState::new();
+        input_map.bind_action("jump", Binding::GamepadButton(GamepadButton::South));
+        let press = |gamepad| InputEvent::GamepadButton { gamepad, button: GamepadButton::South, state: ElementState::Pressed };
+
+        input_state.update(&[press(0)]);
+        input_state.update(&[InputEvent::GamepadDisconnected { gamepad: 0 }]);
+        assert!(!input_map.pressed(&input_state, "jump") && input_map.just_released(&input_state, "jump"));
+
+        // button held on another gamepad keeps the action pressed
+        input_state.update(&[press(0), press(1)]);
+        input_state.update(&[InputEvent::GamepadDisconnected { gamepad: 0 }]);
+        assert!(input_map.pressed(&input_state, "jump") && !input_map.just_released(&input_state, "jump"));
+    }
+
+    #[test]
+    fn bindings_round_trip_through_ron() {
+        let input_map = movement_map();
+        let loaded = InputMap::from_ron(&input_map.to_ron().unwrap()).unwrap();
+
+        assert_eq!(input_map.action_bindings("jump"), loaded.action_bindings("jump"));
+        assert_eq!(input_map.axis_bindings("move_x"), loaded.axis_bindings("move_x"));
+        assert_eq!(input_map.axis_bindings("look_x"), loaded.axis_bindings("look_x"));
+    }
+}
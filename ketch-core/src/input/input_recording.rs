@@ -0,0 +1,114 @@
+use crate::input::input_event::InputEvent;
+use crate::input::input_error::InputFileError;
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+/// Input events passed to the game in one frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Index of the fixed update which runs after the events, counted from the start of the recording.
+    pub tick: u64,
+    pub events: Vec<InputEvent>,
+}
+
+/// Stream of input events with fixed update indices, which can be saved and replayed later.
+/// Every frame is recorded, even without events, so replayed games get the same sequence of calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Creates empty recording.
+    pub fn new() -> Self {
+        InputRecording::default()
+    }
+
+    /// Loads recording from RON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputFileError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&content)?)
+    }
+
+    /// Saves recording to RON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), InputFileError> {
+        std::fs::write(path, ron::ser::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Adds frame at the end of the recording.
+    pub fn record(&mut self, tick: u64, events: Vec<InputEvent>) {
+        self.frames.push(RecordedFrame { tick, events });
+    }
+
+    /// Returns a reference to slice of all recorded frames.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+}
+
+/// Returns recorded frames in place of live input, following fixed updates of the game.
+#[derive(Debug, Clone)]
+pub struct InputReplay {
+    recording: InputRecording,
+    next_frame: usize,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> Self {
+        InputReplay {
+            recording,
+            next_frame: 0,
+        }
+    }
+
+    /// Returns events of frames recorded before the update with given tick, which weren't returned yet.
+    pub fn frames_until(&mut self, tick: u64) -> Vec<Vec<InputEvent>> {
+        let frames = &self.recording.frames[self.next_frame..];
+        let count = frames.iter().take_while(|frame| frame.tick <= tick).count();
+        self.next_frame += count;
+        frames[..count].iter().map(|frame| frame.events.clone()).collect()
+    }
+
+    /// Returns true if all recorded frames were returned.
+    pub fn finished(&self) -> bool {
+        self.next_frame >= self.recording.frames.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::input_event::{ElementState, VirtualKeyCode, ModifiersState};
+
+    fn key_event(state: ElementState) -> InputEvent {
+        InputEvent::KeyboardInput { keycode: VirtualKeyCode::Space, state, modifiers: ModifiersState::default() }
+    }
+
+    #[test]
+    fn replay_returns_frames_up_to_tick_in_order() {
+        let mut recording = InputRecording::new();
+        recording.record(0, vec![key_event(ElementState::Pressed)]);
+        recording.record(0, vec![]);
+        recording.record(2, vec![key_event(ElementState::Released)]);
+        let mut replay = InputReplay::new(recording);
+
+        assert_eq!(vec![vec![key_event(ElementState::Pressed)], vec![]], replay.frames_until(0));
+        assert!(replay.frames_until(1).is_empty());
+        assert!(!replay.finished());
+        assert_eq!(vec![vec![key_event(ElementState::Released)]], replay.frames_until(2));
+        assert!(replay.finished());
+    }
+
+    #[test]
+    fn recording_round_trips_through_ron() {
+        let mut recording = InputRecording::new();
+        recording.record(3, vec![key_event(ElementState::Pressed), InputEvent::MouseMotion { delta: (1.5, -2.0) }]);
+
+        let serialized = ron::ser::to_string(&recording).unwrap();
+        let loaded: InputRecording = ron::de::from_str(&serialized).unwrap();
+        assert_eq!(recording, loaded);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::input::input_event::{InputEvent, ElementState, VirtualKeyCode, MouseButton, MouseScrollDelta, AxisId, ButtonId};
+use crate::input::input_event::{GamepadButton, GamepadStick, GamepadTrigger};
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Buttons held down, pressed and released since changes were last cleared.
+#[derive(Debug, Clone)]
+struct ButtonStates<T: Eq + Hash> {
+    down: HashSet<T>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+
+impl<T: Eq + Hash + Copy> ButtonStates<T> {
+    fn new() -> Self {
+        ButtonStates {
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+
+    fn update(&mut self, button: T, state: ElementState) {
+        // held keys repeat pressed events, which aren't new presses
+        match state {
+            ElementState::Pressed => if self.down.insert(button) {
+                self.pressed.insert(button);
+            },
+            ElementState::Released => if self.down.remove(&button) {
+                self.released.insert(button);
+            },
+        }
+    }
+
+    /// Releases held buttons matching the predicate, used when their releases won't be received.
+    fn release_where<F: Fn(&T) -> bool>(&mut self, predicate: F) {
+        let released: Vec<T> = self.down.iter().filter(|button| predicate(button)).cloned().collect();
+        for button in released {
+            self.update(button, ElementState::Released);
+        }
+    }
+
+    fn clear_changes(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+}
+
+impl<T: Eq + Hash + Copy> Default for ButtonStates<T> {
+    fn default() -> Self {
+        ButtonStates::new()
+    }
+}
+
+/// State of keyboard, mouse and gamepads, so games can poll it instead of tracking events.
+/// Presses, releases, mouse movement and scrolling are summed since the last update, that is until `update`
+/// or `clear_changes` is called. `InputSystem` clears them after every fixed update of the game.
+#[derive(Debug, Clone, Default)]
+pub struct InputState {
+    keys: ButtonStates<VirtualKeyCode>,
+    mouse_buttons: ButtonStates<MouseButton>,
+    buttons: ButtonStates<ButtonId>,
+    gamepad_buttons: ButtonStates<(usize, GamepadButton)>,
+    cursor_position: (f64, f64),
+    mouse_delta: (f64, f64),
+    scroll_lines: (f32, f32),
+    scroll_pixels: (f64, f64),
+    motion_values: HashMap<AxisId, f64>,
+    gamepad_sticks: HashMap<(usize, GamepadStick), (f32, f32)>,
+    gamepad_triggers: HashMap<(usize, GamepadTrigger), f32>,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        InputState::default()
+    }
+
+    /// Clears changes from previous events and updates state with the new ones.
+    pub fn update(&mut self, input_events: &[InputEvent]) {
+        self.clear_changes();
+        self.add_events(input_events);
+    }
+
+    /// Clears presses, releases, mouse movement and scrolling. Held buttons and axis values are kept.
+    pub fn clear_changes(&mut self) {
+        self.keys.clear_changes();
+        self.mouse_buttons.clear_changes();
+        self.buttons.clear_changes();
+        self.gamepad_buttons.clear_changes();
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll_lines = (0.0, 0.0);
+        self.scroll_pixels = (0.0, 0.0);
+    }
+
+    /// Updates state with input events, keeping changes which weren't cleared yet.
+    pub fn add_events(&mut self, input_events: &[InputEvent]) {
+        for event in input_events {
+            match event {
+                InputEvent::KeyboardInput { keycode, state, .. } => self.keys.update(*keycode, *state),
+                InputEvent::MouseInput { button, state } => self.mouse_buttons.update(*button, *state),
+                InputEvent::Button { button, state } => self.buttons.update(*button, *state),
+                InputEvent::GamepadButton { gamepad, button, state } => self.gamepad_buttons.update((*gamepad, *button), *state),
+                InputEvent::CursorMoved { x, y } => self.cursor_position = (*x, *y),
+                InputEvent::MouseMotion { delta } => {
+                    self.mouse_delta.0 += delta.0;
+                    self.mouse_delta.1 += delta.1;
+                },
+                InputEvent::MouseWheel(MouseScrollDelta::LineDelta(x, y)) => {
+                    self.scroll_lines.0 += x;
+                    self.scroll_lines.1 += y;
+                },
+                InputEvent::MouseWheel(MouseScrollDelta::PixelDelta(position)) => {
+                    self.scroll_pixels.0 += position.x;
+                    self.scroll_pixels.1 += position.y;
+                },
+                InputEvent::Motion { axis, value } => {
+                    self.motion_values.insert(*axis, *value);
+                },
+                InputEvent::GamepadStick { gamepad, stick, x, y } => {
+                    self.gamepad_sticks.insert((*gamepad, *stick), (*x, *y));
+                },
+                InputEvent::GamepadTrigger { gamepad, trigger, value } => {
+                    self.gamepad_triggers.insert((*gamepad, *trigger), *value);
+                },
+                // releases aren't received when window isn't focused, so keys would stay down
+                InputEvent::Focused(false) => {
+                    self.keys.release_where(|_| true);
+                    self.mouse_buttons.release_where(|_| true);
+                    self.buttons.release_where(|_| true);
+                },
+                // buttons held on unplugged gamepad won't be released by events
+                InputEvent::GamepadDisconnected { gamepad } => {
+                    self.gamepad_buttons.release_where(|(button_gamepad, _)| button_gamepad == gamepad);
+                    self.gamepad_sticks.retain(|(stick_gamepad, _), _| stick_gamepad != gamepad);
+                    self.gamepad_triggers.retain(|(trigger_gamepad, _), _| trigger_gamepad != gamepad);
+                },
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns true if the key is held down.
+    pub fn is_key_down(&self, keycode: VirtualKeyCode) -> bool {
+        self.keys.down.contains(&keycode)
+    }
+
+    /// Returns true if the key was pressed since the last update.
+    pub fn was_pressed(&self, keycode: VirtualKeyCode) -> bool {
+        self.keys.pressed.contains(&keycode)
+    }
+
+    /// Returns true if the key was released since the last update.
+    pub fn was_released(&self, keycode: VirtualKeyCode) -> bool {
+        self.keys.released.contains(&keycode)
+    }
+
+    /// Returns true if the mouse button is held down.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.down.contains(&button)
+    }
+
+    /// Returns true if the mouse button was pressed since the last update.
+    pub fn was_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.pressed.contains(&button)
+    }
+
+    /// Returns true if the mouse button was released since the last update.
+    pub fn was_mouse_button_released(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.released.contains(&button)
+    }
+
+    /// Returns true if the button of a device other than keyboard and mouse is held down.
+    pub fn is_button_down(&self, button: ButtonId) -> bool {
+        self.buttons.down.contains(&button)
+    }
+
+    /// Returns true if the button of a device other than keyboard and mouse was pressed since the last update.
+    pub fn was_button_pressed(&self, button: ButtonId) -> bool {
+        self.buttons.pressed.contains(&button)
+    }
+
+    /// Returns true if the button of a device other than keyboard and mouse was released since the last update.
+    pub fn was_button_released(&self, button: ButtonId) -> bool {
+        self.buttons.released.contains(&button)
+    }
+
+    /// Returns true if the button is held down on any gamepad.
+    pub fn is_gamepad_button_down(&self, button: GamepadButton) -> bool {
+        self.gamepad_buttons.down.iter().any(|(_, down_button)| *down_button == button)
+    }
+
+    /// Returns true if the button was pressed on any gamepad since the last update.
+    pub fn was_gamepad_button_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepad_buttons.pressed.iter().any(|(_, pressed_button)| *pressed_button == button)
+    }
+
+    /// Returns true if the button was released since the last update and isn't held on any other gamepad.
+    pub fn was_gamepad_button_released(&self, button: GamepadButton) -> bool {
+        self.gamepad_buttons.released.iter().any(|(_, released_button)| *released_button == button)
+            && !self.is_gamepad_button_down(button)
+    }
+
+    /// Returns the last cursor position in the window.
+    pub fn cursor_position(&self) -> (f64, f64) {
+        self.cursor_position
+    }
+
+    /// Returns mouse movement summed since the last update.
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.mouse_delta
+    }
+
+    /// Returns scrolling in lines summed since the last update, reported by most mouse wheels.
+    pub fn scroll_lines(&self) -> (f32, f32) {
+        self.scroll_lines
+    }
+
+    /// Returns scrolling in pixels summed since the last update, reported by touchpads.
+    pub fn scroll_pixels(&self) -> (f64, f64) {
+        self.scroll_pixels
+    }
+
+    /// Returns the last value reported by device axis, like a joystick.
+    pub fn motion(&self, axis: AxisId) -> f64 {
+        self.motion_values.get(&axis).cloned().unwrap_or(0.0)
+    }
+
+    /// Returns position of the stick of the gamepad.
+    pub fn gamepad_stick(&self, gamepad: usize, stick: GamepadStick) -> (f32, f32) {
+        self.gamepad_sticks.get(&(gamepad, stick)).cloned().unwrap_or((0.0, 0.0))
+    }
+
+    /// Returns positions of the stick on all gamepads which reported it.
+    pub fn gamepad_stick_positions(&self, stick: GamepadStick) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.gamepad_sticks.iter().filter(move |((_, s), _)| *s == stick).map(|(_, position)| *position)
+    }
+
+    /// Returns value of the trigger of the gamepad.
+    pub fn gamepad_trigger(&self, gamepad: usize, trigger: GamepadTrigger) -> f32 {
+        self.gamepad_triggers.get(&(gamepad, trigger)).cloned().unwrap_or(0.0)
+    }
+
+    /// Returns values of the trigger on all gamepads which reported it.
+    pub fn gamepad_trigger_values(&self, trigger: GamepadTrigger) -> impl Iterator<Item = f32> + '_ {
+        self.gamepad_triggers.iter().filter(move |((_, t), _)| *t == trigger).map(|(_, value)| *value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::input_event::{ModifiersState, LogicalPosition};
+
+    fn key_event(keycode: VirtualKeyCode, state: ElementState) -> InputEvent {
+        InputEvent::KeyboardInput { keycode, state, modifiers: ModifiersState::default() }
+    }
+
+    #[test]
+    fn key_is_pressed_only_until_changes_are_cleared() {
+        let mut input_state = InputState::new();
+
+        input_state.update(&[key_event(VirtualKeyCode::W, ElementState::Pressed)]);
+        assert!(input_state.is_key_down(VirtualKeyCode::W) && input_state.was_pressed(VirtualKeyCode::W));
+
+        input_state.update(&[key_event(VirtualKeyCode::W, ElementState::Pressed)]);
+        assert!(input_state.is_key_down(VirtualKeyCode::W) && !input_state.was_pressed(VirtualKeyCode::W));
+
+        input_state.update(&[key_event(VirtualKeyCode::W, ElementState::Released)]);
+        assert!(!input_state.is_key_down(VirtualKeyCode::W) && input_state.was_released(VirtualKeyCode::W));
+    }
+
+    #[test]
+    fn changes_from_many_frames_are_kept_until_cleared() {
+        let mut input_state = InputState::new();
+
+        input_state.add_events(&[key_event(VirtualKeyCode::W, ElementState::Pressed), InputEvent::MouseMotion { delta: (1.0, 0.0) }]);
+        input_state.add_events(&[InputEvent::MouseMotion { delta: (2.0, 0.0) }]);
+        assert!(input_state.was_pressed(VirtualKeyCode::W));
+        assert_eq!((3.0, 0.0), input_state.mouse_delta());
+
+        input_state.clear_changes();
+        assert!(input_state.is_key_down(VirtualKeyCode::W) && !input_state.was_pressed(VirtualKeyCode::W));
+        assert_eq!((0.0, 0.0), input_state.mouse_delta());
+    }
+
+    #[test]
+    fn mouse_movement_and_scroll_are_summed() {
+        let mut input_state = InputState::new();
+
+        input_state.update(&[
+            InputEvent::MouseMotion { delta: (1.0, 2.0) },
+            InputEvent::MouseMotion { delta: (3.0, -1.0) },
+            InputEvent::MouseWheel(MouseScrollDelta::LineDelta(0.0, 1.0)),
+            InputEvent::MouseWheel(MouseScrollDelta::LineDelta(0.0, 2.0)),
+            InputEvent::MouseWheel(MouseScrollDelta::PixelDelta(LogicalPosition::new(4.0, 0.0))),
+            InputEvent::CursorMoved { x: 10.0, y: 20.0 },
+        ]);
+        assert_eq!((4.0, 1.0), input_state.mouse_delta());
+        assert_eq!((0.0, 3.0), input_state.scroll_lines());
+        assert_eq!((4.0, 0.0), input_state.scroll_pixels());
+
+        input_state.update(&[]);
+        assert_eq!((0.0, 0.0), input_state.mouse_delta());
+        assert_eq!((10.0, 20.0), input_state.cursor_position());
+    }
+
+    #[test]
+    fn losing_focus_releases_everything() {
+        let mut input_state = InputState::new();
+
+        input_state.update(&[key_event(VirtualKeyCode::A, ElementState::Pressed), InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed }]);
+        input_state.update(&[InputEvent::Focused(false)]);
+        assert!(!input_state.is_key_down(VirtualKeyCode::A) && input_state.was_released(VirtualKeyCode::A));
+        assert!(!input_state.is_mouse_button_down(MouseButton::Left) && input_state.was_mouse_button_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn disconnecting_gamepad_releases_only_its_buttons() {
+        let mut input_state = InputState::new();
+        let press = |gamepad| InputEvent::GamepadButton { gamepad, button: GamepadButton::South, state: ElementState::Pressed };
+
+        input_state.update(&[press(0), InputEvent::GamepadStick { gamepad: 0, stick: GamepadStick::Left, x: 1.0, y: 0.0 }]);
+        input_state.update(&[InputEvent::GamepadDisconnected { gamepad: 0 }]);
+        assert!(!input_state.is_gamepad_button_down(GamepadButton::South));
+        assert!(input_state.was_gamepad_button_released(GamepadButton::South));
+        assert_eq!((0.0, 0.0), input_state.gamepad_stick(0, GamepadStick::Left));
+
+        input_state.update(&[press(0), press(1)]);
+        input_state.update(&[InputEvent::GamepadDisconnected { gamepad: 0 }]);
+        assert!(input_state.is_gamepad_button_down(GamepadButton::South));
+        assert!(!input_state.was_gamepad_button_released(GamepadButton::South));
+    }
+}
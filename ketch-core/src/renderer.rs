@@ -2,6 +2,8 @@ pub mod queues;
 mod uniform_manager;
 pub mod shader;
 pub mod renderer_error;
+pub mod headless;
+pub mod shadow;
 
 use winit::dpi::PhysicalSize;
 use vulkano::swapchain::SwapchainAcquireFuture;
@@ -18,19 +20,22 @@ use vulkano::device::QueuesIter;
 use vulkano::instance::QueueFamily;
 use vulkano::image::attachment::AttachmentImage;
 use crate::resource::AssetManager;
+use crate::resource::material::Material;
+use crate::resource::mesh::Mesh;
+use crate::resource::texture::Texture;
 use std::cell::RefCell;
 use std::rc::Rc;
 use log::*;
 
 use crate::settings::Settings;
 
-use vulkano::instance::{Instance, InstanceCreationError, PhysicalDevice, PhysicalDeviceType, PhysicalDevicesIter};
+use vulkano::instance::{Instance, InstanceCreationError, InstanceExtensions, PhysicalDevice, PhysicalDeviceType, PhysicalDevicesIter};
 use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
 use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
-use vulkano::device::{Device};
+use vulkano::device::{Device, DeviceExtensions};
 use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
 use vulkano::pipeline::viewport::Viewport;
-use vulkano::image::SwapchainImage;
+use vulkano::image::{ImageViewAccess, SwapchainImage};
 use vulkano::swapchain::{Surface, PresentMode, Swapchain, SurfaceTransform, CompositeAlpha};
 use vulkano::single_pass_renderpass;
 use vulkano::framebuffer::{RenderPassAbstract, Framebuffer, FramebufferAbstract, Subpass};
@@ -42,11 +47,15 @@ use vulkano::swapchain;
 
 use vulkano_win::VkSurfaceBuild;
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
 
 use crate::renderer::queues::Queues;
 use crate::renderer::uniform_manager::UniformManager;
 use crate::renderer::shader::ShaderSet;
+use crate::renderer::shadow::ShadowMap;
+
+use nalgebra_glm::Mat4;
 
 /// Top level struct of vulkan renderer.
 pub struct Renderer {
@@ -61,6 +70,10 @@ pub struct Renderer {
     render_pass: Arc<RenderPassAbstract + Send + Sync>,
     pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
     framebuffers: Vec<Arc<FramebufferAbstract + Send + Sync>>,
+    shadow_map: ShadowMap,
+    default_material: Material,
+    render_stats: RenderStats,
+    highlighted_object: Option<u32>,
 
     recreate_swapchain: bool,
     previous_frame: Option<Box<GpuFuture>>,
@@ -81,19 +94,25 @@ impl Renderer {
 
         let physical_queues = queues::find_queues(physical_device, &surface);
 
-        let (device, queues) = create_logical_device(physical_device, &physical_queues)?;
+        let device_extensions = DeviceExtensions {
+            khr_swapchain: true,
+            .. DeviceExtensions::none()
+        };
+
+        let (device, queues) = create_logical_device(physical_device, &physical_queues, &device_extensions)?;
 
         let queues = Queues::new(queues);
 
         let (swapchain, images) = create_swapchain(surface.clone(), physical_device, device.clone(), &queues)?;
 
-        let uniform_manager = UniformManager::new(device.clone());
+        let uniform_manager = UniformManager::new(device.clone(), settings.max_lights());
         let shader_set = Rc::new(ShaderSet::load(device.clone()));
 
         let render_pass = create_renderpass(device.clone(), swapchain.format())?;
 
-        let pipeline = create_pipeline(device.clone(), shader_set.clone(), &images, render_pass.clone())?;
-        let framebuffers = create_framebuffers(device.clone(), &images, render_pass.clone())?;
+        let pipeline = create_pipeline(device.clone(), shader_set.clone(), swapchain.dimensions(), render_pass.clone())?;
+        let framebuffers = create_framebuffers(device.clone(), swapchain.dimensions(), &images, render_pass.clone())?;
+        let shadow_map = ShadowMap::new(device.clone(), &shader_set, settings.shadow_map_resolution())?;
 
         Ok(Renderer {
             instance,
@@ -107,6 +126,10 @@ impl Renderer {
             render_pass,
             pipeline,
             framebuffers,
+            shadow_map,
+            default_material: Material::new("default"),
+            render_stats: RenderStats::default(),
+            highlighted_object: None,
             recreate_swapchain: false,
             previous_frame: None,
         })
@@ -173,54 +196,19 @@ impl Renderer {
     }
 
     /// Adds commands used to draw current scene to command buffer.
-    fn add_scene_commands(&mut self, mut command_buffer: AutoCommandBufferBuilder, image_num: usize, asset_manager: &mut AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
-        command_buffer = command_buffer.begin_render_pass(
-            self.framebuffers[image_num].clone(), false,
-            vec![
-                [0.0, 0.0, 0.0, 1.0].into(),
-                1f32.into(),
-            ]
-        )?;
-
-        if let Some(scene) = asset_manager.active_scene() {
-            let window_dimensions = get_window_dimensions(self.surface.window());
-            let mut transformation_uniform_data = scene.camera().as_uniform_data(window_dimensions.width as f32, window_dimensions.height as f32);
-            self.uniform_manager.update_light_data(scene.light_data());
-            
-
-            for object in scene.objects() {
-                transformation_uniform_data.model = object.model_matrix().into();
-                self.uniform_manager.update_transformation_data(transformation_uniform_data);
-                let transformation_data_buffer_subbuffer = self.uniform_manager.get_transformation_subbuffer_data()?;
-                let light_data_buffer_subbuffer = self.uniform_manager.get_light_subbuffer_data()?;
-
-                let descriptor_set = PersistentDescriptorSet::start(self.pipeline.clone(), 0)
-                                                             .add_buffer(transformation_data_buffer_subbuffer)?
-                                                             .add_buffer(light_data_buffer_subbuffer)?;
-
-                let push_constants = PushConstants {
-                    light_source: object.light_source() as u32,
-                    uniform_scale: object.uniform_scale() as u32,
-                };
-
-                if let Some(mesh) = object.mesh() {
-                    let (mesh_texture, vertex_buffer, index_buffer) = {
-                        let mesh = mesh.read().unwrap();
-                        (mesh.texture(), mesh.vertex_buffer(), mesh.index_buffer())
-                    };
-                    let descriptor_set = descriptor_set.add_sampled_image(mesh_texture.image_buffer(), mesh_texture.sampler())?.build()?;
-                    command_buffer = command_buffer.draw_indexed(
-                        self.pipeline.clone(), 
-                        &DynamicState::none(), 
-                        vec!(vertex_buffer),
-                        index_buffer, 
-                        descriptor_set,
-                        push_constants,
-                    )?;
-                }
-            }
-        }   
+    fn add_scene_commands(&mut self, command_buffer: AutoCommandBufferBuilder, image_num: usize, asset_manager: &mut AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let window_dimensions: (u32, u32) = get_window_dimensions(self.surface.window()).into();
 
+        let (command_buffer, render_stats) = add_scene_commands(command_buffer, 
+                                                                self.framebuffers[image_num].clone(), 
+                                                                self.pipeline.clone(), 
+                                                                &mut self.uniform_manager, 
+                                                                &self.shadow_map, 
+                                                                &self.default_material,
+                                                                self.highlighted_object,
+                                                                [window_dimensions.0, window_dimensions.1], 
+                                                                asset_manager)?;
+        self.render_stats = render_stats;
         Ok(command_buffer)
     }
 
@@ -233,8 +221,8 @@ impl Renderer {
         self.swapchain = new_swapchain;
         self.images = new_images;
 
-        self.pipeline = create_pipeline(self.device.clone(), self.shader_set.clone(), &self.images, self.render_pass.clone())?;
-        self.framebuffers = create_framebuffers(self.device.clone(), &self.images, self.render_pass.clone())?;
+        self.pipeline = create_pipeline(self.device.clone(), self.shader_set.clone(), self.swapchain.dimensions(), self.render_pass.clone())?;
+        self.framebuffers = create_framebuffers(self.device.clone(), self.swapchain.dimensions(), &self.images, self.render_pass.clone())?;
 
         self.recreate_swapchain = false;
         Ok(())
@@ -262,16 +250,215 @@ impl Renderer {
         self.framebuffers[image_num].clone()
     }
 
+    /// Returns number of drawn and culled objects from the last rendered frame.
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Sets id of the object drawn with highlight color, for example object selected in the editor.
+    pub fn set_highlighted_object(&mut self, id: Option<u32>) {
+        self.highlighted_object = id;
+    }
+
+    /// Returns id of the object drawn with highlight color.
+    pub fn highlighted_object(&self) -> Option<u32> {
+        self.highlighted_object
+    }
+
+}
+
+/// Number of objects drawn and skipped during the last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderStats {
+    drawn_objects: usize,
+    culled_objects: usize,
+    draw_calls: usize,
+}
+
+impl RenderStats {
+    /// Returns number of objects drawn in the main pass.
+    pub fn drawn_objects(&self) -> usize {
+        self.drawn_objects
+    }
+
+    /// Returns number of objects skipped because they were outside of the camera frustum.
+    pub fn culled_objects(&self) -> usize {
+        self.culled_objects
+    }
+
+    /// Returns number of draw calls in the main pass. Objects sharing mesh, texture and material are drawn with a single call.
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+}
+
+/// Visible objects which share mesh, texture, material and shading flags, drawn with one instanced draw call.
+struct InstanceBatch {
+    mesh: Arc<RwLock<Mesh>>,
+    texture: Option<Arc<Texture>>,
+    material: Option<Arc<RwLock<Material>>>,
+    light_source: bool,
+    uniform_scale: bool,
+    receive_shadows: bool,
+    highlighted: bool,
+    model_matrices: Vec<Mat4>,
+}
+
+/// Adds commands used to draw active scene from asset manager into the given framebuffer.
+/// Shadow map of the main light is rendered first, then the main render pass is started.
+/// Default material is used by objects which don't have material and whose mesh doesn't have one.
+/// Objects outside of the camera frustum are skipped in the main pass and visible objects
+/// sharing mesh, texture and material are drawn with one instanced draw call.
+/// Main render pass has to be ended before the command buffer is built.
+fn add_scene_commands(
+    mut command_buffer: AutoCommandBufferBuilder,
+    framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+    pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+    uniform_manager: &mut UniformManager,
+    shadow_map: &ShadowMap,
+    default_material: &Material,
+    highlighted_object: Option<u32>,
+    dimensions: [u32; 2],
+    asset_manager: &mut AssetManager
+) -> Result<(AutoCommandBufferBuilder, RenderStats), RenderError> {
+    let mut render_stats = RenderStats::default();
+
+    let light_space = asset_manager.active_scene()
+                                   .and_then(|scene| scene.main_light())
+                                   .map(|light| light.light_space_matrix())
+                                   .unwrap_or_else(Mat4::identity);
+
+    if let Some(scene) = asset_manager.active_scene() {
+        command_buffer = shadow_map.add_shadow_commands(command_buffer, uniform_manager, scene, &light_space)?;
+    }
+
+    command_buffer = command_buffer.begin_render_pass(
+        framebuffer, false,
+        vec![
+            [0.0, 0.0, 0.0, 1.0].into(),
+            1f32.into(),
+        ]
+    )?;
+
+    if let Some(scene) = asset_manager.active_scene() {
+        let mut transformation_uniform_data = scene.camera().as_uniform_data(dimensions[0] as f32, dimensions[1] as f32);
+        transformation_uniform_data.light_space = light_space.into();
+        uniform_manager.update_light_data(scene.lights());
+
+        let frustum = scene.camera().frustum(dimensions[0] as f32, dimensions[1] as f32);
+
+        let mut batches: Vec<InstanceBatch> = Vec::new();
+        let mut batch_indices = HashMap::new();
+        for (object, world_matrix) in scene.objects().iter().zip(scene.world_matrices()) {
+            if let Some(mesh) = object.mesh() {
+                let visible = object.world_bounding_box(&world_matrix)
+                                    .map(|bounding_box| frustum.intersects_box(&bounding_box))
+                                    .unwrap_or(true);
+                if !visible {
+                    render_stats.culled_objects += 1;
+                    continue;
+                }
+                render_stats.drawn_objects += 1;
+
+                let texture = object.texture();
+                let material = object.material().or_else(|| mesh.read().unwrap().material());
+                let highlighted = highlighted_object == Some(object.id());
+                let key = (
+                    &*mesh as *const RwLock<Mesh> as usize,
+                    texture.as_ref().map(|texture| &**texture as *const Texture as usize),
+                    material.as_ref().map(|material| &**material as *const RwLock<Material> as usize),
+                    object.light_source(),
+                    object.uniform_scale(),
+                    object.receive_shadows(),
+                    highlighted,
+                );
+
+                let index = *batch_indices.entry(key).or_insert_with(|| {
+                    batches.push(InstanceBatch {
+                        mesh,
+                        texture,
+                        material,
+                        light_source: object.light_source(),
+                        uniform_scale: object.uniform_scale(),
+                        receive_shadows: object.receive_shadows(),
+                        highlighted,
+                        model_matrices: Vec::new(),
+                    });
+                    batches.len() - 1
+                });
+                batches[index].model_matrices.push(world_matrix);
+            }
+        }
+
+        uniform_manager.update_transformation_data(transformation_uniform_data);
+        let transformation_data_buffer_subbuffer = uniform_manager.get_transformation_subbuffer_data()?;
+        let light_data_buffer_subbuffer = uniform_manager.get_light_subbuffer_data()?;
+
+        for batch in batches {
+            let (mesh_texture, vertex_buffer, index_buffer) = {
+                let mesh = batch.mesh.read().unwrap();
+                (batch.texture.clone().unwrap_or_else(|| mesh.texture()), mesh.vertex_buffer(), mesh.index_buffer())
+            };
+            let material_lock = batch.material.as_ref().map(|material| material.read().unwrap());
+            let material = material_lock.as_ref().map_or(default_material, |material| &**material);
+
+            let diffuse_map = material.diffuse_map().unwrap_or(mesh_texture);
+            let specular_map = material.specular_map().unwrap_or_else(|| diffuse_map.clone());
+            let normal_map = material.normal_map().unwrap_or_else(|| diffuse_map.clone());
+            let emissive_map = material.emissive_map().unwrap_or_else(|| diffuse_map.clone());
+
+            let (base_r, base_g, base_b) = material.base_color();
+            let (emissive_r, emissive_g, emissive_b) = material.emissive_color();
+            let push_constants = PushConstants {
+                base_color: [base_r, base_g, base_b],
+                ambient_strength: material.ambient_strength(),
+                emissive_color: [emissive_r, emissive_g, emissive_b],
+                specular_strength: material.specular_strength(),
+                shininess: material.shininess(),
+                light_source: batch.light_source as u32,
+                uniform_scale: batch.uniform_scale as u32,
+                receive_shadows: batch.receive_shadows as u32,
+                has_specular_map: material.specular_map().is_some() as u32,
+                has_normal_map: material.normal_map().is_some() as u32,
+                has_emissive_map: material.emissive_map().is_some() as u32,
+                highlighted: batch.highlighted as u32,
+            };
+
+            let descriptor_set = PersistentDescriptorSet::start(pipeline.clone(), 0)
+                                                         .add_buffer(transformation_data_buffer_subbuffer.clone())?
+                                                         .add_buffer(light_data_buffer_subbuffer.clone())?
+                                                         .add_sampled_image(diffuse_map.image_buffer(), diffuse_map.sampler())?
+                                                         .add_sampled_image(shadow_map.image(), shadow_map.sampler())?
+                                                         .add_sampled_image(specular_map.image_buffer(), specular_map.sampler())?
+                                                         .add_sampled_image(normal_map.image_buffer(), normal_map.sampler())?
+                                                         .add_sampled_image(emissive_map.image_buffer(), emissive_map.sampler())?
+                                                         .build()?;
+            let instance_buffer = uniform_manager.get_instance_chunk_data(&batch.model_matrices)?;
+
+            command_buffer = command_buffer.draw_indexed(
+                pipeline.clone(), 
+                &DynamicState::none(), 
+                vec!(vertex_buffer, Arc::new(instance_buffer)),
+                index_buffer, 
+                descriptor_set,
+                push_constants,
+            )?;
+            render_stats.draw_calls += 1;
+        }
+    }   
+
+    Ok((command_buffer, render_stats))
 }
 
 /// Creates framebuffers, which contain list of images that are attached.
-fn create_framebuffers(
+fn create_framebuffers<I>(
     device: Arc<Device>,
-    images: &[Arc<SwapchainImage<Window>>], 
+    dimensions: [u32; 2],
+    images: &[Arc<I>], 
     render_pass: Arc<RenderPassAbstract + Send + Sync>
-) -> Result<Vec<Arc<FramebufferAbstract + Send + Sync>>, FramebufferCreationError> {
-
-    let dimensions = images[0].dimensions();
+) -> Result<Vec<Arc<FramebufferAbstract + Send + Sync>>, FramebufferCreationError> 
+    where I: ImageViewAccess + Send + Sync + 'static
+{
     let depth_buffer = AttachmentImage::transient(device, dimensions, Format::D16Unorm)
                                        .expect("Couldn't create depth buffer!");
 
@@ -292,11 +479,9 @@ fn create_framebuffers(
 fn create_pipeline(
     device: Arc<Device>, 
     shader_set: Rc<ShaderSet>, 
-    images: &[Arc<SwapchainImage<Window>>], 
+    dimensions: [u32; 2], 
     render_pass: Arc<RenderPassAbstract + Send + Sync>
 ) -> Result<Arc<GraphicsPipelineAbstract + Send + Sync>, GraphicsPipelineCreationError> {
-    
-    let dimensions = images[0].dimensions();
 
     let pipeline = GraphicsPipeline::start()
         .vertex_input(ShaderSet::vertex_layout())
@@ -351,22 +536,23 @@ fn create_new_instance() -> Result<Arc<Instance>, InstanceCreationError> {
     Instance::new(None, &extensions, None)
 }
 
+/// Creates new vulkan instance without any window system extensions.
+fn create_new_headless_instance() -> Result<Arc<Instance>, InstanceCreationError> {
+    Instance::new(None, &InstanceExtensions::none(), None)
+}
+
 /// Creates new vulkan logical device
-fn create_logical_device<'a>(physical_device: PhysicalDevice, physical_queues: &[(QueueFamily<'a>, f32)]) 
+fn create_logical_device<'a>(physical_device: PhysicalDevice, physical_queues: &[(QueueFamily<'a>, f32)], 
+                             device_extensions_needed: &DeviceExtensions) 
         -> Result<(Arc<Device>, QueuesIter), DeviceCreationError> {
     let minimal_features = vulkano::device::Features {
         depth_clamp: true, //needed for correct shadow mapping
         .. vulkano::device::Features::none()
     };
 
-    let device_extensions_needed = vulkano::device::DeviceExtensions {
-        khr_swapchain: true,
-        .. vulkano::device::DeviceExtensions::none()
-    };
-
     Device::new(
         physical_device, &minimal_features,
-        &device_extensions_needed, physical_queues.iter().cloned()
+        device_extensions_needed, physical_queues.iter().cloned()
     )
 }
 
@@ -0,0 +1,209 @@
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::device::{Device, DeviceExtensions};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, FramebufferAbstract};
+use vulkano::image::ImageUsage;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::instance::{Instance, PhysicalDevice};
+use vulkano::pipeline::GraphicsPipelineAbstract;
+use vulkano::sync::GpuFuture;
+use vulkano::sync;
+
+use log::*;
+
+use image::RgbaImage;
+
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::renderer::queues;
+use crate::renderer::RenderStats;
+use crate::renderer::queues::Queues;
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shadow::ShadowMap;
+use crate::renderer::uniform_manager::UniformManager;
+use crate::resource::AssetManager;
+use crate::resource::material::Material;
+use crate::settings::Settings;
+
+/// Format of the offscreen color attachment.
+const COLOR_FORMAT: Format = Format::R8G8B8A8Srgb;
+
+/// Vulkan renderer which renders into an offscreen image instead of a window.
+/// It doesn't need an events loop or a surface, so it can be used with software drivers in automated tests.
+pub struct HeadlessRenderer {
+    instance: Arc<Instance>,
+    device: Arc<Device>,
+    queues: Queues,
+    dimensions: [u32; 2],
+    color_image: Arc<AttachmentImage<Format>>,
+    uniform_manager: UniformManager,
+    render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+    framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+    shadow_map: ShadowMap,
+    default_material: Material,
+    render_stats: RenderStats,
+    highlighted_object: Option<u32>,
+}
+
+impl HeadlessRenderer {
+    /// Creates new headless renderer. Initial window size from settings is used as the size of rendered image.
+    pub fn new(settings: &Settings) -> Result<Self, RendererCreationError> {
+        let instance = super::create_new_headless_instance()?;
+
+        let physical_device = super::rank_devices(PhysicalDevice::enumerate(&instance))?;
+        info!("Using device: {} (type: {:?})", physical_device.name(), physical_device.ty());
+
+        let physical_queues = queues::find_headless_queues(physical_device);
+
+        let (device, queues) = super::create_logical_device(physical_device, &physical_queues, &DeviceExtensions::none())?;
+
+        let queues = Queues::new(queues);
+
+        let dimensions: (u32, u32) = settings.initial_window_size().clone().into();
+        let dimensions = [dimensions.0, dimensions.1];
+
+        let usage = ImageUsage {
+            color_attachment: true,
+            transfer_source: true,
+            .. ImageUsage::none()
+        };
+        let color_image = AttachmentImage::with_usage(device.clone(), dimensions, COLOR_FORMAT, usage)?;
+
+        let uniform_manager = UniformManager::new(device.clone(), settings.max_lights());
+        let shader_set = Rc::new(ShaderSet::load(device.clone()));
+
+        let render_pass = super::create_renderpass(device.clone(), COLOR_FORMAT)?;
+
+        let pipeline = super::create_pipeline(device.clone(), shader_set.clone(), dimensions, render_pass.clone())?;
+        let framebuffer = super::create_framebuffers(device.clone(), dimensions, &[color_image.clone()], render_pass.clone())?.remove(0);
+        let shadow_map = ShadowMap::new(device.clone(), &shader_set, settings.shadow_map_resolution())?;
+
+        Ok(HeadlessRenderer {
+            instance,
+            device,
+            queues,
+            dimensions,
+            color_image,
+            uniform_manager,
+            render_pass,
+            pipeline,
+            framebuffer,
+            shadow_map,
+            default_material: Material::new("default"),
+            render_stats: RenderStats::default(),
+            highlighted_object: None,
+        })
+    }
+
+    /// Renders one frame using active scene from asset manager.
+    pub fn render_scene(&mut self, command_buffer: AutoCommandBufferBuilder, asset_manager: &mut AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let (command_buffer, render_stats) = super::add_scene_commands(command_buffer,
+                                                                       self.framebuffer.clone(),
+                                                                       self.pipeline.clone(),
+                                                                       &mut self.uniform_manager,
+                                                                       &self.shadow_map,
+                                                                       &self.default_material,
+                                                                       self.highlighted_object,
+                                                                       self.dimensions,
+                                                                       asset_manager)?;
+        self.render_stats = render_stats;
+        Ok(command_buffer)
+    }
+
+    /// Executes commands stored in command buffer and waits until the frame is rendered.
+    pub fn execute_command_buffer(&mut self, command_buffer: AutoCommandBufferBuilder) -> Result<(), RenderError> {
+        let command_buffer = command_buffer.end_render_pass()?.build()?;
+
+        let future = sync::now(self.device.clone())
+                          .then_execute(self.queues.graphics_queue(), command_buffer)?
+                          .then_signal_fence_and_flush()?;
+
+        future.wait(None)?;
+        Ok(())
+    }
+
+    /// Reads back the last rendered frame from the color attachment.
+    /// Only headless renderer can capture frames, windowed renderer hands its swapchain images
+    /// over to the presentation engine when a frame is executed, so they can't be read back afterwards.
+    pub fn capture_frame(&mut self) -> Result<RgbaImage, RenderError> {
+        let [width, height] = self.dimensions;
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            self.device.clone(),
+            BufferUsage::transfer_destination(),
+            (0 .. width * height * 4).map(|_| 0u8)
+        )?;
+
+        let command_buffer = self.create_command_buffer()?
+                                 .copy_image_to_buffer(self.color_image.clone(), buffer.clone())?
+                                 .build()?;
+
+        command_buffer.execute(self.queues.graphics_queue())?
+                      .then_signal_fence_and_flush()?
+                      .wait(None)?;
+
+        let pixels = buffer.read()?.to_vec();
+        Ok(RgbaImage::from_raw(width, height, pixels).expect("Captured frame has invalid size!"))
+    }
+
+    /// Captures the last rendered frame and saves it to file. Image format is deduced from the file extension.
+    pub fn save_frame<P: AsRef<Path>>(&mut self, path: P) -> Result<(), RenderError> {
+        self.capture_frame()?.save(path)?;
+        Ok(())
+    }
+
+    /// Creates vulkan command buffer.
+    pub fn create_command_buffer(&mut self) -> Result<AutoCommandBufferBuilder, RenderError> {
+        Ok(AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), self.queues.graphics_queue().family())?)
+    }
+
+    /// Returns vulkan queues.
+    pub fn queues(&self) -> Queues {
+        self.queues.clone()
+    }
+
+    /// Returns vulkan device.
+    pub fn device(&self) -> Arc<Device> {
+        self.device.clone()
+    }
+
+    /// Returns width and height of the rendered image.
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    /// Returns image to which frames are rendered.
+    pub fn color_image(&self) -> Arc<AttachmentImage<Format>> {
+        self.color_image.clone()
+    }
+
+    /// Returns render pass used to draw the scene.
+    pub fn render_pass(&self) -> Arc<RenderPassAbstract + Send + Sync> {
+        self.render_pass.clone()
+    }
+
+    /// Returns framebuffer with the offscreen color attachment.
+    pub fn framebuffer(&self) -> Arc<FramebufferAbstract + Send + Sync> {
+        self.framebuffer.clone()
+    }
+
+    /// Returns number of drawn and culled objects from the last rendered frame.
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Sets id of the object drawn with highlight color.
+    pub fn set_highlighted_object(&mut self, id: Option<u32>) {
+        self.highlighted_object = id;
+    }
+
+    /// Returns id of the object drawn with highlight color.
+    pub fn highlighted_object(&self) -> Option<u32> {
+        self.highlighted_object
+    }
+}
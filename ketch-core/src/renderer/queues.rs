@@ -81,7 +81,18 @@ impl Queues {
 
 /// Finds available queues supported by the physical device.
 pub fn find_queues<'a>(physical_device: PhysicalDevice<'a>, surface: &Surface<Window>) -> Vec<(QueueFamily<'a>, f32)>{
-    
+    find_queues_with_presentation(physical_device, |queue| surface.is_supported(queue).unwrap_or(false))
+}
+
+/// Finds available queues supported by the physical device when rendering without a surface.
+pub fn find_headless_queues<'a>(physical_device: PhysicalDevice<'a>) -> Vec<(QueueFamily<'a>, f32)>{
+    find_queues_with_presentation(physical_device, |_queue| true)
+}
+
+/// Finds available queues, graphics queue has to also satisfy presentation requirements.
+fn find_queues_with_presentation<'a, F>(physical_device: PhysicalDevice<'a>, supports_presentation: F) -> Vec<(QueueFamily<'a>, f32)>
+    where F: Fn(QueueFamily<'a>) -> bool
+{
     let mut queue_collection = Vec::new();
 
     let mut has_graphics = false;
@@ -98,7 +109,7 @@ pub fn find_queues<'a>(physical_device: PhysicalDevice<'a>, surface: &Surface<Wi
             queue.queues_count()
         );
 
-        if queue.supports_graphics() && surface.is_supported(queue).unwrap_or(false) && !has_graphics {
+        if queue.supports_graphics() && supports_presentation(queue) && !has_graphics {
             queue_collection.push((queue, 1.0));
             has_graphics = true
         }
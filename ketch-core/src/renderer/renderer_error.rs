@@ -1,10 +1,13 @@
 use std::error::Error;
+use std::io;
 
 use vulkano::sync::FlushError;
 use vulkano::command_buffer::CommandBufferExecError;
 use vulkano::command_buffer::BuildError;
 use vulkano::command_buffer::AutoCommandBufferBuilderContextError;
 use vulkano::command_buffer::DrawIndexedError;
+use vulkano::command_buffer::CopyBufferImageError;
+use vulkano::buffer::cpu_access::ReadLockError;
 use vulkano::descriptor::descriptor_set::PersistentDescriptorSetBuildError;
 use vulkano::descriptor::descriptor_set::PersistentDescriptorSetError;
 use vulkano::memory::DeviceMemoryAllocError;
@@ -19,6 +22,8 @@ use vulkano::swapchain::SwapchainCreationError;
 use vulkano::swapchain::CapabilitiesError;
 use vulkano::device::DeviceCreationError;
 use vulkano::instance::InstanceCreationError;
+use vulkano::image::ImageCreationError;
+use vulkano::sampler::SamplerCreationError;
 use vulkano_win::{CreationError as WindowCreationError};
 
 use quick_error::quick_error; 
@@ -96,6 +101,21 @@ quick_error! {
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        CopyBufferImageError(err: CopyBufferImageError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ReadLockError(err: ReadLockError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        IoError(err: io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
     }
 }
 
@@ -142,6 +162,16 @@ quick_error! {
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        ImageCreationError(err: ImageCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SamplerCreationError(err: SamplerCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
         NoPhysicalDeviceError {
             display("NoPhysicalDeviceError: couldn't find usable physical device")
         }
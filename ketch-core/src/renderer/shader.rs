@@ -1,17 +1,24 @@
 pub mod vertex_shader;
 pub mod fragment_shader;
+pub mod shadow_vertex_shader;
+pub mod shadow_fragment_shader;
 
 use vulkano::device::Device;
 use std::sync::Arc;
 
-use vulkano::pipeline::vertex::SingleBufferDefinition;
+use vulkano::pipeline::vertex::OneVertexOneInstanceDefinition;
 
-use crate::resource::mesh::Vertex;
+use crate::resource::mesh::{Vertex, InstanceData};
+
+/// Maximum number of lights supported by shaders. Has to match MAX_LIGHTS in fragment shader.
+pub const MAX_LIGHTS: usize = 16;
 
 /// Contains shaders used by the engine.
 pub struct ShaderSet {
     vertex_shader: vertex_shader::Shader,
     fragment_shader: fragment_shader::Shader,
+    shadow_vertex_shader: shadow_vertex_shader::Shader,
+    shadow_fragment_shader: shadow_fragment_shader::Shader,
 }
 
 impl ShaderSet {
@@ -20,16 +27,20 @@ impl ShaderSet {
 
         let v_s = vertex_shader::Shader::load(device.clone()).expect("Failed to load vertex shader!");
         let f_s = fragment_shader::Shader::load(device.clone()).expect("Failed to load fragment shader!");
+        let shadow_v_s = shadow_vertex_shader::Shader::load(device.clone()).expect("Failed to load shadow vertex shader!");
+        let shadow_f_s = shadow_fragment_shader::Shader::load(device.clone()).expect("Failed to load shadow fragment shader!");
 
         ShaderSet {
             vertex_shader: v_s,
             fragment_shader: f_s,
+            shadow_vertex_shader: shadow_v_s,
+            shadow_fragment_shader: shadow_f_s,
         }
     }
 
-    /// Returns vertex shader layout.
-    pub fn vertex_layout() -> SingleBufferDefinition<Vertex> {
-        SingleBufferDefinition::<Vertex>::new()
+    /// Returns vertex shader layout. Vertices are read from the first buffer and model matrices of instances from the second one.
+    pub fn vertex_layout() -> OneVertexOneInstanceDefinition<Vertex, InstanceData> {
+        OneVertexOneInstanceDefinition::<Vertex, InstanceData>::new()
     }
 
     /// Returns vertex shader.
@@ -41,4 +52,14 @@ impl ShaderSet {
     pub fn fragment_shader(&self) -> &fragment_shader::Shader {
         &self.fragment_shader
     }
+
+    /// Returns vertex shader used to render shadow maps.
+    pub fn shadow_vertex_shader(&self) -> &shadow_vertex_shader::Shader {
+        &self.shadow_vertex_shader
+    }
+
+    /// Returns fragment shader used to render shadow maps.
+    pub fn shadow_fragment_shader(&self) -> &shadow_fragment_shader::Shader {
+        &self.shadow_fragment_shader
+    }
 }
\ No newline at end of file
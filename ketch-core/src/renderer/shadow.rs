@@ -0,0 +1,187 @@
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Framebuffer, FramebufferAbstract, Subpass};
+use vulkano::image::ImageUsage;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::sampler::{Sampler, SamplerAddressMode, BorderColor, Filter, MipmapMode};
+use vulkano::single_pass_renderpass;
+
+use nalgebra_glm::Mat4;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::shadow_vertex_shader::ty::PushConstants;
+use crate::renderer::uniform_manager::UniformManager;
+use crate::resource::mesh::Mesh;
+use crate::resource::scene::Scene;
+
+/// Format of the shadow map depth attachment.
+const SHADOW_MAP_FORMAT: Format = Format::D16Unorm;
+
+/// Depth of the scene rendered from the point of view of the main light.
+/// It's sampled in the main pass to decide which fragments are in shadow.
+pub struct ShadowMap {
+    resolution: u32,
+    image: Arc<AttachmentImage<Format>>,
+    sampler: Arc<Sampler>,
+    pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+    framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+}
+
+impl ShadowMap {
+    /// Creates new square shadow map with given width and height in texels.
+    pub fn new(device: Arc<Device>, shader_set: &ShaderSet, resolution: u32) -> Result<Self, RendererCreationError> {
+        let usage = ImageUsage {
+            depth_stencil_attachment: true,
+            sampled: true,
+            .. ImageUsage::none()
+        };
+        let image = AttachmentImage::with_usage(device.clone(), [resolution, resolution], SHADOW_MAP_FORMAT, usage)?;
+
+        // everything outside of the shadow map is lit
+        let sampler = Sampler::new(
+                        device.clone(),
+                        Filter::Nearest,
+                        Filter::Nearest,
+                        MipmapMode::Nearest,
+                        SamplerAddressMode::ClampToBorder(BorderColor::FloatOpaqueWhite),
+                        SamplerAddressMode::ClampToBorder(BorderColor::FloatOpaqueWhite),
+                        SamplerAddressMode::ClampToBorder(BorderColor::FloatOpaqueWhite),
+                        0.0,
+                        1.0,
+                        0.0,
+                        1.0
+                      )?;
+
+        let render_pass = create_shadow_renderpass(device.clone())?;
+        let pipeline = create_shadow_pipeline(device.clone(), shader_set, resolution, render_pass.clone())?;
+        let framebuffer = Arc::new(Framebuffer::start(render_pass)
+                                                .add(image.clone())?
+                                                .build()?);
+
+        Ok(ShadowMap {
+            resolution,
+            image,
+            sampler,
+            pipeline,
+            framebuffer,
+        })
+    }
+
+    /// Returns width and height of the shadow map in texels.
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// Returns depth image sampled by the main pass.
+    pub fn image(&self) -> Arc<AttachmentImage<Format>> {
+        self.image.clone()
+    }
+
+    /// Returns sampler used to read the shadow map.
+    pub fn sampler(&self) -> Arc<Sampler> {
+        self.sampler.clone()
+    }
+
+    /// Adds commands rendering depth of all shadow casting objects from the scene using given light space matrix.
+    /// Has to be called outside of any render pass. Instance buffers come from the same pool as in the main pass.
+    pub fn add_shadow_commands(
+        &self,
+        mut command_buffer: AutoCommandBufferBuilder,
+        uniform_manager: &UniformManager,
+        scene: &Scene,
+        light_space: &Mat4
+    ) -> Result<AutoCommandBufferBuilder, RenderError> {
+        command_buffer = command_buffer.begin_render_pass(self.framebuffer.clone(), false, vec![1f32.into()])?;
+
+        let casters = scene.objects().iter()
+                                     .zip(scene.world_matrices())
+                                     .filter(|(object, _)| object.cast_shadows() && !object.light_source());
+
+        // all casters with the same mesh are drawn with one instanced draw call
+        let mut batches: Vec<(Arc<RwLock<Mesh>>, Vec<Mat4>)> = Vec::new();
+        let mut batch_indices = HashMap::new();
+        for (object, world_matrix) in casters {
+            if let Some(mesh) = object.mesh() {
+                let index = *batch_indices.entry(&*mesh as *const RwLock<Mesh> as usize).or_insert_with(|| {
+                    batches.push((mesh.clone(), Vec::new()));
+                    batches.len() - 1
+                });
+                batches[index].1.push(world_matrix);
+            }
+        }
+
+        let push_constants = PushConstants {
+            light_space: (*light_space).into(),
+        };
+
+        for (mesh, model_matrices) in batches {
+            let (vertex_buffer, index_buffer) = {
+                let mesh = mesh.read().unwrap();
+                (mesh.vertex_buffer(), mesh.index_buffer())
+            };
+            let instance_buffer = uniform_manager.get_instance_chunk_data(&model_matrices)?;
+
+            command_buffer = command_buffer.draw_indexed(
+                self.pipeline.clone(),
+                &DynamicState::none(),
+                vec!(vertex_buffer, Arc::new(instance_buffer)),
+                index_buffer,
+                (),
+                push_constants,
+            )?;
+        }
+
+        Ok(command_buffer.end_render_pass()?)
+    }
+}
+
+/// Creates render pass with a single depth attachment, which is stored for sampling in the main pass.
+fn create_shadow_renderpass(device: Arc<Device>) -> Result<Arc<RenderPassAbstract + Send + Sync>, RendererCreationError> {
+    let render_pass = single_pass_renderpass!(device,
+                            attachments: {
+                                depth: {
+                                    load: Clear,
+                                    store: Store,
+                                    format: SHADOW_MAP_FORMAT,
+                                    samples: 1,
+                                }
+                            },
+                            pass: {
+                                color: [],
+                                depth_stencil: {depth}
+                            }
+                      )?;
+    Ok(Arc::new(render_pass))
+}
+
+/// Creates depth only pipeline. Depth clamping keeps casters in front of the near plane in the shadow map.
+fn create_shadow_pipeline(
+    device: Arc<Device>,
+    shader_set: &ShaderSet,
+    resolution: u32,
+    render_pass: Arc<RenderPassAbstract + Send + Sync>
+) -> Result<Arc<GraphicsPipelineAbstract + Send + Sync>, RendererCreationError> {
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(ShaderSet::vertex_layout())
+        .vertex_shader(shader_set.shadow_vertex_shader().main_entry_point(), ())
+        .triangle_list()
+        .viewports(std::iter::once(Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [resolution as f32, resolution as f32],
+            depth_range: 0.0 .. 1.0,
+        }))
+        .fragment_shader(shader_set.shadow_fragment_shader().main_entry_point(), ())
+        .depth_clamp(true)
+        .depth_stencil_simple_depth()
+        .render_pass(Subpass::from(render_pass, 0).unwrap())
+        .build(device)?;
+
+    Ok(Arc::new(pipeline))
+}
@@ -1,35 +1,43 @@
 use vulkano::memory::DeviceMemoryAllocError;
 use vulkano::buffer::cpu_pool::CpuBufferPoolSubbuffer;
+use vulkano::buffer::cpu_pool::CpuBufferPoolChunk;
 use vulkano::buffer::cpu_pool::CpuBufferPool;
 use vulkano::buffer::BufferUsage;
 use vulkano::device::Device;
 use vulkano::memory::pool::StdMemoryPool;
 
-use nalgebra_glm::{Vec3, Mat4};
+use nalgebra_glm::Mat4;
 
 use std::sync::Arc;
 
 use crate::renderer::shader::vertex_shader::ty::TransformationData;
 use crate::renderer::shader::fragment_shader::ty::LightData;
+use crate::renderer::shader::MAX_LIGHTS;
+use crate::resource::light::Light;
+use crate::resource::mesh::InstanceData;
 
 /// Struct which stores uniform data and uniform buffers.
 pub struct UniformManager {
-    // data used in transformations (model, view, projection matrix)
+    // data used in transformations (view, projection matrix)
     transformation_data: TransformationData,
     transformation_data_buffer_pool: CpuBufferPool<TransformationData>,
 
     // data used with lighting
+    max_lights: usize,
     light_data: LightData,
     light_data_buffer_pool: CpuBufferPool<LightData>,
+
+    // model matrices of instances
+    instance_data_buffer_pool: CpuBufferPool<InstanceData>,
 }
 
 impl UniformManager {
-    /// Creates new uniform manager.
-    pub fn new(device: Arc<Device>) -> Self {
+    /// Creates new uniform manager. At most max_lights lights are uploaded to shaders.
+    pub fn new(device: Arc<Device>, max_lights: usize) -> Self {
         let transformation_data = TransformationData {
-            model: Mat4::identity().into(),
             view: Mat4::identity().into(),
             proj: Mat4::identity().into(),
+            light_space: Mat4::identity().into(),
         };
 
         let transformation_data_buffer_pool = CpuBufferPool::<TransformationData>::new(
@@ -37,24 +45,31 @@ impl UniformManager {
         );
 
         let light_data = LightData {
-            _dummy0: [0; 4],
-            light_position: Vec3::new(0.0, 0.0, 0.0).into(),
-            light_color: Vec3::new(1.0, 1.0, 1.0).into(),
+            lights: [Light::point(0.0, 0.0, 0.0).as_uniform_data(); MAX_LIGHTS],
+            light_count: 0,
         };
 
         let light_data_buffer_pool = CpuBufferPool::<LightData>::new(device.clone(), BufferUsage::all());
 
+        let instance_data_buffer_pool = CpuBufferPool::<InstanceData>::vertex_buffer(device.clone());
+
         UniformManager {
             transformation_data,
             transformation_data_buffer_pool,
+            max_lights: max_lights.min(MAX_LIGHTS),
             light_data,
             light_data_buffer_pool,
+            instance_data_buffer_pool,
         }
     }
 
-    /// Updates light uniform data.
-    pub fn update_light_data(&mut self, light_data: LightData) {
-        self.light_data = light_data;
+    /// Updates light uniform data. Lights above the maximum count are ignored.
+    pub fn update_light_data(&mut self, lights: &[Light]) {
+        let light_count = lights.len().min(self.max_lights);
+        for (light_data, light) in self.light_data.lights.iter_mut().zip(&lights[.. light_count]) {
+            *light_data = light.as_uniform_data();
+        }
+        self.light_data.light_count = light_count as u32;
     }
 
     /// Returns subbuffer from light uniform buffer.
@@ -71,5 +86,9 @@ impl UniformManager {
     pub fn get_transformation_subbuffer_data(&self) -> Result<CpuBufferPoolSubbuffer<TransformationData, Arc<StdMemoryPool>>, DeviceMemoryAllocError> {
         self.transformation_data_buffer_pool.next(self.transformation_data.clone())
     }
-}
 
+    /// Returns vertex buffer with model matrices of instances.
+    pub fn get_instance_chunk_data(&self, model_matrices: &[Mat4]) -> Result<CpuBufferPoolChunk<InstanceData, Arc<StdMemoryPool>>, DeviceMemoryAllocError> {
+        self.instance_data_buffer_pool.chunk(model_matrices.iter().map(|model| InstanceData::from(*model)))
+    }
+}
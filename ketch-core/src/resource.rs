@@ -1,8 +1,9 @@
 use image::DynamicImage;
 use std::sync::RwLock;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use vulkano::device::Device;
 use crate::resource::texture::Texture;
+use crate::resource::material::Material;
 use std::sync::Arc;
 use crate::resource::mesh::Vertex;
 use crate::renderer::queues::Queues;
@@ -12,12 +13,24 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use crate::resource::mesh::Mesh;
 use crate::resource::scene::Scene;
+use crate::resource::resource_error::{GltfLoadError, ObjLoadError, SceneFileError};
+use crate::resource::scene_file::SceneFile;
+use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::object::ObjectBuilder;
 
 pub mod mesh;
 pub mod camera;
+pub mod light;
 pub mod scene;
 pub mod object;
 pub mod texture;
+pub mod material;
+pub mod bounds;
+pub mod resource_error;
+pub mod scene_file;
+mod obj;
+mod gltf_import;
 
 const DEFAULT_TEXTURE_NAME: &'static str = "default";
 
@@ -28,6 +41,7 @@ pub struct AssetManager {
     scenes: HashMap<String, Scene>,
     meshes: HashMap<String, Arc<RwLock<Mesh>>>,
     textures: HashMap<String, Arc<Texture>>,
+    materials: HashMap<String, Arc<RwLock<Material>>>,
 
     device: Arc<Device>,
     queues: Queues,
@@ -45,6 +59,7 @@ impl AssetManager {
             scenes: HashMap::new(),
             meshes: HashMap::new(),
             textures,
+            materials: HashMap::new(),
             queues,
             device,
         }
@@ -55,6 +70,130 @@ impl AssetManager {
         Arc::new(RwLock::new(Mesh::new(name, vertices, indices, self.textures.get(DEFAULT_TEXTURE_NAME).unwrap().clone(), self.queues.graphics_queue())))
     }
 
+    /// Loads meshes from Wavefront OBJ file. Every group in the file becomes a separate mesh.
+    /// If file contains only one group the mesh gets the given name, otherwise meshes are named `name.group_name`.
+    /// Diffuse textures from MTL file are loaded and set on meshes, meshes without one use the default texture.
+    /// Loaded meshes and textures are not added to asset manager.
+    pub fn load_obj<S: Into<String>, P: AsRef<Path>>(&self, name: S, path: P) -> Result<Vec<Arc<RwLock<Mesh>>>, ObjLoadError> {
+        let name = name.into();
+        let groups = obj::load_obj(path)?;
+        let single_group = groups.len() == 1;
+
+        let mut mesh_names: Vec<String> = Vec::with_capacity(groups.len());
+        let mut textures: HashMap<PathBuf, Arc<Texture>> = HashMap::new();
+        let mut meshes = Vec::with_capacity(groups.len());
+
+        for (index, group) in groups.into_iter().enumerate() {
+            let mut mesh_name = if single_group { name.clone() } else { format!("{}.{}", name, group.name) };
+            if mesh_names.contains(&mesh_name) {
+                mesh_name = format!("{}.{}", mesh_name, index);
+            }
+            mesh_names.push(mesh_name.clone());
+
+            let mesh = self.create_mesh(mesh_name, group.vertices, group.indices);
+
+            if let Some(texture_path) = group.diffuse_texture {
+                let texture = match textures.get(&texture_path) {
+                    Some(texture) => texture.clone(),
+                    None => {
+                        let image = image::open(&texture_path).map_err(|err| ObjLoadError::TextureLoadError(texture_path.clone(), err))?;
+                        let texture_name = format!("{}.{}", name, group.material_name.unwrap_or_default());
+                        let texture = self.create_texture(texture_name, image);
+                        textures.insert(texture_path, texture.clone());
+                        texture
+                    }
+                };
+                mesh.write().unwrap().set_texture(texture);
+            }
+
+            meshes.push(mesh);
+        }
+
+        Ok(meshes)
+    }
+
+    /// Loads default scene from glTF 2.0 file (.gltf or .glb). The scene is named after the file.
+    /// Every node becomes an object with world space position, rotation and scale. Nodes with meshes made of
+    /// several primitives get one object per primitive. Meshes and embedded textures are added to asset manager
+    /// with names prefixed by the scene name. The first perspective camera and all lights are used by the scene.
+    pub fn load_gltf<P: AsRef<Path>>(&mut self, path: P) -> Result<Scene, GltfLoadError> {
+        let path = path.as_ref();
+        let scene_name = path.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let gltf_scene = gltf_import::load_gltf(path)?;
+
+        let textures: Vec<Arc<Texture>> = gltf_scene.images.into_iter().map(|(image_name, image)| {
+            let texture = self.create_texture(format!("{}.{}", scene_name, image_name), image);
+            self.add_texture(texture.clone());
+            texture
+        }).collect();
+
+        let meshes: Vec<Vec<Arc<RwLock<Mesh>>>> = gltf_scene.meshes.into_iter().map(|gltf_mesh| {
+            let single_primitive = gltf_mesh.primitives.len() == 1;
+            let mesh_name = gltf_mesh.name;
+            gltf_mesh.primitives.into_iter().enumerate().map(|(index, primitive)| {
+                let name = if single_primitive {
+                    format!("{}.{}", scene_name, mesh_name)
+                } else {
+                    format!("{}.{}.{}", scene_name, mesh_name, index)
+                };
+                let mesh = self.create_mesh(name, primitive.vertices, primitive.indices);
+                if let Some(texture) = primitive.image.and_then(|image| textures.get(image)) {
+                    mesh.write().unwrap().set_texture(texture.clone());
+                }
+                self.add_mesh(mesh.clone());
+                mesh
+            }).collect()
+        }).collect();
+
+        let mut camera = Camera::new();
+        if let Some(gltf_camera) = gltf_scene.camera {
+            camera.set_position_vec3(gltf_camera.position);
+            camera.set_yaw(gltf_camera.yaw);
+            camera.set_pitch(gltf_camera.pitch);
+            camera.set_fov(gltf_camera.fov);
+            camera.set_near_plane(gltf_camera.near_plane);
+            if let Some(far_plane) = gltf_camera.far_plane {
+                camera.set_far_plane(far_plane);
+            }
+        }
+
+        let mut scene = Scene::new(scene_name, camera);
+        if !gltf_scene.lights.is_empty() {
+            scene.clear_lights();
+        }
+        for gltf_light in gltf_scene.lights {
+            let mut light = Light::point(gltf_light.position.x, gltf_light.position.y, gltf_light.position.z);
+            light.set_kind(gltf_light.kind);
+            light.set_direction(gltf_light.direction.x, gltf_light.direction.y, gltf_light.direction.z);
+            light.set_color(gltf_light.color.x, gltf_light.color.y, gltf_light.color.z);
+            if let Some((inner, outer)) = gltf_light.cutoff_angles {
+                light.set_cutoff_angles(inner, outer);
+            }
+            scene.add_light(light);
+        }
+
+        for node in gltf_scene.nodes {
+            let object = ObjectBuilder::new(node.name)
+                                       .with_position(node.position.x, node.position.y, node.position.z)
+                                       .with_rotation(node.rotation)
+                                       .with_scale(node.scale.x, node.scale.y, node.scale.z)
+                                       .build();
+
+            match node.mesh.and_then(|mesh| meshes.get(mesh)).filter(|meshes| !meshes.is_empty()) {
+                Some(primitive_meshes) => {
+                    for mesh in primitive_meshes {
+                        let mut object = object.clone();
+                        object.set_mesh(mesh.clone());
+                        scene.add_object(object);
+                    }
+                }
+                None => scene.add_object(object),
+            }
+        }
+
+        Ok(scene)
+    }
+
     /// Adds mesh to asset manager. Meshes need to have unique name. 
     /// If two meshes have the same name, the old mesh will be replaced with the new one.
     pub fn add_mesh(&mut self, mesh: Arc<RwLock<Mesh>>) {
@@ -85,6 +224,11 @@ impl AssetManager {
         Arc::new(Texture::new(name, image, self.queues.graphics_queue(), self.device.clone()))
     }
 
+    /// Creates texture from loaded image without sRGB conversion. Used for normal and specular maps.
+    pub fn create_linear_texture<S: Into<String>>(&self, name: S, image: DynamicImage) -> Arc<Texture> {
+        Arc::new(Texture::new_linear(name, image, self.queues.graphics_queue(), self.device.clone()))
+    }
+
     /// Adds texture to asset manager. Textures need to have unique name. 
     /// If two textures have the same name, the old texture will be replaced with the new one.
     pub fn add_texture(&mut self, texture: Arc<Texture>) {
@@ -108,6 +252,42 @@ impl AssetManager {
         }
     }
 
+    /// Creates a new material with default parameters.
+    pub fn create_material<S: Into<String>>(&self, name: S) -> Arc<RwLock<Material>> {
+        Arc::new(RwLock::new(Material::new(name)))
+    }
+
+    /// Adds material to asset manager. Materials need to have unique name.
+    /// If two materials have the same name, the old material will be replaced with the new one.
+    pub fn add_material(&mut self, material: Arc<RwLock<Material>>) {
+        let name = material.read().unwrap().name().to_string();
+        self.materials.insert(name, material);
+    }
+
+    /// Returns a material with the given name.
+    pub fn material(&self, name: &str) -> Option<Arc<RwLock<Material>>> {
+        self.materials.get(name).cloned()
+    }
+
+    /// Removes and returns a material with the given name.
+    pub fn remove_material(&mut self, name: &str) -> Option<Arc<RwLock<Material>>> {
+        self.materials.remove(name)
+    }
+
+    /// Saves scene to RON file, see `SceneFile` for description of the format.
+    /// Meshes, textures and materials are stored as names, so they have to be added to asset manager before loading the scene.
+    pub fn save_scene<P: AsRef<Path>>(&self, scene: &Scene, path: P) -> Result<(), SceneFileError> {
+        let content = SceneFile::from_scene(scene).to_ron()?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Loads scene from RON file saved with `save_scene`. Meshes, textures and materials used by objects are taken from asset manager.
+    pub fn load_scene<P: AsRef<Path>>(&self, path: P) -> Result<Scene, SceneFileError> {
+        let content = std::fs::read_to_string(path)?;
+        SceneFile::from_ron(&content)?.into_scene(|name| self.mesh(name), |name| self.texture(name), |name| self.material(name))
+    }
+
     /// Adds scene to asset manager. Scenes need to have unique name. 
     /// If two scenes have the same name, the old scene will be replaced with the new one.
     pub fn add_scene(&mut self, scene: Scene) {
@@ -119,6 +299,13 @@ impl AssetManager {
         self.scenes.get_mut(name)
     }
 
+    /// Returns sorted names of scenes stored in asset manager. Active scene isn't included.
+    pub fn scene_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.scenes.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        names
+    }
+
     /// Removes and returns a scene with given name.
     pub fn remove_scene(&mut self, name: &str) -> Option<Scene> {
         self.scenes.remove(name)
@@ -0,0 +1,432 @@
+use nalgebra_glm::{U3, Mat4, Vec3, Vec4};
+use nalgebra_glm as glm;
+
+/// Axis aligned box containing a mesh or an object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl BoundingBox {
+    /// Creates new box with given corners.
+    pub fn new(min: (f32, f32, f32), max: (f32, f32, f32)) -> Self {
+        BoundingBox {
+            min: Vec3::new(min.0, min.1, min.2),
+            max: Vec3::new(max.0, max.1, max.2),
+        }
+    }
+
+    /// Returns the smallest box containing all points. Box of no points is a single point at the origin.
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]>>(points: I) -> Self {
+        let mut points = points.into_iter().map(Vec3::from);
+        let first = match points.next() {
+            Some(point) => point,
+            None => return BoundingBox { min: Vec3::zeros(), max: Vec3::zeros() },
+        };
+
+        let (min, max) = points.fold((first, first), |(min, max), point| (glm::min2(&min, &point), glm::max2(&max, &point)));
+        BoundingBox { min, max }
+    }
+
+    /// Returns corner with the smallest coordinates.
+    pub fn min(&self) -> (f32, f32, f32) {
+        (self.min.x, self.min.y, self.min.z)
+    }
+
+    /// Returns corner with the largest coordinates.
+    pub fn max(&self) -> (f32, f32, f32) {
+        (self.max.x, self.max.y, self.max.z)
+    }
+
+    /// Returns center of the box.
+    pub fn center(&self) -> (f32, f32, f32) {
+        let center = self.center_vec3();
+        (center.x, center.y, center.z)
+    }
+
+    /// Returns half of the box size along each axis.
+    pub fn extents(&self) -> (f32, f32, f32) {
+        let extents = self.extents_vec3();
+        (extents.x, extents.y, extents.z)
+    }
+
+    /// Returns the smallest axis aligned box containing this box transformed by matrix.
+    pub fn transform(&self, matrix: &Mat4) -> BoundingBox {
+        let center = self.center_vec3();
+        let extents = self.extents_vec3();
+
+        let new_center = (matrix * Vec4::new(center.x, center.y, center.z, 1.0)).xyz();
+        let new_extents = Vec3::from_fn(|row, _| {
+            (0 .. 3).map(|col| matrix[(row, col)].abs() * extents[col]).sum()
+        });
+
+        BoundingBox {
+            min: new_center - new_extents,
+            max: new_center + new_extents,
+        }
+    }
+
+    /// Returns sphere containing the whole box.
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        let center = self.center_vec3();
+        BoundingSphere {
+            center,
+            radius: self.extents_vec3().norm(),
+        }
+    }
+
+    fn center_vec3(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    fn extents_vec3(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+}
+
+/// Sphere containing a mesh or an object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    center: Vec3,
+    radius: f32,
+}
+
+impl BoundingSphere {
+    /// Creates new sphere.
+    pub fn new(center: (f32, f32, f32), radius: f32) -> Self {
+        BoundingSphere {
+            center: Vec3::new(center.0, center.1, center.2),
+            radius,
+        }
+    }
+
+    /// Returns center of the sphere.
+    pub fn center(&self) -> (f32, f32, f32) {
+        (self.center.x, self.center.y, self.center.z)
+    }
+
+    /// Returns radius of the sphere.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Returns sphere containing this sphere transformed by matrix. Radius is scaled by the largest scale of the matrix.
+    pub fn transform(&self, matrix: &Mat4) -> BoundingSphere {
+        let center = (matrix * Vec4::new(self.center.x, self.center.y, self.center.z, 1.0)).xyz();
+        let scale = (0 .. 3).map(|col| Vec3::new(matrix[(0, col)], matrix[(1, col)], matrix[(2, col)]).norm())
+                            .fold(0.0, f32::max);
+
+        BoundingSphere {
+            center,
+            radius: self.radius * scale,
+        }
+    }
+}
+
+/// Volume visible by a camera, described by six planes pointing inside.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts frustum planes from view projection matrix, which maps visible points to vulkan clip space
+    /// (x and y from -w to w, depth from 0 to w).
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let row = |i: usize| Vec4::new(matrix[(i, 0)], matrix[(i, 1)], matrix[(i, 2)], matrix[(i, 3)]);
+
+        let planes = [
+            row(3) + row(0), // left
+            row(3) - row(0), // right
+            row(3) + row(1), // bottom
+            row(3) - row(1), // top
+            row(2),          // near
+            row(3) - row(2), // far
+        ];
+
+        Frustum {
+            planes: [
+                normalize_plane(planes[0]), normalize_plane(planes[1]), normalize_plane(planes[2]),
+                normalize_plane(planes[3]), normalize_plane(planes[4]), normalize_plane(planes[5]),
+            ],
+        }
+    }
+
+    /// Returns true if box is at least partially inside the frustum.
+    /// Some boxes near frustum corners are reported as visible even if they are outside.
+    pub fn intersects_box(&self, bounding_box: &BoundingBox) -> bool {
+        let center = bounding_box.center_vec3();
+        let extents = bounding_box.extents_vec3();
+
+        self.planes.iter().all(|plane| {
+            let normal = plane.xyz();
+            let distance = glm::dot(&normal, &center) + plane.w;
+            let radius = glm::dot(&glm::abs(&normal), &extents);
+            distance + radius >= 0.0
+        })
+    }
+
+    /// Returns true if sphere is at least partially inside the frustum.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| glm::dot(&plane.xyz(), &sphere.center) + plane.w >= -sphere.radius)
+    }
+}
+
+/// Half-line used to pick objects. Distances along the ray are measured in lengths of its direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    origin: Vec3,
+    direction: Vec3,
+}
+
+impl Ray {
+    /// Creates new ray starting at origin. Direction is normalized.
+    pub fn new(origin: (f32, f32, f32), direction: (f32, f32, f32)) -> Self {
+        Ray {
+            origin: Vec3::new(origin.0, origin.1, origin.2),
+            direction: glm::normalize(&Vec3::new(direction.0, direction.1, direction.2)),
+        }
+    }
+
+    /// Returns point where the ray starts.
+    pub fn origin(&self) -> (f32, f32, f32) {
+        (self.origin.x, self.origin.y, self.origin.z)
+    }
+
+    /// Returns direction of the ray.
+    pub fn direction(&self) -> (f32, f32, f32) {
+        (self.direction.x, self.direction.y, self.direction.z)
+    }
+
+    /// Returns point at given distance along the ray.
+    pub fn point_at(&self, distance: f32) -> (f32, f32, f32) {
+        let point = self.origin + self.direction * distance;
+        (point.x, point.y, point.z)
+    }
+
+    /// Returns ray transformed by matrix. Direction isn't normalized again,
+    /// so distances along transformed ray match distances along this ray.
+    pub fn transform(&self, matrix: &Mat4) -> Ray {
+        Ray {
+            origin: (matrix * Vec4::new(self.origin.x, self.origin.y, self.origin.z, 1.0)).xyz(),
+            direction: (matrix * Vec4::new(self.direction.x, self.direction.y, self.direction.z, 0.0)).xyz(),
+        }
+    }
+
+    /// Returns distance to the nearest point of the box hit by the ray.
+    /// Distance is zero when the ray starts inside the box.
+    pub fn intersects_box(&self, bounding_box: &BoundingBox) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = std::f32::INFINITY;
+
+        for axis in 0 .. 3 {
+            if self.direction[axis] == 0.0 {
+                if self.origin[axis] < bounding_box.min[axis] || self.origin[axis] > bounding_box.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let first = (bounding_box.min[axis] - self.origin[axis]) / self.direction[axis];
+            let second = (bounding_box.max[axis] - self.origin[axis]) / self.direction[axis];
+            near = near.max(first.min(second));
+            far = far.min(first.max(second));
+            if near > far {
+                return None;
+            }
+        }
+
+        Some(near)
+    }
+
+    /// Returns distance to the point where the ray hits triangle. Both sides of the triangle can be hit.
+    pub fn intersects_triangle(&self, a: &Vec3, b: &Vec3, c: &Vec3) -> Option<f32> {
+        const EPSILON: f32 = 1e-7;
+
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = glm::cross::<f32, U3>(&self.direction, &edge_2);
+        let determinant = glm::dot(&edge_1, &p);
+        if determinant.abs() < EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let t = self.origin - a;
+        let u = glm::dot(&t, &p) * inverse_determinant;
+        if u < 0.0 || u > 1.0 {
+            return None;
+        }
+
+        let q = glm::cross::<f32, U3>(&t, &edge_1);
+        let v = glm::dot(&self.direction, &q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = glm::dot(&edge_2, &q) * inverse_determinant;
+        if distance >= 0.0 {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    /// Returns distance to the point where the ray hits plane going through point with given normal.
+    pub fn intersects_plane(&self, point: &Vec3, normal: &Vec3) -> Option<f32> {
+        const EPSILON: f32 = 1e-7;
+
+        let denominator = glm::dot(normal, &self.direction);
+        if denominator.abs() < EPSILON {
+            return None;
+        }
+
+        let distance = glm::dot(normal, &(point - self.origin)) / denominator;
+        if distance >= 0.0 {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    /// Returns position of the point on the line which is closest to the ray,
+    /// measured from line origin in lengths of line direction. Lines parallel to the ray don't have such point.
+    pub fn closest_point_on_line(&self, line_origin: &Vec3, line_direction: &Vec3) -> Option<f32> {
+        const EPSILON: f32 = 1e-7;
+
+        let w = line_origin - self.origin;
+        let a = glm::dot(line_direction, line_direction);
+        let b = glm::dot(line_direction, &self.direction);
+        let c = glm::dot(&self.direction, &self.direction);
+        let d = glm::dot(line_direction, &w);
+        let e = glm::dot(&self.direction, &w);
+
+        let denominator = a * c - b * b;
+        if denominator.abs() < EPSILON * a * c {
+            return None;
+        }
+        Some((b * e - c * d) / denominator)
+    }
+}
+
+fn normalize_plane(plane: Vec4) -> Vec4 {
+    let length = plane.xyz().norm();
+    if length > 0.0 {
+        plane / length
+    } else {
+        plane
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::camera::correction_matrix;
+
+    fn test_frustum() -> Frustum {
+        let projection = correction_matrix() * glm::perspective(1.0, 90.0f32.to_radians(), 0.1, 100.0);
+        let view = glm::look_at(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 0.0, -1.0), &Vec3::y());
+        Frustum::from_matrix(&(projection * view))
+    }
+
+    #[test]
+    fn box_of_points_contains_all_points() {
+        let bounding_box = BoundingBox::from_points(vec![[1.0, -2.0, 0.5], [-1.0, 3.0, 0.0], [0.0, 0.0, 2.0]]);
+        assert_eq!((-1.0, -2.0, 0.0), bounding_box.min());
+        assert_eq!((1.0, 3.0, 2.0), bounding_box.max());
+        assert_eq!((0.0, 0.5, 1.0), bounding_box.center());
+    }
+
+    #[test]
+    fn transformed_box_contains_rotated_and_translated_box() {
+        let bounding_box = BoundingBox::new((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0));
+        let matrix = glm::translate(&glm::identity(), &Vec3::new(5.0, 0.0, 0.0))
+                   * glm::rotate_y(&glm::identity(), 45.0f32.to_radians())
+                   * glm::scale(&glm::identity(), &Vec3::new(2.0, 1.0, 1.0));
+
+        let (min_x, min_y, _) = bounding_box.transform(&matrix).min();
+        let (max_x, max_y, _) = bounding_box.transform(&matrix).max();
+        let half_width = 3.0 * 45.0f32.to_radians().cos();
+        assert!((min_x - (5.0 - half_width)).abs() < 0.0001 && (max_x - (5.0 + half_width)).abs() < 0.0001);
+        assert!((min_y + 1.0).abs() < 0.0001 && (max_y - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn transformed_sphere_uses_largest_scale() {
+        let sphere = BoundingSphere::new((1.0, 0.0, 0.0), 1.0);
+        let matrix = glm::scale(&glm::identity(), &Vec3::new(1.0, 3.0, 2.0));
+
+        let transformed = sphere.transform(&matrix);
+        assert_eq!((1.0, 0.0, 0.0), transformed.center());
+        assert_eq!(3.0, transformed.radius());
+    }
+
+    #[test]
+    fn objects_in_front_of_camera_are_inside_frustum() {
+        let frustum = test_frustum();
+
+        assert!(frustum.intersects_box(&BoundingBox::new((-1.0, -1.0, -6.0), (1.0, 1.0, -4.0))));
+        assert!(frustum.intersects_sphere(&BoundingSphere::new((0.0, 0.0, -5.0), 1.0)));
+        // partially visible box crossing the right plane
+        assert!(frustum.intersects_box(&BoundingBox::new((4.0, -1.0, -6.0), (6.0, 1.0, -4.0))));
+    }
+
+    #[test]
+    fn objects_behind_beside_or_too_far_from_camera_are_outside_frustum() {
+        let frustum = test_frustum();
+
+        assert!(!frustum.intersects_box(&BoundingBox::new((-1.0, -1.0, 4.0), (1.0, 1.0, 6.0))));
+        assert!(!frustum.intersects_box(&BoundingBox::new((10.0, -1.0, -6.0), (12.0, 1.0, -4.0))));
+        assert!(!frustum.intersects_box(&BoundingBox::new((-1.0, -1.0, -200.0), (1.0, 1.0, -150.0))));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new((0.0, 20.0, -5.0), 1.0)));
+    }
+
+    #[test]
+    fn ray_hits_nearest_side_of_box() {
+        let bounding_box = BoundingBox::new((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0));
+
+        assert_eq!(Some(4.0), Ray::new((0.0, 0.0, 5.0), (0.0, 0.0, -1.0)).intersects_box(&bounding_box));
+        assert_eq!(Some(0.0), Ray::new((0.0, 0.5, 0.0), (1.0, 0.0, 0.0)).intersects_box(&bounding_box));
+        assert_eq!(None, Ray::new((0.0, 0.0, 5.0), (0.0, 0.0, 1.0)).intersects_box(&bounding_box));
+        assert_eq!(None, Ray::new((0.0, 2.0, 5.0), (0.0, 0.0, -1.0)).intersects_box(&bounding_box));
+    }
+
+    #[test]
+    fn ray_hits_triangle_only_inside_its_edges() {
+        let (a, b, c) = (Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let distance = Ray::new((0.0, 0.0, 3.0), (0.0, 0.0, -1.0)).intersects_triangle(&a, &b, &c).unwrap();
+        assert!((distance - 3.0).abs() < 0.0001);
+        assert_eq!(None, Ray::new((0.9, 0.9, 3.0), (0.0, 0.0, -1.0)).intersects_triangle(&a, &b, &c));
+        assert_eq!(None, Ray::new((0.0, 0.0, 3.0), (0.0, 0.0, 1.0)).intersects_triangle(&a, &b, &c));
+    }
+
+    #[test]
+    fn transformed_ray_keeps_distances() {
+        let ray = Ray::new((0.0, 0.0, 10.0), (0.0, 0.0, -1.0));
+        let model = glm::translate(&glm::identity(), &Vec3::new(0.0, 0.0, 2.0)) * glm::scale(&glm::identity(), &Vec3::new(2.0, 2.0, 2.0));
+        let local_ray = ray.transform(&model.try_inverse().unwrap());
+
+        let bounding_box = BoundingBox::new((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0));
+        assert_eq!(ray.intersects_box(&bounding_box.transform(&model)), local_ray.intersects_box(&bounding_box));
+    }
+
+    #[test]
+    fn ray_hits_plane_only_in_front_of_origin() {
+        let (point, normal) = (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        assert_eq!(Some(4.0), Ray::new((2.0, 5.0, 0.0), (0.0, -1.0, 0.0)).intersects_plane(&point, &normal));
+        assert_eq!(None, Ray::new((2.0, 5.0, 0.0), (0.0, 1.0, 0.0)).intersects_plane(&point, &normal));
+        assert_eq!(None, Ray::new((2.0, 5.0, 0.0), (1.0, 0.0, 0.0)).intersects_plane(&point, &normal));
+    }
+
+    #[test]
+    fn closest_point_on_line_is_measured_in_line_direction_lengths() {
+        let ray = Ray::new((3.0, 0.0, 5.0), (0.0, 0.0, -1.0));
+        let line_origin = Vec3::new(1.0, 0.0, 0.0);
+
+        assert_eq!(Some(1.0), ray.closest_point_on_line(&line_origin, &Vec3::new(2.0, 0.0, 0.0)));
+        assert_eq!(None, ray.closest_point_on_line(&line_origin, &Vec3::new(0.0, 0.0, 1.0)));
+    }
+}
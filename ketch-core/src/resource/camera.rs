@@ -2,7 +2,8 @@ use crate::settings::Settings;
 use std::cell::RefCell;
 use std::rc::Rc;
 use crate::renderer::shader::vertex_shader::ty::TransformationData;
-use nalgebra_glm::{U3, Vec3, Mat4};
+use crate::resource::bounds::{Frustum, Ray};
+use nalgebra_glm::{U3, Vec3, Vec4, Mat4};
 use nalgebra_glm as glm;
 
 const DEFAULT_NEAR_PLANE: f32 = 0.1;
@@ -170,27 +171,67 @@ impl Camera {
     pub fn projection_matrix(&self, window_width: f32, window_height: f32) -> Mat4 {
         let aspect_ratio = window_width / window_height;
 
-        let correction_matrix: Mat4 = Mat4::new(1.0, 0.0, 0.0, 0.0,
-                                                0.0,-1.0, 0.0, 0.0,
-                                                0.0, 0.0, 0.5, 0.5,
-                                                0.0, 0.0, 0.0, 1.0);
-
         let proj_matrix = glm::perspective(aspect_ratio, self.fov, self.near_plane, self.far_plane);
 
-        return correction_matrix * proj_matrix;
+        return correction_matrix() * proj_matrix;
+    }
+
+    /// Returns volume visible by this camera.
+    pub fn frustum(&self, window_width: f32, window_height: f32) -> Frustum {
+        Frustum::from_matrix(&(self.projection_matrix(window_width, window_height) * self.view_matrix()))
     }
 
-    /// Returns model, view and projection matrix as uniform data. 
-    /// Model should be updated with model matrix from Object.
+    /// Returns ray going from the camera through the point on the window given in physical pixels,
+    /// with the origin in the top left corner of the window.
+    pub fn screen_ray(&self, x: f32, y: f32, window_width: f32, window_height: f32) -> Ray {
+        let inverse = (self.projection_matrix(window_width, window_height) * self.view_matrix()).try_inverse()
+                                                                                                .unwrap_or_else(Mat4::identity);
+        let ndc_x = 2.0 * x / window_width - 1.0;
+        let ndc_y = 2.0 * y / window_height - 1.0;
+
+        let unproject = |depth: f32| {
+            let point = inverse * Vec4::new(ndc_x, ndc_y, depth, 1.0);
+            point.xyz() / point.w
+        };
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        let direction = far - near;
+
+        Ray::new((near.x, near.y, near.z), (direction.x, direction.y, direction.z))
+    }
+
+    /// Returns position of the point on the window in physical pixels, with the origin in the top left corner
+    /// of the window, the same as in `screen_ray`. Points behind the camera can't be shown on the window.
+    pub fn world_to_screen(&self, point: (f32, f32, f32), window_width: f32, window_height: f32) -> Option<(f32, f32)> {
+        let clip = self.projection_matrix(window_width, window_height) * self.view_matrix() * Vec4::new(point.0, point.1, point.2, 1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let (ndc_x, ndc_y) = (clip.x / clip.w, clip.y / clip.w);
+        Some(((ndc_x + 1.0) * window_width / 2.0, (ndc_y + 1.0) * window_height / 2.0))
+    }
+
+    /// Returns view and projection matrix as uniform data. 
+    /// Model matrices of objects are passed to shaders as instance data.
     pub fn as_uniform_data(&self, window_width: f32, window_height: f32) -> TransformationData {
         TransformationData {
-            model: Mat4::identity().into(),
             view: self.view_matrix().into(),
             proj: self.projection_matrix(window_width, window_height).into(),
+            light_space: Mat4::identity().into(),
         }
     }
 }
 
+/// Returns matrix which converts OpenGL clip space used by projection matrices to vulkan clip space
+/// (y axis pointing down and depth in range from 0 to 1).
+pub fn correction_matrix() -> Mat4 {
+    Mat4::new(1.0, 0.0, 0.0, 0.0,
+              0.0,-1.0, 0.0, 0.0,
+              0.0, 0.0, 0.5, 0.5,
+              0.0, 0.0, 0.0, 1.0)
+}
+
 pub enum Direction {
     Up,
     Down,
@@ -372,4 +413,32 @@ mod tests {
         assert_eq!(MIN_FOV, camera.fov());
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn screen_ray_through_window_center_follows_camera_front() {
+        let camera = Camera::new();
+        let ray = camera.screen_ray(400.0, 300.0, 800.0, 600.0);
+
+        let (x, y, z) = ray.origin();
+        assert!(x.abs() < 0.0001 && y.abs() < 0.0001 && (z - (3.0 - camera.near_plane())).abs() < 0.0001);
+        let (x, y, z) = ray.direction();
+        assert!(x.abs() < 0.0001 && y.abs() < 0.0001 && (z + 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn screen_ray_through_top_left_corner_points_up_and_left() {
+        let camera = Camera::new();
+        let (x, y, _) = camera.screen_ray(0.0, 0.0, 800.0, 600.0).direction();
+
+        assert!(x < 0.0 && y > 0.0);
+    }
+
+    #[test]
+    fn point_on_screen_ray_is_shown_under_the_same_pixel() {
+        let camera = Camera::new();
+        let ray = camera.screen_ray(200.0, 100.0, 800.0, 600.0);
+
+        let (x, y) = camera.world_to_screen(ray.point_at(5.0), 800.0, 600.0).unwrap();
+        assert!((x - 200.0).abs() < 0.01 && (y - 100.0).abs() < 0.01);
+        assert_eq!(None, camera.world_to_screen((0.0, 0.0, 5.0), 800.0, 600.0));
+    }
+}
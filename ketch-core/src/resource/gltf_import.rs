@@ -0,0 +1,360 @@
+use crate::resource::light::LightKind;
+use crate::resource::mesh::Vertex;
+use crate::resource::obj::compute_normals;
+use crate::resource::resource_error::GltfLoadError;
+use image::{DynamicImage, ImageBuffer};
+use nalgebra_glm::{Qua, Vec3};
+use nalgebra_glm as glm;
+
+use std::path::Path;
+
+/// Triangles of one glTF primitive together with index of its base color image.
+pub struct GltfPrimitive {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub image: Option<usize>,
+}
+
+/// Mesh from glTF file. Every primitive becomes a separate mesh in the engine.
+pub struct GltfMesh {
+    pub name: String,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+/// Node with its transformation converted to world space.
+pub struct GltfNode {
+    pub name: String,
+    pub mesh: Option<usize>,
+    pub position: Vec3,
+    pub rotation: Qua<f32>,
+    pub scale: Vec3,
+}
+
+/// Perspective camera placed in world space. Yaw, pitch and fov are in degrees.
+pub struct GltfCamera {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+    pub near_plane: f32,
+    pub far_plane: Option<f32>,
+}
+
+/// Light placed in world space. Cutoff angles of spot lights are in degrees.
+pub struct GltfLight {
+    pub kind: LightKind,
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub cutoff_angles: Option<(f32, f32)>,
+}
+
+/// Contents of the default scene from glTF file.
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    pub meshes: Vec<GltfMesh>,
+    pub images: Vec<(String, DynamicImage)>,
+    pub camera: Option<GltfCamera>,
+    pub lights: Vec<GltfLight>,
+}
+
+/// World space transformation of a node.
+#[derive(Clone, Copy)]
+struct Transform {
+    translation: Vec3,
+    rotation: Qua<f32>,
+    scale: Vec3,
+}
+
+impl Transform {
+    fn identity() -> Self {
+        Transform {
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            rotation: glm::quat(0.0, 0.0, 0.0, 1.0),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// Applies local transformation of a child node on top of this one.
+    /// Shear resulting from non uniform scale of rotated children is ignored.
+    fn then(&self, (translation, rotation, scale): ([f32; 3], [f32; 4], [f32; 3])) -> Self {
+        let translation = Vec3::from(translation).component_mul(&self.scale);
+        let rotation = glm::quat(rotation[0], rotation[1], rotation[2], rotation[3]);
+        Transform {
+            translation: self.translation + glm::quat_rotate_vec3(&self.rotation, &translation),
+            rotation: self.rotation * rotation,
+            scale: self.scale.component_mul(&Vec3::from(scale)),
+        }
+    }
+}
+
+/// Loads default scene (or the first one if default isn't set) from .gltf or .glb file.
+/// Buffers and images referenced by the file are resolved relative to it.
+pub fn load_gltf<P: AsRef<Path>>(path: P) -> Result<GltfScene, GltfLoadError> {
+    let (document, buffers, images) = gltf::import(path)?;
+    let scene = document.default_scene()
+                        .or_else(|| document.scenes().next())
+                        .ok_or(GltfLoadError::MissingSceneError)?;
+
+    let meshes = document.meshes().map(|mesh| {
+        let name = mesh.name().map(|name| name.to_string()).unwrap_or_else(|| mesh.index().to_string());
+        let primitives = mesh.primitives().map(|primitive| {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                return Err(GltfLoadError::UnsupportedPrimitiveModeError(name.clone(), primitive.mode()));
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let positions: Vec<[f32; 3]> = reader.read_positions()
+                                                 .ok_or_else(|| GltfLoadError::MissingPositionsError(name.clone()))?
+                                                 .collect();
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0 .. positions.len() as u32).collect(),
+            };
+            let normals: Vec<[f32; 3]> = match reader.read_normals() {
+                Some(normals) => normals.collect(),
+                None => compute_normals(&positions.concat(), &indices),
+            };
+            let tex_coords: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+                Some(tex_coords) => tex_coords.into_f32().collect(),
+                None => vec![[0.0, 0.0]; positions.len()],
+            };
+
+            // glTF places texture origin in the top left corner, textures in the engine are flipped vertically.
+            let vertices = positions.into_iter().zip(normals).zip(tex_coords).map(|((position, normal), tex_coord)| Vertex {
+                position,
+                normal,
+                tex_coord: [tex_coord[0], 1.0 - tex_coord[1]],
+            }).collect();
+
+            let image = primitive.material()
+                                 .pbr_metallic_roughness()
+                                 .base_color_texture()
+                                 .map(|info| info.texture().source().index());
+
+            Ok(GltfPrimitive { vertices, indices, image })
+        }).collect::<Result<Vec<_>, GltfLoadError>>()?;
+
+        Ok(GltfMesh { name, primitives })
+    }).collect::<Result<Vec<_>, GltfLoadError>>()?;
+
+    let images = document.images().zip(images).map(|(image, data)| {
+        let name = image.name().map(|name| name.to_string()).unwrap_or_else(|| image.index().to_string());
+        Ok((name, to_dynamic_image(image.index(), data)?))
+    }).collect::<Result<Vec<_>, GltfLoadError>>()?;
+
+    let mut gltf_scene = GltfScene {
+        nodes: Vec::new(),
+        meshes,
+        images,
+        camera: None,
+        lights: Vec::new(),
+    };
+    for node in scene.nodes() {
+        add_node(&mut gltf_scene, &node, &Transform::identity());
+    }
+
+    Ok(gltf_scene)
+}
+
+/// Adds node and all of its children to the scene. Only the first camera is kept.
+fn add_node(scene: &mut GltfScene, node: &gltf::Node, parent_transform: &Transform) {
+    let transform = parent_transform.then(node.transform().decomposed());
+
+    if let (None, Some(camera)) = (&scene.camera, node.camera()) {
+        if let gltf::camera::Projection::Perspective(perspective) = camera.projection() {
+            // Cameras in glTF look along the negative z axis.
+            let front = glm::quat_rotate_vec3(&transform.rotation, &Vec3::new(0.0, 0.0, -1.0));
+            scene.camera = Some(GltfCamera {
+                position: transform.translation,
+                yaw: front.z.atan2(front.x).to_degrees(),
+                pitch: front.y.max(-1.0).min(1.0).asin().to_degrees(),
+                fov: perspective.yfov().to_degrees(),
+                near_plane: perspective.znear(),
+                far_plane: perspective.zfar(),
+            });
+        }
+    }
+
+    if let Some(light) = node.light() {
+        use gltf::khr_lights_punctual::Kind;
+
+        let (kind, cutoff_angles) = match light.kind() {
+            Kind::Point => (LightKind::Point, None),
+            Kind::Directional => (LightKind::Directional, None),
+            Kind::Spot { inner_cone_angle, outer_cone_angle } => {
+                (LightKind::Spot, Some((inner_cone_angle.to_degrees(), outer_cone_angle.to_degrees())))
+            }
+        };
+        // Lights in glTF shine along the negative z axis. Intensity is given in photometric units
+        // which don't map onto light intensity used by the engine, so only the color is imported.
+        scene.lights.push(GltfLight {
+            kind,
+            position: transform.translation,
+            direction: glm::quat_rotate_vec3(&transform.rotation, &Vec3::new(0.0, 0.0, -1.0)),
+            color: Vec3::from(light.color()),
+            cutoff_angles,
+        });
+    }
+
+    scene.nodes.push(GltfNode {
+        name: node.name().map(|name| name.to_string()).unwrap_or_else(|| format!("node{}", node.index())),
+        mesh: node.mesh().map(|mesh| mesh.index()),
+        position: transform.translation,
+        rotation: transform.rotation,
+        scale: transform.scale,
+    });
+
+    for child in node.children() {
+        add_node(scene, &child, &transform);
+    }
+}
+
+/// Converts decoded glTF image to image which can be used to create texture.
+fn to_dynamic_image(image_index: usize, data: gltf::image::Data) -> Result<DynamicImage, GltfLoadError> {
+    use gltf::image::Format;
+
+    let (width, height, pixels) = (data.width, data.height, data.pixels);
+    let image = match data.format {
+        Format::R8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        Format::R8G8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLumaA8),
+        Format::R8G8B8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+        Format::R8G8B8A8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8),
+        _ => None,
+    };
+    image.ok_or(GltfLoadError::UnsupportedImageFormatError(image_index, data.format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn assert_vec3_eq(expected: Vec3, actual: Vec3) {
+        assert!((expected - actual).norm() < EPSILON, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    /// Writes glTF file with one triangle in a separate binary buffer. Triangle has texture coordinates but no normals.
+    fn write_test_file(file_name: &str, nodes: &str, extra: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join("ketch_gltf_tests");
+        fs::create_dir_all(&directory).unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        for value in &[0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0] {
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        for index in &[0u16, 1, 2] {
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+        let buffer_name = format!("{}.bin", file_name);
+        fs::write(directory.join(&buffer_name), &buffer).unwrap();
+
+        let content = format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "scene": 0,
+            "scenes": [ {{ "nodes": [0] }} ],
+            "nodes": {},
+            "meshes": [ {{ "name": "triangle", "primitives": [ {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }}, "indices": 2 }} ] }} ],
+            "buffers": [ {{ "uri": "{}", "byteLength": {} }} ],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 36, "byteLength": 24 }},
+                {{ "buffer": 0, "byteOffset": 60, "byteLength": 6 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] }},
+                {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" }},
+                {{ "bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+            ]
+            {}
+        }}"#, nodes, buffer_name, buffer.len(), extra);
+
+        let path = directory.join(file_name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn mesh_is_loaded_with_computed_normals_and_flipped_tex_coords() {
+        let path = write_test_file("triangle.gltf", r#"[ { "name": "triangle", "mesh": 0 } ]"#, "");
+
+        let scene = load_gltf(&path).unwrap();
+        assert_eq!(1, scene.meshes.len());
+        assert_eq!("triangle", scene.meshes[0].name);
+
+        let primitive = &scene.meshes[0].primitives[0];
+        assert_eq!(vec![0, 1, 2], primitive.indices);
+        assert_eq!([1.0, 1.0], primitive.vertices[1].tex_coord);
+        for vertex in primitive.vertices.iter() {
+            assert_eq!([0.0, 0.0, 1.0], vertex.normal);
+        }
+        assert!(primitive.image.is_none());
+    }
+
+    #[test]
+    fn child_nodes_are_transformed_to_world_space() {
+        let path = write_test_file("hierarchy.gltf", r#"[
+            { "name": "parent", "translation": [1.0, 0.0, 0.0], "rotation": [0.0, 0.7071068, 0.0, 0.7071068], "scale": [2.0, 2.0, 2.0], "children": [1] },
+            { "name": "child", "mesh": 0, "translation": [0.0, 0.0, 1.0] }
+        ]"#, "");
+
+        let scene = load_gltf(&path).unwrap();
+        assert_eq!(2, scene.nodes.len());
+        assert_eq!("parent", scene.nodes[0].name);
+        assert_eq!(None, scene.nodes[0].mesh);
+
+        let child = &scene.nodes[1];
+        assert_eq!("child", child.name);
+        assert_eq!(Some(0), child.mesh);
+        assert_vec3_eq(Vec3::new(3.0, 0.0, 0.0), child.position);
+        let rotated = glm::quat_rotate_vec3(&child.rotation, &Vec3::new(1.0, 0.0, 0.0));
+        assert_vec3_eq(Vec3::new(0.0, 0.0, -1.0), rotated);
+        assert_vec3_eq(Vec3::new(2.0, 2.0, 2.0), child.scale);
+    }
+
+    #[test]
+    fn first_camera_and_all_lights_are_imported() {
+        let path = write_test_file("camera_and_light.gltf", r#"[
+            { "name": "root", "children": [1, 2, 3] },
+            { "name": "camera", "camera": 0, "translation": [0.0, 1.0, 5.0] },
+            { "name": "sun", "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+            { "name": "lamp", "translation": [2.0, 3.0, 4.0], "extensions": { "KHR_lights_punctual": { "light": 1 } } }
+        ]"#, r#",
+            "extensionsUsed": [ "KHR_lights_punctual" ],
+            "cameras": [ { "type": "perspective", "perspective": { "yfov": 0.5, "znear": 0.5, "zfar": 100.0 } } ],
+            "extensions": { "KHR_lights_punctual": { "lights": [
+                { "type": "directional", "color": [0.0, 0.0, 1.0] },
+                { "type": "point", "color": [1.0, 0.5, 0.25] }
+            ] } }
+        "#);
+
+        let scene = load_gltf(&path).unwrap();
+        assert_eq!(4, scene.nodes.len());
+
+        let camera = scene.camera.expect("camera wasn't imported");
+        assert_vec3_eq(Vec3::new(0.0, 1.0, 5.0), camera.position);
+        assert!((camera.yaw + 90.0).abs() < EPSILON);
+        assert!(camera.pitch.abs() < EPSILON);
+        assert!((camera.fov - 0.5f32.to_degrees()).abs() < EPSILON);
+        assert_eq!(0.5, camera.near_plane);
+        assert_eq!(Some(100.0), camera.far_plane);
+
+        assert_eq!(2, scene.lights.len());
+        assert_eq!(LightKind::Directional, scene.lights[0].kind);
+        assert_vec3_eq(Vec3::new(0.0, 0.0, -1.0), scene.lights[0].direction);
+        assert_eq!(LightKind::Point, scene.lights[1].kind);
+        assert_vec3_eq(Vec3::new(2.0, 3.0, 4.0), scene.lights[1].position);
+        assert_vec3_eq(Vec3::new(1.0, 0.5, 0.25), scene.lights[1].color);
+    }
+
+    #[test]
+    fn missing_file_returns_error() {
+        match load_gltf(std::env::temp_dir().join("ketch_gltf_tests").join("missing.gltf")) {
+            Err(GltfLoadError::GltfError(gltf::Error::Io(_))) => (),
+            _ => panic!("expected Io error"),
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use crate::renderer::shader::fragment_shader::ty::Light as LightUniformData;
+use crate::resource::camera::correction_matrix;
+use nalgebra_glm::{U3, Vec3, Mat4};
+use nalgebra_glm as glm;
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_INNER_CUTOFF_ANGLE: f32 = 12.5;
+const DEFAULT_OUTER_CUTOFF_ANGLE: f32 = 17.5;
+
+const SHADOW_NEAR_PLANE: f32 = 0.1;
+const SHADOW_FAR_PLANE: f32 = 100.0;
+/// Field of view in degrees used for shadows of point lights.
+const POINT_LIGHT_SHADOW_FOV: f32 = 90.0;
+/// Half of the width of the area around the origin covered by shadows of directional lights.
+const DIRECTIONAL_LIGHT_SHADOW_EXTENT: f32 = 10.0;
+/// Distance from the origin at which directional lights are placed when rendering shadows.
+const DIRECTIONAL_LIGHT_SHADOW_DISTANCE: f32 = 20.0;
+
+/// Type of the light source.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LightKind {
+    /// Light emitted in all directions from the light position.
+    Point,
+    /// Light coming from infinitely far away in the light direction. Position is ignored.
+    Directional,
+    /// Light emitted from the light position in a cone around the light direction.
+    Spot,
+}
+
+impl LightKind {
+    /// Returns value identifying this kind of light in shaders.
+    fn shader_value(self) -> u32 {
+        match self {
+            LightKind::Point => 0,
+            LightKind::Directional => 1,
+            LightKind::Spot => 2,
+        }
+    }
+}
+
+/// Light source illuminating the scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    kind: LightKind,
+
+    position: Vec3,
+    direction: Vec3,
+
+    color: Vec3,
+    intensity: f32,
+
+    constant_attenuation: f32,
+    linear_attenuation: f32,
+    quadratic_attenuation: f32,
+
+    inner_cutoff_angle: f32,
+    outer_cutoff_angle: f32,
+}
+
+impl Light {
+    /// Creates new white point light without attenuation.
+    pub fn point(x: f32, y: f32, z: f32) -> Self {
+        Light {
+            kind: LightKind::Point,
+            position: Vec3::new(x, y, z),
+            direction: Vec3::new(0.0, -1.0, 0.0),
+            color: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            constant_attenuation: 1.0,
+            linear_attenuation: 0.0,
+            quadratic_attenuation: 0.0,
+            inner_cutoff_angle: DEFAULT_INNER_CUTOFF_ANGLE,
+            outer_cutoff_angle: DEFAULT_OUTER_CUTOFF_ANGLE,
+        }
+    }
+
+    /// Creates new white directional light shining in the given direction.
+    pub fn directional(x: f32, y: f32, z: f32) -> Self {
+        Light {
+            kind: LightKind::Directional,
+            direction: Vec3::new(x, y, z),
+            .. Light::point(0.0, 0.0, 0.0)
+        }
+    }
+
+    /// Creates new white spot light placed at position and shining in the given direction.
+    pub fn spot(position: (f32, f32, f32), direction: (f32, f32, f32)) -> Self {
+        Light {
+            kind: LightKind::Spot,
+            direction: Vec3::new(direction.0, direction.1, direction.2),
+            .. Light::point(position.0, position.1, position.2)
+        }
+    }
+
+    /// Returns type of this light.
+    pub fn kind(&self) -> LightKind {
+        self.kind
+    }
+
+    /// Changes type of this light.
+    pub fn set_kind(&mut self, kind: LightKind) {
+        self.kind = kind;
+    }
+
+    /// Returns light position.
+    pub fn position(&self) -> (f32, f32, f32) {
+        (self.position.x, self.position.y, self.position.z)
+    }
+
+    /// Sets light position.
+    pub fn set_position(&mut self, x: f32, y: f32, z: f32) {
+        self.position = Vec3::new(x, y, z);
+    }
+
+    /// Returns direction in which directional and spot lights shine.
+    pub fn direction(&self) -> (f32, f32, f32) {
+        (self.direction.x, self.direction.y, self.direction.z)
+    }
+
+    /// Sets direction in which directional and spot lights shine.
+    pub fn set_direction(&mut self, x: f32, y: f32, z: f32) {
+        self.direction = Vec3::new(x, y, z);
+    }
+
+    /// Returns light color.
+    pub fn color(&self) -> (f32, f32, f32) {
+        (self.color.x, self.color.y, self.color.z)
+    }
+
+    /// Sets light color.
+    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
+        self.color = Vec3::new(r, g, b);
+    }
+
+    /// Returns light intensity, which multiplies light color.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets light intensity, which multiplies light color.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    /// Returns constant, linear and quadratic attenuation factors.
+    pub fn attenuation(&self) -> (f32, f32, f32) {
+        (self.constant_attenuation, self.linear_attenuation, self.quadratic_attenuation)
+    }
+
+    /// Sets constant, linear and quadratic attenuation factors. Light reaching a point in distance d
+    /// is divided by constant + linear * d + quadratic * d^2. Directional lights are not attenuated.
+    pub fn set_attenuation(&mut self, constant: f32, linear: f32, quadratic: f32) {
+        self.constant_attenuation = constant;
+        self.linear_attenuation = linear;
+        self.quadratic_attenuation = quadratic;
+    }
+
+    /// Returns inner and outer angle of spot light cone in degrees.
+    pub fn cutoff_angles(&self) -> (f32, f32) {
+        (self.inner_cutoff_angle, self.outer_cutoff_angle)
+    }
+
+    /// Sets inner and outer angle of spot light cone in degrees. Light fades out between inner and outer angle.
+    pub fn set_cutoff_angles(&mut self, inner: f32, outer: f32) {
+        self.inner_cutoff_angle = inner.min(outer);
+        self.outer_cutoff_angle = outer;
+    }
+
+    /// Returns view projection matrix used to render shadow map of this light.
+    /// Directional lights cover area around the origin, spot lights cover their cone
+    /// and point lights look at the origin (or in light direction if they are placed at the origin).
+    pub fn light_space_matrix(&self) -> Mat4 {
+        let direction = match self.kind {
+            LightKind::Point if self.position != Vec3::new(0.0, 0.0, 0.0) => -self.position,
+            _ => self.direction,
+        };
+        let direction = if direction == Vec3::new(0.0, 0.0, 0.0) {
+            Vec3::new(0.0, -1.0, 0.0)
+        } else {
+            glm::normalize(&direction)
+        };
+
+        let position = match self.kind {
+            LightKind::Directional => -direction * DIRECTIONAL_LIGHT_SHADOW_DISTANCE,
+            _ => self.position,
+        };
+        let up = if glm::cross::<f32, U3>(&direction, &Vec3::new(0.0, 1.0, 0.0)).norm() < 0.001 {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        let view_matrix = glm::look_at(&position, &(position + direction), &up);
+
+        let projection_matrix = match self.kind {
+            LightKind::Directional => glm::ortho(-DIRECTIONAL_LIGHT_SHADOW_EXTENT, DIRECTIONAL_LIGHT_SHADOW_EXTENT,
+                                                 -DIRECTIONAL_LIGHT_SHADOW_EXTENT, DIRECTIONAL_LIGHT_SHADOW_EXTENT,
+                                                 SHADOW_NEAR_PLANE, SHADOW_FAR_PLANE),
+            LightKind::Spot => glm::perspective(1.0, (self.outer_cutoff_angle * 2.0).to_radians(), SHADOW_NEAR_PLANE, SHADOW_FAR_PLANE),
+            LightKind::Point => glm::perspective(1.0, POINT_LIGHT_SHADOW_FOV.to_radians(), SHADOW_NEAR_PLANE, SHADOW_FAR_PLANE),
+        };
+
+        correction_matrix() * projection_matrix * view_matrix
+    }
+
+    /// Returns light as uniform data used by shaders.
+    pub fn as_uniform_data(&self) -> LightUniformData {
+        let direction = if self.direction == Vec3::new(0.0, 0.0, 0.0) {
+            self.direction
+        } else {
+            glm::normalize(&self.direction)
+        };
+
+        LightUniformData {
+            position: self.position.into(),
+            kind: self.kind.shader_value(),
+            direction: direction.into(),
+            intensity: self.intensity,
+            color: self.color.into(),
+            constant_attenuation: self.constant_attenuation,
+            linear_attenuation: self.linear_attenuation,
+            quadratic_attenuation: self.quadratic_attenuation,
+            inner_cutoff: self.inner_cutoff_angle.to_radians().cos(),
+            outer_cutoff: self.outer_cutoff_angle.to_radians().cos(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directional_light_direction_is_normalized_in_uniform_data() {
+        let light = Light::directional(0.0, -2.0, 0.0);
+        let data = light.as_uniform_data();
+        assert_eq!([0.0, -1.0, 0.0], data.direction);
+        assert_eq!(1, data.kind);
+    }
+
+    #[test]
+    fn spot_light_cutoff_angles_are_stored_as_cosines() {
+        let mut light = Light::spot((0.0, 1.0, 0.0), (0.0, -1.0, 0.0));
+        light.set_cutoff_angles(0.0, 60.0);
+        let data = light.as_uniform_data();
+        assert!((data.inner_cutoff - 1.0).abs() < 0.0001);
+        assert!((data.outer_cutoff - 0.5).abs() < 0.0001);
+        assert_eq!(2, data.kind);
+    }
+
+    fn project(matrix: &Mat4, x: f32, y: f32, z: f32) -> Vec3 {
+        let projected = matrix * glm::vec4(x, y, z, 1.0);
+        projected.xyz() / projected.w
+    }
+
+    #[test]
+    fn origin_is_in_the_middle_of_shadow_map_of_every_light_kind() {
+        let lights = vec![
+            Light::point(2.0, 3.0, 1.0),
+            Light::directional(-1.0, -1.0, 0.0),
+            Light::spot((0.0, 5.0, 0.0), (0.0, -1.0, 0.0)),
+        ];
+
+        for light in lights {
+            let origin = project(&light.light_space_matrix(), 0.0, 0.0, 0.0);
+            assert!(origin.x.abs() < 0.0001 && origin.y.abs() < 0.0001, "{:?} projects origin to {:?}", light.kind(), origin);
+            assert!(origin.z > 0.0 && origin.z < 1.0, "{:?} projects origin to depth {}", light.kind(), origin.z);
+        }
+    }
+
+    #[test]
+    fn points_closer_to_light_have_smaller_depth() {
+        let light = Light::spot((0.0, 5.0, 0.0), (0.0, -1.0, 0.0));
+        let matrix = light.light_space_matrix();
+        assert!(project(&matrix, 0.0, 1.0, 0.0).z < project(&matrix, 0.0, 0.0, 0.0).z);
+    }
+
+    #[test]
+    fn inner_cutoff_angle_is_not_greater_than_outer_cutoff_angle() {
+        let mut light = Light::spot((0.0, 0.0, 0.0), (0.0, 0.0, -1.0));
+        light.set_cutoff_angles(30.0, 20.0);
+        assert_eq!((20.0, 20.0), light.cutoff_angles());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::resource::texture::Texture;
+use nalgebra_glm::Vec3;
+
+use std::sync::Arc;
+
+const DEFAULT_AMBIENT_STRENGTH: f32 = 0.1;
+const DEFAULT_SPECULAR_STRENGTH: f32 = 0.5;
+const DEFAULT_SHININESS: f32 = 32.0;
+
+/// Describes how surface of a mesh reacts to light.
+/// Maps are optional, texture of the object or its mesh is used when diffuse map is missing. Base color multiplies the diffuse texture.
+#[derive(Clone)]
+pub struct Material {
+    name: String,
+
+    base_color: Vec3,
+    emissive_color: Vec3,
+    ambient_strength: f32,
+    specular_strength: f32,
+    shininess: f32,
+
+    diffuse_map: Option<Arc<Texture>>,
+    specular_map: Option<Arc<Texture>>,
+    normal_map: Option<Arc<Texture>>,
+    emissive_map: Option<Arc<Texture>>,
+}
+
+impl Material {
+    /// Creates new white material without maps and emission.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Material {
+            name: name.into(),
+            base_color: Vec3::new(1.0, 1.0, 1.0),
+            emissive_color: Vec3::new(0.0, 0.0, 0.0),
+            ambient_strength: DEFAULT_AMBIENT_STRENGTH,
+            specular_strength: DEFAULT_SPECULAR_STRENGTH,
+            shininess: DEFAULT_SHININESS,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+            emissive_map: None,
+        }
+    }
+
+    /// Returns name of this material.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns base color, which multiplies color from diffuse map.
+    pub fn base_color(&self) -> (f32, f32, f32) {
+        (self.base_color.x, self.base_color.y, self.base_color.z)
+    }
+
+    /// Sets base color, which multiplies color from diffuse map.
+    pub fn set_base_color(&mut self, r: f32, g: f32, b: f32) {
+        self.base_color = Vec3::new(r, g, b);
+    }
+
+    /// Returns color emitted by the surface regardless of lights.
+    pub fn emissive_color(&self) -> (f32, f32, f32) {
+        (self.emissive_color.x, self.emissive_color.y, self.emissive_color.z)
+    }
+
+    /// Sets color emitted by the surface regardless of lights. It multiplies color from emissive map.
+    pub fn set_emissive_color(&mut self, r: f32, g: f32, b: f32) {
+        self.emissive_color = Vec3::new(r, g, b);
+    }
+
+    /// Returns part of the light color which reaches surfaces not facing the light.
+    pub fn ambient_strength(&self) -> f32 {
+        self.ambient_strength
+    }
+
+    /// Sets part of the light color which reaches surfaces not facing the light.
+    pub fn set_ambient_strength(&mut self, value: f32) {
+        self.ambient_strength = value.max(0.0);
+    }
+
+    /// Returns intensity of specular highlights.
+    pub fn specular_strength(&self) -> f32 {
+        self.specular_strength
+    }
+
+    /// Sets intensity of specular highlights. It multiplies value from specular map.
+    pub fn set_specular_strength(&mut self, value: f32) {
+        self.specular_strength = value.max(0.0);
+    }
+
+    /// Returns shininess exponent. Higher values make smaller specular highlights.
+    pub fn shininess(&self) -> f32 {
+        self.shininess
+    }
+
+    /// Sets shininess exponent. Higher values make smaller specular highlights.
+    pub fn set_shininess(&mut self, value: f32) {
+        self.shininess = value.max(1.0);
+    }
+
+    /// Returns texture with surface color.
+    pub fn diffuse_map(&self) -> Option<Arc<Texture>> {
+        self.diffuse_map.clone()
+    }
+
+    /// Sets texture with surface color. Texture of the object or its mesh is used when the material doesn't have one.
+    pub fn set_diffuse_map(&mut self, texture: Option<Arc<Texture>>) {
+        self.diffuse_map = texture;
+    }
+
+    /// Returns texture with specular intensity.
+    pub fn specular_map(&self) -> Option<Arc<Texture>> {
+        self.specular_map.clone()
+    }
+
+    /// Sets texture with specular intensity in the red channel.
+    /// It should be created as linear texture, see `AssetManager::create_linear_texture`.
+    pub fn set_specular_map(&mut self, texture: Option<Arc<Texture>>) {
+        self.specular_map = texture;
+    }
+
+    /// Returns tangent space normal map.
+    pub fn normal_map(&self) -> Option<Arc<Texture>> {
+        self.normal_map.clone()
+    }
+
+    /// Sets tangent space normal map. It should be created as linear texture, see `AssetManager::create_linear_texture`.
+    pub fn set_normal_map(&mut self, texture: Option<Arc<Texture>>) {
+        self.normal_map = texture;
+    }
+
+    /// Returns texture with emitted color.
+    pub fn emissive_map(&self) -> Option<Arc<Texture>> {
+        self.emissive_map.clone()
+    }
+
+    /// Sets texture with emitted color.
+    pub fn set_emissive_map(&mut self, texture: Option<Arc<Texture>>) {
+        self.emissive_map = texture;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_material_uses_previous_phong_constants() {
+        let material = Material::new("test");
+        assert_eq!(0.1, material.ambient_strength());
+        assert_eq!(0.5, material.specular_strength());
+        assert_eq!(32.0, material.shininess());
+        assert_eq!((1.0, 1.0, 1.0), material.base_color());
+        assert_eq!((0.0, 0.0, 0.0), material.emissive_color());
+    }
+
+    #[test]
+    fn shininess_is_at_least_one() {
+        let mut material = Material::new("test");
+        material.set_shininess(0.0);
+        assert_eq!(1.0, material.shininess());
+    }
+}
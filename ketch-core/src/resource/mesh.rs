@@ -1,4 +1,8 @@
 use crate::resource::texture::Texture;
+use crate::resource::material::Material;
+use crate::resource::bounds::{BoundingBox, BoundingSphere, Ray};
+use nalgebra_glm::{Mat4, Vec3};
+use std::sync::RwLock;
 use std::sync::Arc;
 
 use vulkano::impl_vertex;
@@ -16,6 +20,24 @@ pub struct Vertex {
 
 impl_vertex!(Vertex, position, normal, tex_coord);
 
+/// Data of one instance in instanced draw call. Model matrix is stored as four columns.
+#[derive(Clone,Copy)]
+pub struct InstanceData {
+    pub model_0: [f32; 4],
+    pub model_1: [f32; 4],
+    pub model_2: [f32; 4],
+    pub model_3: [f32; 4],
+}
+
+impl_vertex!(InstanceData, model_0, model_1, model_2, model_3);
+
+impl From<Mat4> for InstanceData {
+    fn from(model: Mat4) -> Self {
+        let [model_0, model_1, model_2, model_3]: [[f32; 4]; 4] = model.into();
+        InstanceData { model_0, model_1, model_2, model_3 }
+    }
+}
+
 /// Mesh is a collection of vertices, edges and faces that defines shape of object.
 pub struct Mesh {
     name: String,
@@ -27,6 +49,10 @@ pub struct Mesh {
     index_buffer: Arc<ImmutableBuffer<[u32]>>,
 
     texture: Arc<Texture>,
+    material: Option<Arc<RwLock<Material>>>,
+
+    bounding_box: BoundingBox,
+    bounding_sphere: BoundingSphere,
 }
 
 impl Mesh {
@@ -44,6 +70,9 @@ impl Mesh {
             upload_queue
         ).expect("failed to create index buffer");
 
+        let bounding_box = BoundingBox::from_points(vertices.iter().map(|vertex| vertex.position));
+        let bounding_sphere = bounding_box.bounding_sphere();
+
         Mesh {
             name: name.into(),
             
@@ -54,6 +83,10 @@ impl Mesh {
             index_buffer: index_buffer,
 
             texture,
+            material: None,
+
+            bounding_box,
+            bounding_sphere,
         }
     }
 
@@ -72,6 +105,46 @@ impl Mesh {
         self.texture.clone()
     }
 
+    /// Sets material used by this mesh. Objects can override it with their own material.
+    pub fn set_material(&mut self, material: Option<Arc<RwLock<Material>>>) {
+        self.material = material;
+    }
+
+    /// Returns material used by this mesh.
+    pub fn material(&self) -> Option<Arc<RwLock<Material>>> {
+        self.material.clone()
+    }
+
+    /// Returns box containing all vertices of this mesh in its local space.
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+
+    /// Returns sphere containing all vertices of this mesh in its local space.
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_sphere
+    }
+
+    /// Returns vertices of this mesh kept in memory for CPU side queries like picking.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// Returns indices of this mesh, every three indices make one triangle.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Returns distance along the ray to the nearest triangle of this mesh. Ray has to be in local space of the mesh.
+    pub fn ray_intersection(&self, ray: &Ray) -> Option<f32> {
+        let position = |index: u32| Vec3::from(self.vertices[index as usize].position);
+
+        self.indices.chunks(3)
+                    .filter(|triangle| triangle.len() == 3)
+                    .filter_map(|triangle| ray.intersects_triangle(&position(triangle[0]), &position(triangle[1]), &position(triangle[2])))
+                    .fold(None, |nearest: Option<f32>, distance| Some(nearest.map_or(distance, |nearest| nearest.min(distance))))
+    }
+
     /// Returns the vertex buffer of this mesh.
     pub fn vertex_buffer(&self) -> Arc<ImmutableBuffer<[Vertex]>> {
         self.vertex_buffer.clone()
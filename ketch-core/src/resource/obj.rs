@@ -0,0 +1,206 @@
+use crate::resource::mesh::Vertex;
+use crate::resource::resource_error::ObjLoadError;
+use nalgebra_glm::{U3, Vec3};
+use nalgebra_glm as glm;
+
+use std::path::{Path, PathBuf};
+
+/// Geometry of a single group from OBJ file together with its diffuse texture.
+pub struct ObjGroup {
+    pub name: String,
+    pub material_name: Option<String>,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub diffuse_texture: Option<PathBuf>,
+}
+
+/// Loads all groups from OBJ file. Polygons are triangulated and vertices with the same
+/// position, normal and texture coordinate are merged into one vertex.
+/// Diffuse texture paths from MTL file are resolved relative to the OBJ file.
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Vec<ObjGroup>, ObjLoadError> {
+    let path = path.as_ref();
+    let (models, materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)?;
+    let materials = materials.map_err(ObjLoadError::MaterialLoadError)?;
+    let base_directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    models.into_iter().map(|model| {
+        let material = match model.mesh.material_id {
+            Some(material_id) => Some(materials.get(material_id).ok_or(ObjLoadError::InvalidMaterialError(material_id))?),
+            None => None,
+        };
+
+        Ok(ObjGroup {
+            name: model.name,
+            material_name: material.map(|material| material.name.clone()),
+            vertices: to_vertices(&model.mesh),
+            indices: model.mesh.indices,
+            diffuse_texture: material.filter(|material| !material.diffuse_texture.is_empty())
+                                     .map(|material| base_directory.join(&material.diffuse_texture)),
+        })
+    }).collect()
+}
+
+/// Converts single index mesh to vertices. Missing normals are computed from faces,
+/// missing texture coordinates are set to zero.
+fn to_vertices(mesh: &tobj::Mesh) -> Vec<Vertex> {
+    let vertex_count = mesh.positions.len() / 3;
+    let normals = if mesh.normals.len() == mesh.positions.len() {
+        mesh.normals.chunks(3).map(|n| [n[0], n[1], n[2]]).collect()
+    } else {
+        compute_normals(&mesh.positions, &mesh.indices)
+    };
+    let has_tex_coords = mesh.texcoords.len() / 2 == vertex_count;
+
+    (0 .. vertex_count).map(|i| Vertex {
+        position: [mesh.positions[i * 3], mesh.positions[i * 3 + 1], mesh.positions[i * 3 + 2]],
+        normal: normals[i],
+        tex_coord: if has_tex_coords {
+            [mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1]]
+        } else {
+            [0.0, 0.0]
+        },
+    }).collect()
+}
+
+/// Computes smooth vertex normals by averaging normals of faces which share the vertex.
+pub fn compute_normals(positions: &[f32], indices: &[u32]) -> Vec<[f32; 3]> {
+    let position = |index: u32| {
+        let index = index as usize * 3;
+        Vec3::new(positions[index], positions[index + 1], positions[index + 2])
+    };
+
+    let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); positions.len() / 3];
+    for triangle in indices.chunks(3).filter(|triangle| triangle.len() == 3) {
+        let (a, b, c) = (position(triangle[0]), position(triangle[1]), position(triangle[2]));
+        let face_normal = glm::cross::<f32, U3>(&(b - a), &(c - a));
+        for index in triangle {
+            normals[*index as usize] += face_normal;
+        }
+    }
+
+    normals.into_iter().map(|normal| {
+        if normal == Vec3::new(0.0, 0.0, 0.0) {
+            [0.0, 0.0, 0.0]
+        } else {
+            glm::normalize(&normal).into()
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_test_file(file_name: &str, content: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join("ketch_obj_tests");
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join(file_name);
+        let content: Vec<&str> = content.lines().map(|line| line.trim()).collect();
+        fs::write(&path, content.join("\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn quad_is_triangulated_and_vertices_are_deduplicated() {
+        let path = write_test_file("quad.obj", "
+            v 0.0 0.0 0.0
+            v 1.0 0.0 0.0
+            v 1.0 1.0 0.0
+            v 0.0 1.0 0.0
+            vt 0.0 0.0
+            vt 1.0 0.0
+            vt 1.0 1.0
+            vt 0.0 1.0
+            vn 0.0 0.0 1.0
+            f 1/1/1 2/2/1 3/3/1 4/4/1
+        ");
+
+        let groups = load_obj(&path).unwrap();
+        assert_eq!(1, groups.len());
+        assert_eq!(4, groups[0].vertices.len());
+        assert_eq!(6, groups[0].indices.len());
+        assert_eq!([1.0, 1.0], groups[0].vertices[2].tex_coord);
+        assert_eq!([0.0, 0.0, 1.0], groups[0].vertices[0].normal);
+    }
+
+    #[test]
+    fn every_group_is_loaded_separately() {
+        let path = write_test_file("groups.obj", "
+            v 0.0 0.0 0.0
+            v 1.0 0.0 0.0
+            v 1.0 1.0 0.0
+            v 0.0 0.0 1.0
+            o first
+            f 1 2 3
+            o second
+            f 1 2 4
+        ");
+
+        let groups = load_obj(&path).unwrap();
+        assert_eq!(2, groups.len());
+        assert_eq!("first", groups[0].name);
+        assert_eq!("second", groups[1].name);
+    }
+
+    #[test]
+    fn missing_normals_are_computed_from_faces() {
+        let path = write_test_file("no_normals.obj", "
+            v 0.0 0.0 0.0
+            v 1.0 0.0 0.0
+            v 0.0 1.0 0.0
+            f 1 2 3
+        ");
+
+        let groups = load_obj(&path).unwrap();
+        for vertex in groups[0].vertices.iter() {
+            assert_eq!([0.0, 0.0, 1.0], vertex.normal);
+            assert_eq!([0.0, 0.0], vertex.tex_coord);
+        }
+    }
+
+    #[test]
+    fn diffuse_texture_path_is_resolved_relative_to_obj_file() {
+        write_test_file("textured.mtl", "
+            newmtl wood
+            map_Kd wood.png
+        ");
+        let path = write_test_file("textured.obj", "
+            mtllib textured.mtl
+            v 0.0 0.0 0.0
+            v 1.0 0.0 0.0
+            v 0.0 1.0 0.0
+            usemtl wood
+            f 1 2 3
+        ");
+
+        let groups = load_obj(&path).unwrap();
+        assert_eq!(Some("wood".to_string()), groups[0].material_name);
+        assert_eq!(Some(path.parent().unwrap().join("wood.png")), groups[0].diffuse_texture);
+    }
+
+    #[test]
+    fn missing_file_returns_error() {
+        let result = load_obj(std::env::temp_dir().join("ketch_obj_tests").join("missing.obj"));
+        match result {
+            Err(ObjLoadError::ObjParseError(tobj::LoadError::OpenFileFailed)) => (),
+            _ => panic!("expected OpenFileFailed error"),
+        }
+    }
+
+    #[test]
+    fn missing_mtl_file_returns_error() {
+        let path = write_test_file("missing_mtl.obj", "
+            mtllib does_not_exist.mtl
+            v 0.0 0.0 0.0
+            v 1.0 0.0 0.0
+            v 0.0 1.0 0.0
+            f 1 2 3
+        ");
+
+        match load_obj(&path) {
+            Err(ObjLoadError::MaterialLoadError(_)) => (),
+            _ => panic!("expected MaterialLoadError"),
+        }
+    }
+}
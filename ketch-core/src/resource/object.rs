@@ -1,7 +1,11 @@
 use std::sync::RwLock;
 use std::sync::Arc;
 use crate::resource::mesh::Mesh;
-use nalgebra_glm::{Mat4, Vec3};
+use crate::resource::material::Material;
+use crate::resource::texture::Texture;
+use crate::resource::bounds::{BoundingBox, BoundingSphere, Ray};
+use nalgebra_glm::{Mat3, Mat4, Qua, Vec3, U3};
+use nalgebra_glm as glm;
 
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 
@@ -17,9 +21,8 @@ pub struct Object {
     position_y: f32,
     position_z: f32,
 
-    rotation_angle_x: f32,
-    rotation_angle_y: f32,
-    rotation_angle_z: f32,
+    rotation: Qua<f32>,
+    rotation_angles: Vec3,
 
     scale_x: f32,
     scale_y: f32,
@@ -33,8 +36,14 @@ pub struct Object {
 
     light_source: bool,
     uniform_scale: bool,
+    cast_shadows: bool,
+    receive_shadows: bool,
 
     mesh: Option<Arc<RwLock<Mesh>>>,
+    texture: Option<Arc<Texture>>,
+    material: Option<Arc<RwLock<Material>>>,
+
+    parent: Option<u32>,
 }
 
 
@@ -86,34 +95,73 @@ impl Object {
 
     /// Sets object x rotation angle.
     pub fn set_rotation_angle_x(&mut self, rotation_angle_x: f32) {
-        self.rotation_angle_x = rotation_angle_x;
-        self.update_rotation_matrix();
-        self.update_model_matrix();
+        self.rotation_angles.x = rotation_angle_x;
+        self.update_rotation_from_angles();
     }
 
     /// Sets object y rotation angle.
     pub fn set_rotation_angle_y(&mut self, rotation_angle_y: f32) {
-        self.rotation_angle_y = rotation_angle_y;
-        self.update_rotation_matrix();
-        self.update_model_matrix();
+        self.rotation_angles.y = rotation_angle_y;
+        self.update_rotation_from_angles();
     }
 
     /// Sets object z rotation angle.
     pub fn set_rotation_angle_z(&mut self, rotation_angle_z: f32) {
-        self.rotation_angle_z = rotation_angle_z;
-        self.update_rotation_matrix();
-        self.update_model_matrix();
+        self.rotation_angles.z = rotation_angle_z;
+        self.update_rotation_from_angles();
     }
 
-    /// Sets object x, y and z rotation angle.
+    /// Sets object x, y and z rotation angle. Rotation around x axis is applied first, then y and z.
     pub fn set_rotation_angles(&mut self, rotation_angle_x: f32, rotation_angle_y: f32, rotation_angle_z: f32) {
-        self.rotation_angle_x = rotation_angle_x;
-        self.rotation_angle_y = rotation_angle_y;
-        self.rotation_angle_z = rotation_angle_z;
+        self.rotation_angles = Vec3::new(rotation_angle_x, rotation_angle_y, rotation_angle_z);
+        self.update_rotation_from_angles();
+    }
+
+    /// Sets object rotation. Quaternion is normalized.
+    pub fn set_rotation(&mut self, rotation: Qua<f32>) {
+        self.rotation = glm::quat_normalize(&rotation);
+        let (x, y, z) = quat_to_rotation_angles(&self.rotation);
+        self.rotation_angles = Vec3::new(x, y, z);
         self.update_rotation_matrix();
         self.update_model_matrix();
     }
 
+    /// Sets object rotation to rotation by angle in radians around the given axis.
+    pub fn set_rotation_axis_angle(&mut self, axis: (f32, f32, f32), angle: f32) {
+        let axis = Vec3::new(axis.0, axis.1, axis.2);
+        if axis.norm() > 0.0 {
+            self.set_rotation(glm::quat_angle_axis(angle, &glm::normalize(&axis)));
+        } else {
+            self.set_rotation(glm::quat_identity());
+        }
+    }
+
+    /// Rotates object so its negative z axis points at target. Up decides rotation around that axis,
+    /// if it's parallel to the direction to target, another axis is used instead.
+    /// Rotation doesn't change when target is at object position.
+    pub fn look_at(&mut self, target: (f32, f32, f32), up: (f32, f32, f32)) {
+        let direction = Vec3::new(target.0 - self.position_x, target.1 - self.position_y, target.2 - self.position_z);
+        if direction.norm() < 0.0001 {
+            return;
+        }
+
+        let back = -glm::normalize(&direction);
+        let mut up = Vec3::new(up.0, up.1, up.2);
+        if glm::cross::<f32, U3>(&up, &back).norm() < 0.0001 {
+            up = if back.y.abs() < 0.9 { Vec3::y() } else { Vec3::z() };
+        }
+        let right = glm::normalize(&glm::cross::<f32, U3>(&up, &back));
+        let up = glm::cross::<f32, U3>(&back, &right);
+
+        self.set_rotation(glm::mat3_to_quat(&Mat3::from_columns(&[right, up, back])));
+    }
+
+    /// Rotates object from its current rotation towards target rotation. t = 0 keeps current rotation, t = 1 sets target.
+    pub fn slerp_rotation(&mut self, target: &Qua<f32>, t: f32) {
+        let rotation = slerp(&self.rotation, target, t);
+        self.set_rotation(rotation);
+    }
+
     /// Sets object x scale.
     pub fn set_scale_x(&mut self, scale_x: f32) {
         self.scale_x = scale_x;
@@ -179,22 +227,28 @@ impl Object {
 
     /// Returns object x rotation angle.
     pub fn rotation_angle_x(&self) -> f32 {
-        self.rotation_angle_x
+        self.rotation_angles.x
     }
 
     /// Returns object y rotation angle.
     pub fn rotation_angle_y(&self) -> f32 {
-        self.rotation_angle_y
+        self.rotation_angles.y
     }
 
     /// Returns object z rotation angle.
     pub fn rotation_angle_z(&self) -> f32 {
-        self.rotation_angle_z
+        self.rotation_angles.z
     }
 
-    /// Returns object x, y and z rotation angle.
+    /// Returns object x, y and z rotation angle. Angles set with `set_rotation_angles` are returned unchanged,
+    /// otherwise they are computed from the rotation quaternion.
     pub fn rotation_angles(&self) -> (f32, f32, f32) {
-        (self.rotation_angle_x, self.rotation_angle_y, self.rotation_angle_z)
+        (self.rotation_angles.x, self.rotation_angles.y, self.rotation_angles.z)
+    }
+
+    /// Returns object rotation as unit quaternion.
+    pub fn rotation(&self) -> Qua<f32> {
+        self.rotation
     }
 
     /// Returns object x scale.
@@ -231,6 +285,26 @@ impl Object {
         self.light_source = value;
     }
 
+    /// Returns true if object is drawn into shadow map of the main light.
+    pub fn cast_shadows(&self) -> bool {
+        self.cast_shadows
+    }
+
+    /// Sets whether object is drawn into shadow map of the main light. Light source objects never cast shadows.
+    pub fn set_cast_shadows(&mut self, value: bool) {
+        self.cast_shadows = value;
+    }
+
+    /// Returns true if shadows are drawn on the object.
+    pub fn receive_shadows(&self) -> bool {
+        self.receive_shadows
+    }
+
+    /// Sets whether shadows are drawn on the object.
+    pub fn set_receive_shadows(&mut self, value: bool) {
+        self.receive_shadows = value;
+    }
+
     /// Returns model matrix.
     pub fn model_matrix(&self) -> Mat4 {
         self.model_matrix
@@ -249,40 +323,66 @@ impl Object {
         self.mesh = Some(mesh);
     }
 
-    fn update_translation_matrix(&mut self) {
-        self.translation_matrix = position_to_translation_matrix(self.position_x, self.position_y, self.position_z);
+    /// Returns texture set on this object, which replaces texture of its mesh.
+    pub fn texture(&self) -> Option<Arc<Texture>> {
+        self.texture.clone()
     }
 
-    fn update_rotation_matrix(&mut self) {
-        self.rotation_matrix = rotation_of_axes_to_rotation_matrix(self.rotation_angle_x, self.rotation_angle_y, self.rotation_angle_z);
+    /// Sets texture replacing texture of object mesh, other objects using the same mesh are not affected.
+    /// With None mesh texture is used.
+    pub fn set_texture(&mut self, texture: Option<Arc<Texture>>) {
+        self.texture = texture;
     }
 
-    fn update_scaling_matrix(&mut self) {
-        self.scaling_matrix = scale_of_axes_to_scale_matrix(self.scale_x, self.scale_y, self.scale_z);
+    /// Returns material set on this object, which overrides material of its mesh.
+    pub fn material(&self) -> Option<Arc<RwLock<Material>>> {
+        self.material.clone()
     }
 
-    fn update_model_matrix(&mut self) {
-        self.model_matrix = create_model_matrix(&self.translation_matrix, &self.rotation_matrix, &self.scaling_matrix);
+    /// Sets material overriding material of object mesh. With None mesh material is used.
+    pub fn set_material(&mut self, material: Option<Arc<RwLock<Material>>>) {
+        self.material = material;
     }
 
-    fn update_uniform_scale(&mut self) {
-        self.uniform_scale = self.scale_x == self.scale_y && self.scale_y == self.scale_z;
+    /// Returns box containing object mesh transformed by world matrix of the object, see `Scene::world_matrix`.
+    /// Objects without mesh don't have bounds.
+    pub fn world_bounding_box(&self, world_matrix: &Mat4) -> Option<BoundingBox> {
+        self.mesh.as_ref().map(|mesh| mesh.read().unwrap().bounding_box().transform(world_matrix))
     }
-}
 
-impl<'a> Clone for Object {
-    fn clone(&self) -> Object {
+    /// Returns sphere containing object mesh transformed by world matrix of the object, see `Scene::world_matrix`.
+    /// Objects without mesh don't have bounds.
+    pub fn world_bounding_sphere(&self, world_matrix: &Mat4) -> Option<BoundingSphere> {
+        self.mesh.as_ref().map(|mesh| mesh.read().unwrap().bounding_sphere().transform(world_matrix))
+    }
+
+    /// Returns distance along the ray to the nearest triangle of object mesh transformed by world matrix of the object.
+    /// Bounding box of the mesh is tested first, so most missed objects don't need to test their triangles.
+    pub fn ray_intersection(&self, ray: &Ray, world_matrix: &Mat4) -> Option<f32> {
+        let mesh = self.mesh.as_ref()?.read().unwrap();
+        ray.intersects_box(&mesh.bounding_box().transform(world_matrix))?;
+
+        let local_ray = ray.transform(&world_matrix.try_inverse()?);
+        mesh.ray_intersection(&local_ray)
+    }
+
+    /// Returns id of the parent object. Model matrix of the object is relative to its parent.
+    pub fn parent(&self) -> Option<u32> {
+        self.parent
+    }
+
+    /// Returns copy of the object with the same id. Unlike clones, copies can't be in the same scene as the original.
+    pub(crate) fn snapshot(&self) -> Object {
         Object {
-            id: generate_id(),
+            id: self.id,
             name: self.name.clone(),
 
             position_x: self.position_x,
             position_y: self.position_y,
             position_z: self.position_z,
 
-            rotation_angle_x: self.rotation_angle_x,
-            rotation_angle_y: self.rotation_angle_y,
-            rotation_angle_z: self.rotation_angle_z,
+            rotation: self.rotation,
+            rotation_angles: self.rotation_angles,
 
             scale_x: self.scale_x,
             scale_y: self.scale_y,
@@ -296,10 +396,66 @@ impl<'a> Clone for Object {
 
             light_source: self.light_source,
             uniform_scale: self.uniform_scale,
+            cast_shadows: self.cast_shadows,
+            receive_shadows: self.receive_shadows,
 
-            mesh: self.mesh.clone(),    
+            mesh: self.mesh.clone(),
+            texture: self.texture.clone(),
+            material: self.material.clone(),
+
+            parent: self.parent,
         }
     }
+
+    /// Changes parent without changing local transform. Scene is responsible for keeping parents valid.
+    pub(crate) fn set_parent_id(&mut self, parent: Option<u32>) {
+        self.parent = parent;
+    }
+
+    /// Sets position, rotation angles and scale from transformation matrix.
+    /// Shear, which can't be represented by object transform, is lost.
+    pub fn set_transform_from_matrix(&mut self, matrix: &Mat4) {
+        let (position, rotation_angles, scale) = decompose_matrix(matrix);
+        self.set_position(position.x, position.y, position.z);
+        self.set_rotation_angles(rotation_angles.x, rotation_angles.y, rotation_angles.z);
+        self.set_scale_xyz(scale.x, scale.y, scale.z);
+        self.update_uniform_scale();
+    }
+
+    fn update_translation_matrix(&mut self) {
+        self.translation_matrix = position_to_translation_matrix(self.position_x, self.position_y, self.position_z);
+    }
+
+    fn update_rotation_from_angles(&mut self) {
+        self.rotation = rotation_angles_to_quat(self.rotation_angles.x, self.rotation_angles.y, self.rotation_angles.z);
+        self.update_rotation_matrix();
+        self.update_model_matrix();
+    }
+
+    fn update_rotation_matrix(&mut self) {
+        self.rotation_matrix = glm::quat_to_mat4(&self.rotation);
+    }
+
+    fn update_scaling_matrix(&mut self) {
+        self.scaling_matrix = scale_of_axes_to_scale_matrix(self.scale_x, self.scale_y, self.scale_z);
+    }
+
+    fn update_model_matrix(&mut self) {
+        self.model_matrix = create_model_matrix(&self.translation_matrix, &self.rotation_matrix, &self.scaling_matrix);
+    }
+
+    fn update_uniform_scale(&mut self) {
+        self.uniform_scale = self.scale_x == self.scale_y && self.scale_y == self.scale_z;
+    }
+}
+
+impl<'a> Clone for Object {
+    /// Clones get new ids, so they can be added to the same scene as the original.
+    fn clone(&self) -> Object {
+        let mut object = self.snapshot();
+        object.id = generate_id();
+        object
+    }
 }
 
 /// Builder used for constructing new objects.
@@ -310,9 +466,8 @@ pub struct ObjectBuilder {
     position_y: f32,
     position_z: f32,
 
-    rotation_angle_x: f32,
-    rotation_angle_y: f32,
-    rotation_angle_z: f32,
+    rotation: Qua<f32>,
+    rotation_angles: Vec3,
 
     scale_x: f32,
     scale_y: f32,
@@ -323,6 +478,7 @@ pub struct ObjectBuilder {
     scaling_matrix: Mat4,
 
     mesh: Option<Arc<RwLock<Mesh>>>,
+    material: Option<Arc<RwLock<Material>>>,
 }
 
 impl ObjectBuilder {
@@ -334,9 +490,8 @@ impl ObjectBuilder {
             position_y: 0.0,
             position_z: 0.0,
 
-            rotation_angle_x: 0.0,
-            rotation_angle_y: 0.0,
-            rotation_angle_z: 0.0,
+            rotation: glm::quat_identity(),
+            rotation_angles: Vec3::zeros(),
 
             scale_x: 1.0,
             scale_y: 1.0,
@@ -347,6 +502,7 @@ impl ObjectBuilder {
             scaling_matrix: Mat4::identity(),
 
             mesh: None,        
+            material: None,
         }
     }
 
@@ -358,9 +514,8 @@ impl ObjectBuilder {
             position_y: self.position_y,
             position_z: self.position_z,
 
-            rotation_angle_x: self.rotation_angle_x,
-            rotation_angle_y: self.rotation_angle_y,
-            rotation_angle_z: self.rotation_angle_z,
+            rotation: self.rotation,
+            rotation_angles: self.rotation_angles,
 
             scale_x: self.scale_x,
             scale_y: self.scale_y,
@@ -371,6 +526,7 @@ impl ObjectBuilder {
             scaling_matrix: self.scaling_matrix,
 
             mesh: Some(mesh),  
+            material: self.material,
         }
     }
 
@@ -382,9 +538,8 @@ impl ObjectBuilder {
             position_y: y,
             position_z: z,
 
-            rotation_angle_x: self.rotation_angle_x,
-            rotation_angle_y: self.rotation_angle_y,
-            rotation_angle_z: self.rotation_angle_z,
+            rotation: self.rotation,
+            rotation_angles: self.rotation_angles,
 
             scale_x: self.scale_x,
             scale_y: self.scale_y,
@@ -395,10 +550,12 @@ impl ObjectBuilder {
             scaling_matrix: self.scaling_matrix,
 
             mesh: self.mesh,        
+            material: self.material,
         }
     }
 
     pub fn with_rotation_angle(self, x: f32, y: f32, z: f32) -> Self {
+        let rotation = rotation_angles_to_quat(x, y, z);
         Self {
             name: self.name,
 
@@ -406,19 +563,45 @@ impl ObjectBuilder {
             position_y: self.position_y,
             position_z: self.position_z,
 
-            rotation_angle_x: x,
-            rotation_angle_y: y,
-            rotation_angle_z: z,
+            rotation,
+            rotation_angles: Vec3::new(x, y, z),
 
             scale_x: self.scale_x,
             scale_y: self.scale_y,
             scale_z: self.scale_z,
 
             translation_matrix: self.translation_matrix,
-            rotation_matrix: rotation_of_axes_to_rotation_matrix(x, y, z),
+            rotation_matrix: glm::quat_to_mat4(&rotation),
             scaling_matrix: self.scaling_matrix,
 
             mesh: self.mesh,         
+            material: self.material,
+        }
+    }
+
+    pub fn with_rotation(self, rotation: Qua<f32>) -> Self {
+        let rotation = glm::quat_normalize(&rotation);
+        let (x, y, z) = quat_to_rotation_angles(&rotation);
+        Self {
+            name: self.name,
+
+            position_x: self.position_x,
+            position_y: self.position_y,
+            position_z: self.position_z,
+
+            rotation,
+            rotation_angles: Vec3::new(x, y, z),
+
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            scale_z: self.scale_z,
+
+            translation_matrix: self.translation_matrix,
+            rotation_matrix: glm::quat_to_mat4(&rotation),
+            scaling_matrix: self.scaling_matrix,
+
+            mesh: self.mesh,
+            material: self.material,
         }
     }
 
@@ -430,9 +613,8 @@ impl ObjectBuilder {
             position_y: self.position_y,
             position_z: self.position_z,
 
-            rotation_angle_x: self.rotation_angle_x,
-            rotation_angle_y: self.rotation_angle_y,
-            rotation_angle_z: self.rotation_angle_z,
+            rotation: self.rotation,
+            rotation_angles: self.rotation_angles,
 
             scale_x: x,
             scale_y: y,
@@ -443,6 +625,31 @@ impl ObjectBuilder {
             scaling_matrix: scale_of_axes_to_scale_matrix(x, y, z),
 
             mesh: self.mesh,        
+            material: self.material,
+        }
+    }
+
+    pub fn with_material(self, material: Arc<RwLock<Material>>) -> Self {
+        Self {
+            name: self.name,
+
+            position_x: self.position_x,
+            position_y: self.position_y,
+            position_z: self.position_z,
+
+            rotation: self.rotation,
+            rotation_angles: self.rotation_angles,
+
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            scale_z: self.scale_z,
+
+            translation_matrix: self.translation_matrix,
+            rotation_matrix: self.rotation_matrix,
+            scaling_matrix: self.scaling_matrix,
+
+            mesh: self.mesh,
+            material: Some(material),
         }
     }
 
@@ -456,9 +663,8 @@ impl ObjectBuilder {
             position_y: self.position_y,
             position_z: self.position_z,
 
-            rotation_angle_x: self.rotation_angle_x,
-            rotation_angle_y: self.rotation_angle_y,
-            rotation_angle_z: self.rotation_angle_z,
+            rotation: self.rotation,
+            rotation_angles: self.rotation_angles,
 
             scale_x: self.scale_x,
             scale_y: self.scale_y,
@@ -472,8 +678,14 @@ impl ObjectBuilder {
 
             light_source: false,
             uniform_scale, 
+            cast_shadows: true,
+            receive_shadows: true,
 
             mesh: self.mesh.clone(),  
+            texture: None,
+            material: self.material.clone(),
+
+            parent: None,
         }
     }
 }
@@ -482,12 +694,52 @@ fn position_to_translation_matrix(position_x: f32, position_y: f32, position_z:
     nalgebra_glm::translate(&nalgebra_glm::identity(), &Vec3::new(position_x, position_y, position_z))
 }
 
-fn rotation_of_axes_to_rotation_matrix(rotation_angle_x: f32, rotation_angle_y: f32, rotation_angle_z: f32) -> Mat4 {
-    let rotation_matrix_z = nalgebra_glm::rotate_z(&nalgebra_glm::identity(), rotation_angle_z);
-    let rotation_matrix_y = nalgebra_glm::rotate_y(&nalgebra_glm::identity(), rotation_angle_y);
-    let rotation_matrix_x = nalgebra_glm::rotate_x(&nalgebra_glm::identity(), rotation_angle_x);
+/// Converts angles around x, y and z axes in radians to quaternion.
+/// Rotation around x axis is applied first, then y and z.
+pub fn rotation_angles_to_quat(x: f32, y: f32, z: f32) -> Qua<f32> {
+    let rotation_x = glm::quat_angle_axis(x, &Vec3::x());
+    let rotation_y = glm::quat_angle_axis(y, &Vec3::y());
+    let rotation_z = glm::quat_angle_axis(z, &Vec3::z());
+
+    glm::quat_normalize(&(rotation_z * rotation_y * rotation_x))
+}
 
-    rotation_matrix_z * rotation_matrix_y * rotation_matrix_x
+/// Converts quaternion to angles around x, y and z axes in radians, which give the same rotation
+/// with `rotation_angles_to_quat`. Angle around y axis is in range from -pi/2 to pi/2.
+pub fn quat_to_rotation_angles(rotation: &Qua<f32>) -> (f32, f32, f32) {
+    rotation_matrix_to_angles(&glm::quat_to_mat3(rotation))
+}
+
+/// Spherical linear interpolation between two rotations along the shorter path.
+/// t = 0 gives from, t = 1 gives to.
+pub fn slerp(from: &Qua<f32>, to: &Qua<f32>, t: f32) -> Qua<f32> {
+    let from = glm::quat_normalize(from);
+    let mut to = glm::quat_normalize(to);
+    let mut cos_angle = glm::quat_dot(&from, &to);
+    if cos_angle < 0.0 {
+        to = -to;
+        cos_angle = -cos_angle;
+    }
+
+    if cos_angle > 0.9995 {
+        // rotations are almost the same, linear interpolation avoids division by zero
+        return glm::quat_normalize(&(from * (1.0 - t) + to * t));
+    }
+
+    let angle = cos_angle.acos();
+    let sin_angle = angle.sin();
+    glm::quat_normalize(&(from * (((1.0 - t) * angle).sin() / sin_angle) + to * ((t * angle).sin() / sin_angle)))
+}
+
+/// Converts rotation matrix Rz * Ry * Rx to angles around x, y and z axes.
+fn rotation_matrix_to_angles(m: &Mat3) -> (f32, f32, f32) {
+    let sin_y = (-m[(2, 0)]).max(-1.0).min(1.0);
+    if sin_y.abs() < 0.9999 {
+        (m[(2, 1)].atan2(m[(2, 2)]), sin_y.asin(), m[(1, 0)].atan2(m[(0, 0)]))
+    } else {
+        // gimbal lock, z rotation is merged into x rotation
+        ((-m[(1, 2)]).atan2(m[(1, 1)]), sin_y.signum() * std::f32::consts::FRAC_PI_2, 0.0)
+    }
 }
 
 fn scale_of_axes_to_scale_matrix(scale_x: f32, scale_y: f32, scale_z: f32) -> Mat4 {
@@ -498,6 +750,23 @@ fn create_model_matrix(translation_matrix: &Mat4, rotation_matrix: &Mat4, scalin
     translation_matrix * rotation_matrix * scaling_matrix
 }
 
+/// Splits transformation matrix into position, rotation angles and scale used by objects.
+fn decompose_matrix(matrix: &Mat4) -> (Vec3, Vec3, Vec3) {
+    let position = Vec3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+
+    let column = |i: usize| Vec3::new(matrix[(0, i)], matrix[(1, i)], matrix[(2, i)]);
+    let mut scale = Vec3::new(column(0).norm(), column(1).norm(), column(2).norm());
+    if nalgebra_glm::dot(&nalgebra_glm::cross::<f32, U3>(&column(0), &column(1)), &column(2)) < 0.0 {
+        scale.x = -scale.x;
+    }
+
+    let rotation = Mat3::from_fn(|row, col| if scale[col] != 0.0 { matrix[(row, col)] / scale[col] } else { 0.0 });
+    let (x, y, z) = rotation_matrix_to_angles(&rotation);
+    let rotation_angles = Vec3::new(x, y, z);
+
+    (position, rotation_angles, scale)
+}
+
 /// Generates new unique id.
 fn generate_id() -> u32 {
     let id = ID_COUNTER.fetch_add(1, Ordering::SeqCst) + 1;
@@ -581,6 +850,100 @@ mod tests {
         assert_ne!(model_matrix_5, model_matrix_6);
     }
 
+    #[test]
+    fn transform_set_from_model_matrix_gives_the_same_model_matrix() {
+        let object = ObjectBuilder::new("test").with_position(1.0, -2.0, 3.0)
+                                               .with_rotation_angle(0.3, -0.7, 1.2)
+                                               .with_scale(2.0, 0.5, 1.5)
+                                               .build();
+        let mut copy = ObjectBuilder::new("copy").build();
+        copy.set_transform_from_matrix(&object.model_matrix());
+
+        assert!((object.model_matrix() - copy.model_matrix()).amax() < 0.0001);
+        assert!(!copy.uniform_scale());
+    }
+
+    #[test]
+    fn transform_set_from_gimbal_locked_matrix_gives_the_same_model_matrix() {
+        let object = ObjectBuilder::new("test").with_rotation_angle(0.4, std::f32::consts::FRAC_PI_2, 0.2).build();
+        let mut copy = ObjectBuilder::new("copy").build();
+        copy.set_transform_from_matrix(&object.model_matrix());
+
+        assert!((object.model_matrix() - copy.model_matrix()).amax() < 0.0001);
+    }
+
+    fn assert_quat_eq(expected: &Qua<f32>, actual: &Qua<f32>) {
+        // q and -q are the same rotation
+        assert!(glm::quat_dot(expected, actual).abs() > 0.9999, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn rotation_angles_round_trip_through_quaternion() {
+        let axis = glm::normalize(&Vec3::new(1.0, 2.0, 3.0));
+        let rotation = glm::quat_angle_axis(0.8, &axis);
+        let (x, y, z) = quat_to_rotation_angles(&rotation);
+
+        assert_quat_eq(&rotation, &rotation_angles_to_quat(x, y, z));
+
+        let matrix = glm::rotate_z(&glm::identity(), z) * glm::rotate_y(&glm::identity(), y) * glm::rotate_x(&glm::identity(), x);
+        assert!((matrix - glm::quat_to_mat4(&rotation)).amax() < 0.0001);
+    }
+
+    #[test]
+    fn rotation_angles_set_by_user_are_kept() {
+        let mut object = ObjectBuilder::new("test").build();
+        object.set_rotation_angles(0.0, 3.0, 0.0);
+        object.set_rotation_angle_y(object.rotation_angle_y() + 0.5);
+
+        assert_eq!((0.0, 3.5, 0.0), object.rotation_angles());
+        assert_quat_eq(&glm::quat_angle_axis(3.5, &Vec3::y()), &object.rotation());
+    }
+
+    #[test]
+    fn set_rotation_updates_rotation_angles() {
+        let mut object = ObjectBuilder::new("test").build();
+        object.set_rotation_axis_angle((0.0, 0.0, 2.0), 0.5);
+
+        let (x, y, z) = object.rotation_angles();
+        assert!(x.abs() < 0.0001 && y.abs() < 0.0001 && (z - 0.5).abs() < 0.0001);
+        let expected = glm::rotate_z(&glm::identity(), 0.5);
+        assert!((expected - object.model_matrix()).amax() < 0.0001);
+    }
+
+    #[test]
+    fn look_at_points_negative_z_axis_at_target() {
+        let mut object = ObjectBuilder::new("test").with_position(1.0, 2.0, 3.0).build();
+        object.look_at((4.0, 2.0, -1.0), (0.0, 1.0, 0.0));
+
+        let forward = glm::quat_rotate_vec3(&object.rotation(), &Vec3::new(0.0, 0.0, -1.0));
+        assert!((forward - Vec3::new(0.6, 0.0, -0.8)).amax() < 0.0001);
+        let up = glm::quat_rotate_vec3(&object.rotation(), &Vec3::y());
+        assert!((up - Vec3::y()).amax() < 0.0001);
+
+        object.look_at((1.0, 10.0, 3.0), (0.0, 1.0, 0.0));
+        let forward = glm::quat_rotate_vec3(&object.rotation(), &Vec3::new(0.0, 0.0, -1.0));
+        assert!((forward - Vec3::y()).amax() < 0.0001);
+    }
+
+    #[test]
+    fn slerp_interpolates_along_shorter_path() {
+        let from = glm::quat_angle_axis(0.2, &Vec3::y());
+        let to = -glm::quat_angle_axis(1.0, &Vec3::y());
+
+        assert_quat_eq(&from, &slerp(&from, &to, 0.0));
+        assert_quat_eq(&to, &slerp(&from, &to, 1.0));
+        assert_quat_eq(&glm::quat_angle_axis(0.6, &Vec3::y()), &slerp(&from, &to, 0.5));
+        assert_quat_eq(&from, &slerp(&from, &from, 0.5));
+    }
+
+    #[test]
+    fn slerp_rotation_moves_object_towards_target() {
+        let mut object = ObjectBuilder::new("test").build();
+        object.slerp_rotation(&glm::quat_angle_axis(1.0, &Vec3::x()), 0.25);
+
+        assert!((object.rotation_angle_x() - 0.25).abs() < 0.0001);
+    }
+
     #[test]
     fn cloned_object_has_different_id() {
         let object = ObjectBuilder::new("test").build();
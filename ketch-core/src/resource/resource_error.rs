@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::io;
+use std::path::PathBuf;
+
+use image::ImageError;
+
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum ObjLoadError {
+        ObjParseError(err: tobj::LoadError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        MaterialLoadError(err: tobj::LoadError) {
+            display(x) -> ("{}: couldn't load MTL file: {}", x.description(), err)
+            cause(err)
+        }
+        InvalidMaterialError(material_id: usize) {
+            display("InvalidMaterialError: mesh refers to material {} which doesn't exist", material_id)
+        }
+        TextureLoadError(path: PathBuf, err: ImageError) {
+            display(x) -> ("{}: couldn't load texture {}: {}", x.description(), path.display(), err)
+            cause(err)
+        }
+    }
+}
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum GltfLoadError {
+        GltfError(err: gltf::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        MissingSceneError {
+            display("MissingSceneError: file doesn't contain any scene")
+        }
+        MissingPositionsError(mesh_name: String) {
+            display("MissingPositionsError: mesh {} has a primitive without vertex positions", mesh_name)
+        }
+        UnsupportedPrimitiveModeError(mesh_name: String, mode: gltf::mesh::Mode) {
+            display("UnsupportedPrimitiveModeError: mesh {} uses {:?} primitives, only triangles are supported", mesh_name, mode)
+        }
+        UnsupportedImageFormatError(image_index: usize, format: gltf::image::Format) {
+            display("UnsupportedImageFormatError: image {} uses unsupported pixel format {:?}", image_index, format)
+        }
+    }
+}
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum SceneFileError {
+        IoError(err: io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SerializationError(err: ron::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ParseError(err: ron::error::SpannedError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        MissingMeshError(name: String) {
+            display("MissingMeshError: asset manager doesn't have mesh {}", name)
+        }
+        MissingTextureError(name: String) {
+            display("MissingTextureError: asset manager doesn't have texture {}", name)
+        }
+        MissingMaterialError(name: String) {
+            display("MissingMaterialError: asset manager doesn't have material {}", name)
+        }
+        InvalidParentError(index: usize) {
+            display("InvalidParentError: object {} has parent which doesn't exist or is its descendant", index)
+        }
+    }
+}
@@ -1,29 +1,30 @@
-use crate::renderer::shader::fragment_shader::ty::LightData;
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Mat4, Vec3};
 use crate::resource::object::Object;
 use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::bounds::{BoundingBox, Ray};
+
+use std::collections::HashMap;
 
 /// Scene is a collection of game objects and world properties.
 /// Only one can be active at a time.
-#[derive(Clone)]
+/// Objects can be attached to other objects, their transforms are then relative to the parent.
 pub struct Scene {
     name: String,
 
     camera: Camera,
     objects: Vec<Object>,
-    light_position: Vec3,
-    light_color: Vec3,
+    lights: Vec<Light>,
 }
 
 impl Scene {
-    /// Creates new scene.
+    /// Creates new scene with a white point light placed at the origin.
     pub fn new<S: Into<String>>(name: S, camera: Camera) -> Self {
         Scene {
             name: name.into(),
             camera,
             objects: Vec::new(),
-            light_position: Vec3::new(0.0, 0.0, 0.0),
-            light_color: Vec3::new(1.0, 1.0, 1.0),
+            lights: vec![Light::point(0.0, 0.0, 0.0)],
         }
     }
 
@@ -32,33 +33,185 @@ impl Scene {
         &self.name
     }
 
+    /// Returns copy of the scene in which objects keep their ids, unlike in `clone`.
+    /// It can replace the scene later to discard changes made since the snapshot was taken.
+    /// Meshes and materials are shared with the scene, so changes made to them aren't discarded.
+    pub fn snapshot(&self) -> Scene {
+        Scene {
+            name: self.name.clone(),
+            camera: self.camera.clone(),
+            objects: self.objects.iter().map(Object::snapshot).collect(),
+            lights: self.lights.clone(),
+        }
+    }
+
     /// Adds object to the scene.
     pub fn add_object(&mut self, object: Object) {
         self.objects.push(object);
     }
 
-    /// Removes object from the scene and returns it if found.
+    /// Adds object as a child of object with id parent. Object transform is relative to the parent.
+    /// Returns false and doesn't add the object if the parent isn't in the scene.
+    pub fn add_child_object(&mut self, parent: u32, mut object: Object) -> bool {
+        if self.object(parent).is_none() {
+            return false;
+        }
+        object.set_parent_id(Some(parent));
+        self.objects.push(object);
+        true
+    }
+
+    /// Removes object from the scene and returns it if found. Children of the object are removed as well.
     pub fn remove_object(&mut self, id: u32) -> Option<Object> {
-        let object_index = self.objects.iter().position(|x| x.id() == id);
-        match object_index {
-            Some(index) => Some(self.objects.remove(index)),
-            None => None,
+        self.remove_object_with_descendants(id).into_iter().next()
+    }
+
+    /// Removes object and all its descendants from the scene and returns them, parents before their children.
+    /// Returned objects keep their ids, so adding them back in the same order restores the hierarchy.
+    pub fn remove_object_with_descendants(&mut self, id: u32) -> Vec<Object> {
+        let object_index = match self.objects.iter().position(|x| x.id() == id) {
+            Some(object_index) => object_index,
+            None => return Vec::new(),
+        };
+        let mut removed = vec![self.objects.remove(object_index)];
+
+        let child_ids: Vec<u32> = self.children(id).iter().map(|child| child.id()).collect();
+        for child_id in child_ids {
+            removed.extend(self.remove_object_with_descendants(child_id));
         }
+
+        removed
     }
 
-    /// Removes objects with specified name and returns them in a vector if any are found. 
+    /// Removes objects with specified name and returns them in a vector if any are found.
+    /// Children of removed objects are removed as well.
     pub fn remove_objects_with_name(&mut self, name: &str) -> Vec<Object> {
-        let object_indexes: Vec<usize> = self.objects.iter()
-                                                     .enumerate()
-                                                     .filter(|(_i, x)| x.name() == name)
-                                                     .map(|(i, _x)| i)
-                                                     .collect();
-
-        let mut removed_objects = Vec::new();
-        for index in object_indexes {
-            removed_objects.push(self.objects.remove(index));
+        let object_ids: Vec<u32> = self.objects.iter()
+                                               .filter(|x| x.name() == name)
+                                               .map(|x| x.id())
+                                               .collect();
+
+        object_ids.into_iter().filter_map(|id| self.remove_object(id)).collect()
+    }
+
+    /// Returns a reference to object with the given id.
+    pub fn object(&self, id: u32) -> Option<&Object> {
+        self.objects.iter().find(|object| object.id() == id)
+    }
+
+    /// Returns a mutable reference to object with the given id.
+    pub fn object_mut(&mut self, id: u32) -> Option<&mut Object> {
+        self.objects.iter_mut().find(|object| object.id() == id)
+    }
+
+    /// Returns direct children of object with the given id.
+    pub fn children(&self, id: u32) -> Vec<&Object> {
+        self.objects.iter().filter(|object| object.parent() == Some(id)).collect()
+    }
+
+    /// Attaches object to a new parent, or detaches it with None, keeping its world transform.
+    /// Returns false if any of the objects isn't in the scene or parent is the object itself or one of its descendants.
+    pub fn set_parent(&mut self, id: u32, parent: Option<u32>) -> bool {
+        self.attach(id, parent, true)
+    }
+
+    /// Attaches object to a new parent. If keep_world_transform is false, local transform of the object is kept instead.
+    pub(crate) fn attach(&mut self, id: u32, parent: Option<u32>, keep_world_transform: bool) -> bool {
+        if self.object(id).is_none() {
+            return false;
+        }
+
+        let parent_world_matrix = match parent {
+            Some(parent) => {
+                if self.ancestors(parent).into_iter().chain(Some(parent)).any(|ancestor| ancestor == id) {
+                    return false;
+                }
+                match self.world_matrix(parent) {
+                    Some(matrix) => matrix,
+                    None => return false,
+                }
+            },
+            None => Mat4::identity(),
+        };
+
+        let world_matrix = self.world_matrix(id).unwrap();
+        let object = self.object_mut(id).unwrap();
+        object.set_parent_id(parent);
+        if keep_world_transform {
+            let local_matrix = parent_world_matrix.try_inverse().unwrap_or_else(Mat4::identity) * world_matrix;
+            object.set_transform_from_matrix(&local_matrix);
+        }
+        true
+    }
+
+    /// Returns ids of parent, grandparent and so on of the object.
+    fn ancestors(&self, id: u32) -> Vec<u32> {
+        let mut ancestors = Vec::new();
+        let mut current = self.object(id).and_then(|object| object.parent());
+        while let Some(parent) = current {
+            // guards against cycles, which can't be created using the scene methods
+            if ancestors.contains(&parent) || ancestors.len() > self.objects.len() {
+                break;
+            }
+            ancestors.push(parent);
+            current = self.object(parent).and_then(|object| object.parent());
         }
-        removed_objects
+        ancestors
+    }
+
+    /// Returns matrix transforming object from its local space to world space,
+    /// which is its model matrix composed with model matrices of all its ancestors.
+    pub fn world_matrix(&self, id: u32) -> Option<Mat4> {
+        let object = self.object(id)?;
+        let world_matrix = self.ancestors(id).into_iter()
+                                             .filter_map(|ancestor| self.object(ancestor))
+                                             .fold(object.model_matrix(), |matrix, ancestor| ancestor.model_matrix() * matrix);
+        Some(world_matrix)
+    }
+
+    /// Returns world space box containing mesh of object with the given id.
+    pub fn world_bounding_box(&self, id: u32) -> Option<BoundingBox> {
+        self.object(id)?.world_bounding_box(&self.world_matrix(id)?)
+    }
+
+    /// Returns world matrices of all objects in the same order as `objects`.
+    pub fn world_matrices(&self) -> Vec<Mat4> {
+        let mut world_matrices: HashMap<u32, Mat4> = HashMap::with_capacity(self.objects.len());
+        let objects: HashMap<u32, &Object> = self.objects.iter().map(|object| (object.id(), object)).collect();
+
+        self.objects.iter().map(|object| {
+            // walk up until an ancestor with already known world matrix is found
+            let mut chain = vec![object];
+            let mut matrix = Mat4::identity();
+            while let Some(parent) = chain.last().unwrap().parent().and_then(|parent| objects.get(&parent).cloned()) {
+                if let Some(parent_matrix) = world_matrices.get(&parent.id()) {
+                    matrix = *parent_matrix;
+                    break;
+                }
+                if chain.len() > self.objects.len() {
+                    break;
+                }
+                chain.push(parent);
+            }
+
+            for ancestor in chain.into_iter().rev() {
+                matrix = matrix * ancestor.model_matrix();
+                world_matrices.insert(ancestor.id(), matrix);
+            }
+            matrix
+        }).collect()
+    }
+
+    /// Returns id of the nearest object hit by the ray, see `Camera::screen_ray`. Objects without mesh can't be hit.
+    pub fn pick_object(&self, ray: &Ray) -> Option<u32> {
+        self.objects.iter()
+                    .zip(self.world_matrices())
+                    .filter_map(|(object, world_matrix)| object.ray_intersection(ray, &world_matrix).map(|distance| (object.id(), distance)))
+                    .fold(None, |nearest: Option<(u32, f32)>, (id, distance)| match nearest {
+                        Some((_, nearest_distance)) if nearest_distance <= distance => nearest,
+                        _ => Some((id, distance)),
+                    })
+                    .map(|(id, _)| id)
     }
 
     /// Returns a reference to slice of all objects.
@@ -86,55 +239,230 @@ impl Scene {
         &mut self.camera
     }
 
+    /// Adds light to the scene.
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    /// Removes and returns light with the given index if it exists.
+    pub fn remove_light(&mut self, index: usize) -> Option<Light> {
+        if index < self.lights.len() {
+            Some(self.lights.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes all lights from the scene.
+    pub fn clear_lights(&mut self) {
+        self.lights.clear();
+    }
+
+    /// Returns a reference to slice of all lights.
+    pub fn lights(&self) -> &[Light] {
+        self.lights.as_slice()
+    }
+
+    /// Returns a reference to a mutable slice of all lights.
+    pub fn lights_mut(&mut self) -> &mut [Light] {
+        self.lights.as_mut_slice()
+    }
+
+    /// Returns the main light of the scene, which is the first light on the list.
+    pub fn main_light(&self) -> Option<&Light> {
+        self.lights.first()
+    }
+
+    /// Returns a mutable reference to the main light of the scene.
+    /// If scene has no lights, a white point light placed at the origin is added.
+    pub fn main_light_mut(&mut self) -> &mut Light {
+        if self.lights.is_empty() {
+            self.lights.push(Light::point(0.0, 0.0, 0.0));
+        }
+        &mut self.lights[0]
+    }
+
     pub fn light_color_vec3(&self) -> Vec3 {
-        self.light_color
+        let (r, g, b) = self.light_color();
+        Vec3::new(r, g, b)
     }
 
+    /// Returns color of the main light, or white if scene has no lights.
     pub fn light_color(&self) -> (f32, f32, f32) {
-        (self.light_color.x, self.light_color.y, self.light_color.z)
+        self.main_light().map(|light| light.color()).unwrap_or((1.0, 1.0, 1.0))
     }
 
+    /// Sets color of the main light.
     pub fn set_light_color(&mut self, r: f32, g: f32, b: f32) {
-        self.light_color = Vec3::new(r, g, b);
+        self.main_light_mut().set_color(r, g, b);
     }
 
+    /// Sets position of the main light.
     pub fn set_light_position(&mut self, x: f32, y: f32, z: f32) {
-        self.light_position = Vec3::new(x, y, z);
+        self.main_light_mut().set_position(x, y, z);
     }
 
     pub fn set_light_position_x(&mut self, x: f32) {
-        self.light_position.x = x;
+        let (_, y, z) = self.light_position();
+        self.set_light_position(x, y, z);
     }
 
     pub fn set_light_position_y(&mut self, y: f32) {
-        self.light_position.y = y;
+        let (x, _, z) = self.light_position();
+        self.set_light_position(x, y, z);
     }
 
     pub fn set_light_position_z(&mut self, z: f32) {
-        self.light_position.z = z;
+        let (x, y, _) = self.light_position();
+        self.set_light_position(x, y, z);
     }
 
+    /// Returns position of the main light, or the origin if scene has no lights.
     pub fn light_position(&self) -> (f32, f32, f32) {
-        (self.light_position.x, self.light_position.y, self.light_position.z)
+        self.main_light().map(|light| light.position()).unwrap_or((0.0, 0.0, 0.0))
     }
 
     pub fn light_position_x(&self) -> f32 {
-        self.light_position.x
+        self.light_position().0
     }
 
     pub fn light_position_y(&self) -> f32 {
-        self.light_position.y
+        self.light_position().1
     }
 
     pub fn light_position_z(&self) -> f32 {
-        self.light_position.z
+        self.light_position().2
+    }
+}
+
+impl Clone for Scene {
+    /// Cloned objects get new ids, so parents are remapped to the clones.
+    fn clone(&self) -> Scene {
+        let objects: Vec<Object> = self.objects.iter().cloned().collect();
+        let new_ids: HashMap<u32, u32> = self.objects.iter().zip(objects.iter())
+                                                    .map(|(object, clone)| (object.id(), clone.id()))
+                                                    .collect();
+        let objects = objects.into_iter().map(|mut object| {
+            let parent = object.parent().and_then(|parent| new_ids.get(&parent).cloned());
+            object.set_parent_id(parent);
+            object
+        }).collect();
+
+        Scene {
+            name: self.name.clone(),
+            camera: self.camera.clone(),
+            objects,
+            lights: self.lights.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::object::ObjectBuilder;
+
+    fn assert_matrix_eq(expected: &Mat4, actual: &Mat4) {
+        assert!((expected - actual).amax() < 0.0001, "expected {}, got {}", expected, actual);
+    }
+
+    fn tank_scene() -> (Scene, u32, u32, u32) {
+        let mut scene = Scene::new("test", Camera::new());
+        let tank = ObjectBuilder::new("tank").with_position(10.0, 0.0, 0.0).with_rotation_angle(0.0, 1.0, 0.0).build();
+        let turret = ObjectBuilder::new("turret").with_position(0.0, 1.0, 0.0).build();
+        let barrel = ObjectBuilder::new("barrel").with_position(0.0, 0.0, 2.0).with_scale(0.5, 0.5, 2.0).build();
+        let (tank_id, turret_id, barrel_id) = (tank.id(), turret.id(), barrel.id());
+
+        scene.add_object(tank);
+        assert!(scene.add_child_object(tank_id, turret));
+        assert!(scene.add_child_object(turret_id, barrel));
+        (scene, tank_id, turret_id, barrel_id)
     }
 
-    pub fn light_data(&self) -> LightData {
-        LightData {
-            _dummy0: [0; 4],
-            light_position: self.light_position.into(),
-            light_color: self.light_color.into(),
+    #[test]
+    fn world_matrix_composes_model_matrices_of_ancestors() {
+        let (scene, tank_id, turret_id, barrel_id) = tank_scene();
+        let expected = scene.object(tank_id).unwrap().model_matrix()
+                     * scene.object(turret_id).unwrap().model_matrix()
+                     * scene.object(barrel_id).unwrap().model_matrix();
+
+        assert_matrix_eq(&expected, &scene.world_matrix(barrel_id).unwrap());
+        assert_matrix_eq(&expected, &scene.world_matrices()[2]);
+        assert_eq!(scene.world_matrices().len(), 3);
+    }
+
+    #[test]
+    fn reparenting_keeps_world_transform() {
+        let (mut scene, tank_id, turret_id, barrel_id) = tank_scene();
+        let world_matrix = scene.world_matrix(turret_id).unwrap();
+
+        assert!(scene.set_parent(turret_id, None));
+        assert_eq!(None, scene.object(turret_id).unwrap().parent());
+        assert_matrix_eq(&world_matrix, &scene.world_matrix(turret_id).unwrap());
+
+        let barrel_world_matrix = scene.world_matrix(barrel_id).unwrap();
+        assert!(scene.set_parent(barrel_id, Some(tank_id)));
+        assert_matrix_eq(&barrel_world_matrix, &scene.world_matrix(barrel_id).unwrap());
+    }
+
+    #[test]
+    fn object_can_not_be_attached_to_its_descendant() {
+        let (mut scene, tank_id, _turret_id, barrel_id) = tank_scene();
+
+        assert!(!scene.set_parent(tank_id, Some(barrel_id)));
+        assert!(!scene.set_parent(tank_id, Some(tank_id)));
+        assert_eq!(None, scene.object(tank_id).unwrap().parent());
+    }
+
+    #[test]
+    fn removing_object_removes_its_descendants() {
+        let (mut scene, _tank_id, turret_id, _barrel_id) = tank_scene();
+        scene.add_object(ObjectBuilder::new("tree").build());
+
+        let removed = scene.remove_object(turret_id).unwrap();
+        assert_eq!("turret", removed.name());
+        let names: Vec<&str> = scene.objects().iter().map(|object| object.name()).collect();
+        assert_eq!(vec!["tank", "tree"], names);
+    }
+
+    #[test]
+    fn cloned_scene_keeps_hierarchy() {
+        let (scene, _tank_id, _turret_id, _barrel_id) = tank_scene();
+        let cloned = scene.clone();
+
+        let barrel = &cloned.objects()[2];
+        let turret = &cloned.objects()[1];
+        assert_eq!(Some(turret.id()), barrel.parent());
+        assert_matrix_eq(&scene.world_matrices()[2], &cloned.world_matrices()[2]);
+    }
+
+    #[test]
+    fn removed_descendants_can_be_added_back() {
+        let (mut scene, tank_id, turret_id, barrel_id) = tank_scene();
+        let world_matrix = scene.world_matrix(barrel_id).unwrap();
+
+        let removed = scene.remove_object_with_descendants(turret_id);
+        let ids: Vec<u32> = removed.iter().map(|object| object.id()).collect();
+        assert_eq!(vec![turret_id, barrel_id], ids);
+        assert_eq!(1, scene.objects().len());
+
+        for object in removed {
+            scene.add_object(object);
         }
+        assert_eq!(Some(tank_id), scene.object(turret_id).unwrap().parent());
+        assert_matrix_eq(&world_matrix, &scene.world_matrix(barrel_id).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn snapshot_keeps_ids_and_state_from_before_changes() {
+        let (mut scene, tank_id, turret_id, barrel_id) = tank_scene();
+        let snapshot = scene.snapshot();
+
+        scene.object_mut(tank_id).unwrap().set_position(0.0, 5.0, 0.0);
+        scene.remove_object(barrel_id);
+
+        assert_eq!((10.0, 0.0, 0.0), snapshot.object(tank_id).unwrap().position());
+        assert_eq!(Some(turret_id), snapshot.object(barrel_id).unwrap().parent());
+        assert_eq!(3, snapshot.objects().len());
+    }
+}
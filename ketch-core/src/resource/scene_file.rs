@@ -0,0 +1,399 @@
+use crate::resource::camera::Camera;
+use crate::resource::light::{Light, LightKind};
+use crate::resource::mesh::Mesh;
+use crate::resource::object::ObjectBuilder;
+use crate::resource::resource_error::SceneFileError;
+use crate::resource::scene::Scene;
+use crate::resource::texture::Texture;
+use crate::resource::material::Material;
+
+use serde::{Deserialize, Serialize};
+
+use std::sync::{Arc, RwLock};
+
+/// On-disk representation of a scene stored in RON format.
+/// Meshes, textures and materials are not stored in the file, they are referenced by their names in asset manager.
+/// Angles of object rotation are in radians, camera yaw, pitch and fov are in degrees.
+///
+/// ```text
+/// (
+///     name: "level",
+///     camera: (
+///         position: (0.0, 0.0, 3.0),
+///         yaw: -90.0,
+///         pitch: 0.0,
+///         fov: 45.0,
+///         near_plane: 0.1,
+///         far_plane: 1000.0,
+///     ),
+///     lights: [
+///         (
+///             kind: Point,
+///             position: (0.0, 5.0, 0.0),
+///             direction: (0.0, -1.0, 0.0),
+///             color: (1.0, 1.0, 1.0),
+///             intensity: 1.0,
+///             attenuation: (1.0, 0.0, 0.0),
+///             cutoff_angles: (12.5, 17.5),
+///         ),
+///     ],
+///     objects: [
+///         (
+///             name: "cube",
+///             position: (0.0, 0.0, 0.0),
+///             rotation_angles: (0.0, 0.5, 0.0),
+///             scale: (1.0, 1.0, 1.0),
+///             light_source: false,
+///             cast_shadows: true,
+///             receive_shadows: true,
+///             mesh: Some("cube"),
+///             texture: Some("wood"),
+///             material: Some("varnish"),
+///             parent: None,
+///         ),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneFile {
+    pub name: String,
+    pub camera: CameraFile,
+    pub lights: Vec<LightFile>,
+    pub objects: Vec<ObjectFile>,
+}
+
+/// Camera parameters stored in scene file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraFile {
+    pub position: (f32, f32, f32),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+    pub near_plane: f32,
+    pub far_plane: f32,
+}
+
+/// Light parameters stored in scene file. Cutoff angles of spot lights are in degrees.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightFile {
+    pub kind: LightKind,
+    pub position: (f32, f32, f32),
+    pub direction: (f32, f32, f32),
+    pub color: (f32, f32, f32),
+    pub intensity: f32,
+    pub attenuation: (f32, f32, f32),
+    pub cutoff_angles: (f32, f32),
+}
+
+impl LightFile {
+    fn from_light(light: &Light) -> Self {
+        LightFile {
+            kind: light.kind(),
+            position: light.position(),
+            direction: light.direction(),
+            color: light.color(),
+            intensity: light.intensity(),
+            attenuation: light.attenuation(),
+            cutoff_angles: light.cutoff_angles(),
+        }
+    }
+
+    fn into_light(self) -> Light {
+        let mut light = Light::point(self.position.0, self.position.1, self.position.2);
+        light.set_kind(self.kind);
+        light.set_direction(self.direction.0, self.direction.1, self.direction.2);
+        light.set_color(self.color.0, self.color.1, self.color.2);
+        light.set_intensity(self.intensity);
+        light.set_attenuation(self.attenuation.0, self.attenuation.1, self.attenuation.2);
+        light.set_cutoff_angles(self.cutoff_angles.0, self.cutoff_angles.1);
+        light
+    }
+}
+
+/// Object stored in scene file. Mesh, texture and material are names of assets from asset manager.
+/// Texture replaces texture of the mesh only for this object, if it's missing, the texture set on the mesh is used.
+/// If material is missing, the object uses material of its mesh.
+/// Parent is index of the parent object in the objects list, transform of the object is relative to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectFile {
+    pub name: String,
+    pub position: (f32, f32, f32),
+    pub rotation_angles: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+    pub light_source: bool,
+    #[serde(default = "default_true")]
+    pub cast_shadows: bool,
+    #[serde(default = "default_true")]
+    pub receive_shadows: bool,
+    #[serde(default)]
+    pub mesh: Option<String>,
+    #[serde(default)]
+    pub texture: Option<String>,
+    #[serde(default)]
+    pub material: Option<String>,
+    #[serde(default)]
+    pub parent: Option<usize>,
+}
+
+/// Objects from files without shadow flags cast and receive shadows.
+fn default_true() -> bool {
+    true
+}
+
+impl SceneFile {
+    /// Creates scene file from scene.
+    pub fn from_scene(scene: &Scene) -> Self {
+        let camera = scene.camera();
+
+        SceneFile {
+            name: scene.name().to_string(),
+            camera: CameraFile {
+                position: camera.position(),
+                yaw: camera.yaw(),
+                pitch: camera.pitch(),
+                fov: camera.fov(),
+                near_plane: camera.near_plane(),
+                far_plane: camera.far_plane(),
+            },
+            lights: scene.lights().iter().map(LightFile::from_light).collect(),
+            objects: scene.objects().iter().map(|object| {
+                let parent = object.parent().and_then(|parent| scene.objects().iter().position(|x| x.id() == parent));
+
+                ObjectFile {
+                    name: object.name().to_string(),
+                    position: object.position(),
+                    rotation_angles: object.rotation_angles(),
+                    scale: object.scale(),
+                    light_source: object.light_source(),
+                    cast_shadows: object.cast_shadows(),
+                    receive_shadows: object.receive_shadows(),
+                    mesh: object.mesh().map(|mesh| mesh.read().unwrap().name().to_string()),
+                    texture: object.texture().map(|texture| texture.name().to_string()),
+                    material: object.material().map(|material| material.read().unwrap().name().to_string()),
+                    parent,
+                }
+            }).collect(),
+        }
+    }
+
+    /// Creates scene from scene file. Meshes, textures and materials are looked up by name using provided functions.
+    pub fn into_scene<M, T, A>(self, find_mesh: M, find_texture: T, find_material: A) -> Result<Scene, SceneFileError>
+        where M: Fn(&str) -> Option<Arc<RwLock<Mesh>>>,
+              T: Fn(&str) -> Option<Arc<Texture>>,
+              A: Fn(&str) -> Option<Arc<RwLock<Material>>>
+    {
+        let mut camera = Camera::new();
+        let (x, y, z) = self.camera.position;
+        camera.set_position(x, y, z);
+        camera.set_yaw(self.camera.yaw);
+        camera.set_pitch(self.camera.pitch);
+        camera.set_fov(self.camera.fov);
+        camera.set_near_plane(self.camera.near_plane);
+        camera.set_far_plane(self.camera.far_plane);
+
+        let mut scene = Scene::new(self.name, camera);
+        scene.clear_lights();
+        for light_file in self.lights {
+            scene.add_light(light_file.into_light());
+        }
+
+        let mut parents = Vec::with_capacity(self.objects.len());
+        for object_file in self.objects {
+            parents.push(object_file.parent);
+
+            let (position_x, position_y, position_z) = object_file.position;
+            let (angle_x, angle_y, angle_z) = object_file.rotation_angles;
+            let (scale_x, scale_y, scale_z) = object_file.scale;
+
+            let mut object = ObjectBuilder::new(object_file.name)
+                                           .with_position(position_x, position_y, position_z)
+                                           .with_rotation_angle(angle_x, angle_y, angle_z)
+                                           .with_scale(scale_x, scale_y, scale_z)
+                                           .build();
+            object.set_light_source(object_file.light_source);
+            object.set_cast_shadows(object_file.cast_shadows);
+            object.set_receive_shadows(object_file.receive_shadows);
+
+            if let Some(mesh_name) = object_file.mesh {
+                let mesh = find_mesh(&mesh_name).ok_or(SceneFileError::MissingMeshError(mesh_name))?;
+                object.set_mesh(mesh);
+            }
+
+            if let Some(texture_name) = object_file.texture {
+                let texture = find_texture(&texture_name).ok_or(SceneFileError::MissingTextureError(texture_name))?;
+                object.set_texture(Some(texture));
+            }
+
+            if let Some(material_name) = object_file.material {
+                let material = find_material(&material_name).ok_or(SceneFileError::MissingMaterialError(material_name))?;
+                object.set_material(Some(material));
+            }
+
+            scene.add_object(object);
+        }
+
+        let ids: Vec<u32> = scene.objects().iter().map(|object| object.id()).collect();
+        for (index, parent) in parents.into_iter().enumerate() {
+            if let Some(parent) = parent {
+                let parent_id = *ids.get(parent).ok_or(SceneFileError::InvalidParentError(index))?;
+                if !scene.attach(ids[index], Some(parent_id), false) {
+                    return Err(SceneFileError::InvalidParentError(index));
+                }
+            }
+        }
+
+        Ok(scene)
+    }
+
+    /// Serializes scene file to RON string.
+    pub fn to_ron(&self) -> Result<String, SceneFileError> {
+        Ok(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?)
+    }
+
+    /// Deserializes scene file from RON string.
+    pub fn from_ron(content: &str) -> Result<Self, SceneFileError> {
+        Ok(ron::de::from_str(content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_scene() -> Scene {
+        let mut camera = Camera::new();
+        camera.set_position(1.0, 2.0, 3.0);
+        camera.set_yaw(30.0);
+        camera.set_pitch(-20.0);
+        camera.set_fov(40.0);
+        camera.set_near_plane(0.5);
+        camera.set_far_plane(200.0);
+
+        let mut scene = Scene::new("test", camera);
+        scene.set_light_position(4.0, 5.0, 6.0);
+        scene.set_light_color(0.5, 0.25, 1.0);
+        let mut spot_light = Light::spot((1.0, 1.0, 1.0), (0.0, -1.0, 0.0));
+        spot_light.set_intensity(2.0);
+        spot_light.set_attenuation(1.0, 0.09, 0.032);
+        spot_light.set_cutoff_angles(10.0, 15.0);
+        scene.add_light(spot_light);
+
+        let mut lamp = ObjectBuilder::new("lamp").with_position(4.0, 5.0, 6.0).build();
+        lamp.set_light_source(true);
+        scene.add_object(lamp);
+        scene.add_object(ObjectBuilder::new("box")
+                                       .with_position(-1.0, 0.0, 2.5)
+                                       .with_rotation_angle(0.1, 0.2, 0.3)
+                                       .with_scale(1.0, 2.0, 3.0)
+                                       .build());
+        let mut floor = ObjectBuilder::new("floor").with_scale(10.0, 0.1, 10.0).build();
+        floor.set_cast_shadows(false);
+        scene.add_object(floor);
+        scene
+    }
+
+    #[test]
+    fn scene_file_round_trips_through_ron() {
+        let scene_file = SceneFile::from_scene(&test_scene());
+        let content = scene_file.to_ron().unwrap();
+
+        assert_eq!(scene_file, SceneFile::from_ron(&content).unwrap());
+    }
+
+    #[test]
+    fn scene_round_trips_through_scene_file() {
+        let scene_file = SceneFile::from_scene(&test_scene());
+        let scene = scene_file.clone().into_scene(|_| None, |_| None, |_| None).unwrap();
+
+        assert_eq!(scene_file, SceneFile::from_scene(&scene));
+        assert_eq!((4.0, 5.0, 6.0), scene.light_position());
+        assert_eq!((0.5, 0.25, 1.0), scene.light_color());
+        assert_eq!(2, scene.lights().len());
+        assert_eq!(LightKind::Spot, scene.lights()[1].kind());
+        assert_eq!((1.0, 0.09, 0.032), scene.lights()[1].attenuation());
+        assert_eq!(30.0, scene.camera().yaw());
+        assert!(scene.objects()[0].light_source());
+        assert_eq!((1.0, 2.0, 3.0), scene.objects()[1].scale());
+        assert!(!scene.objects()[2].cast_shadows());
+        assert!(scene.objects()[2].receive_shadows());
+    }
+
+    #[test]
+    fn objects_without_shadow_flags_cast_and_receive_shadows() {
+        let mut scene_file = SceneFile::from_scene(&test_scene());
+        scene_file.objects.truncate(1);
+        let content = scene_file.to_ron().unwrap()
+                                .lines()
+                                .filter(|line| !line.contains("shadows"))
+                                .collect::<Vec<_>>()
+                                .join("\n");
+
+        let object = &SceneFile::from_ron(&content).unwrap().objects[0];
+        assert!(object.cast_shadows);
+        assert!(object.receive_shadows);
+    }
+
+    #[test]
+    fn missing_mesh_returns_error() {
+        let mut scene_file = SceneFile::from_scene(&test_scene());
+        scene_file.objects[1].mesh = Some("missing".to_string());
+
+        match scene_file.into_scene(|_| None, |_| None, |_| None) {
+            Err(SceneFileError::MissingMeshError(name)) => assert_eq!("missing", name),
+            _ => panic!("expected MissingMeshError"),
+        }
+    }
+
+    #[test]
+    fn object_material_is_looked_up_by_name() {
+        let mut scene_file = SceneFile::from_scene(&test_scene());
+        scene_file.objects[1].material = Some("metal".to_string());
+
+        let metal = Arc::new(RwLock::new(Material::new("metal")));
+        let scene = scene_file.clone().into_scene(|_| None, |_| None, |name| if name == "metal" { Some(metal.clone()) } else { None }).unwrap();
+        assert_eq!("metal", scene.objects()[1].material().unwrap().read().unwrap().name());
+        assert_eq!(scene_file, SceneFile::from_scene(&scene));
+
+        scene_file.objects[1].material = Some("missing".to_string());
+        match scene_file.into_scene(|_| None, |_| None, |_| None) {
+            Err(SceneFileError::MissingMaterialError(name)) => assert_eq!("missing", name),
+            _ => panic!("expected MissingMaterialError"),
+        }
+    }
+
+    #[test]
+    fn object_hierarchy_round_trips_through_scene_file() {
+        let mut scene = test_scene();
+        let (lamp_id, box_id) = (scene.objects()[0].id(), scene.objects()[1].id());
+        assert!(scene.set_parent(lamp_id, Some(box_id)));
+        let lamp_world_matrix = scene.world_matrix(lamp_id).unwrap();
+
+        let scene_file = SceneFile::from_scene(&scene);
+        assert_eq!(Some(1), scene_file.objects[0].parent);
+
+        let loaded_scene = scene_file.into_scene(|_| None, |_| None, |_| None).unwrap();
+        let lamp = &loaded_scene.objects()[0];
+        assert_eq!(Some(loaded_scene.objects()[1].id()), lamp.parent());
+        assert!((lamp_world_matrix - loaded_scene.world_matrix(lamp.id()).unwrap()).amax() < 0.0001);
+    }
+
+    #[test]
+    fn cyclic_parents_return_error() {
+        let mut scene_file = SceneFile::from_scene(&test_scene());
+        scene_file.objects[0].parent = Some(1);
+        scene_file.objects[1].parent = Some(0);
+
+        match scene_file.into_scene(|_| None, |_| None, |_| None) {
+            Err(SceneFileError::InvalidParentError(index)) => assert_eq!(1, index),
+            _ => panic!("expected InvalidParentError"),
+        }
+    }
+
+    #[test]
+    fn invalid_content_returns_parse_error() {
+        match SceneFile::from_ron("(name: \"test\", camera: ") {
+            Err(SceneFileError::ParseError(_)) => (),
+            _ => panic!("expected ParseError"),
+        }
+    }
+}
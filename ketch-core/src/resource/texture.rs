@@ -26,13 +26,23 @@ impl Texture {
         Texture::new(name, loaded_image, upload_queue, device)
     }
 
-    /// Creates new texture from loaded image.
+    /// Creates new texture from loaded image. Colors are treated as sRGB.
     pub fn new<S: Into<String>>(name: S, image: DynamicImage, upload_queue: Arc<Queue>, device: Arc<Device>) -> Self {
+        Texture::with_format(name, image, Format::R8G8B8A8Srgb, upload_queue, device)
+    }
+
+    /// Creates new texture from loaded image without sRGB conversion.
+    /// Used for images storing data instead of colors, like normal or specular maps.
+    pub fn new_linear<S: Into<String>>(name: S, image: DynamicImage, upload_queue: Arc<Queue>, device: Arc<Device>) -> Self {
+        Texture::with_format(name, image, Format::R8G8B8A8Unorm, upload_queue, device)
+    }
+
+    fn with_format<S: Into<String>>(name: S, image: DynamicImage, format: Format, upload_queue: Arc<Queue>, device: Arc<Device>) -> Self {
         let image = image.flipv().to_rgba();
         let (image_buffer, _future) = ImmutableImage::from_iter(
             image.clone().into_raw().into_iter(),
             Dimensions::Dim2d { width: image.width(), height: image.height() },
-            Format::R8G8B8A8Srgb,
+            format,
             upload_queue,
         ).expect("Failed to create image buffer");
 
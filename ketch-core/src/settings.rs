@@ -1,12 +1,18 @@
 use std::time::Duration;
 use winit::dpi::PhysicalSize;
 
+use crate::renderer::shader::MAX_LIGHTS;
+
 /// Stores engine settings.
 pub struct Settings {
     window_title: String,
     initial_window_size: PhysicalSize,
     time_per_update: Duration,
+    max_updates_per_frame: u32,
+    max_frame_rate: Option<u32>,
     log_fps_frequency: Duration,
+    max_lights: usize,
+    shadow_map_resolution: u32,
 }
 
 impl Settings {
@@ -16,7 +22,11 @@ impl Settings {
             window_title: window_title.into(),
             initial_window_size: PhysicalSize::new(scr_width, scr_height),
             time_per_update: Duration::from_millis(16),
+            max_updates_per_frame: 5,
+            max_frame_rate: None,
             log_fps_frequency: Duration::from_secs(5),
+            max_lights: MAX_LIGHTS,
+            shadow_map_resolution: 2048,
         }
     }
 
@@ -40,6 +50,27 @@ impl Settings {
         self.time_per_update
     }
 
+    /// Sets maximum number of game updates run before rendering a frame. When the game falls further behind,
+    /// remaining time is skipped, so slow updates don't make every next frame slower. Value is clamped to at least 1.
+    pub fn set_max_updates_per_frame(&mut self, value: u32) {
+        self.max_updates_per_frame = value.max(1);
+    }
+
+    /// Returns maximum number of game updates run before rendering a frame.
+    pub fn max_updates_per_frame(&self) -> u32 {
+        self.max_updates_per_frame
+    }
+
+    /// Sets maximum number of frames rendered per second. None or 0 renders frames as fast as possible.
+    pub fn set_max_frame_rate(&mut self, value: Option<u32>) {
+        self.max_frame_rate = value.filter(|fps| *fps > 0);
+    }
+
+    /// Returns maximum number of frames rendered per second.
+    pub fn max_frame_rate(&self) -> Option<u32> {
+        self.max_frame_rate
+    }
+
     /// Sets duration between game fps logs.
     pub fn set_log_fps_frequency(&mut self, value: Duration) {
         self.log_fps_frequency = value;
@@ -49,4 +80,51 @@ impl Settings {
     pub fn log_fps_frequency(&self) -> Duration {
         self.log_fps_frequency
     }
-}
\ No newline at end of file
+
+    /// Sets maximum number of lights uploaded to shaders. Lights above this count are ignored while rendering.
+    /// Value can't be greater than the number of lights supported by shaders.
+    pub fn set_max_lights(&mut self, value: usize) {
+        self.max_lights = value.min(MAX_LIGHTS);
+    }
+
+    /// Returns maximum number of lights uploaded to shaders.
+    pub fn max_lights(&self) -> usize {
+        self.max_lights
+    }
+
+    /// Sets width and height of the shadow map in texels. Value is clamped to at least 1.
+    pub fn set_shadow_map_resolution(&mut self, value: u32) {
+        self.shadow_map_resolution = value.max(1);
+    }
+
+    /// Returns width and height of the shadow map in texels.
+    pub fn shadow_map_resolution(&self) -> u32 {
+        self.shadow_map_resolution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_updates_per_frame_is_at_least_one() {
+        let mut settings = Settings::new("test", 600.0, 400.0);
+
+        settings.set_max_updates_per_frame(0);
+        assert_eq!(1, settings.max_updates_per_frame());
+        settings.set_max_updates_per_frame(8);
+        assert_eq!(8, settings.max_updates_per_frame());
+    }
+
+    #[test]
+    fn zero_max_frame_rate_disables_frame_rate_cap() {
+        let mut settings = Settings::new("test", 600.0, 400.0);
+        assert_eq!(None, settings.max_frame_rate());
+
+        settings.set_max_frame_rate(Some(60));
+        assert_eq!(Some(60), settings.max_frame_rate());
+        settings.set_max_frame_rate(Some(0));
+        assert_eq!(None, settings.max_frame_rate());
+    }
+}
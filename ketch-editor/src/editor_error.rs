@@ -2,6 +2,7 @@ use std::error::Error;
 
 use conrod_core::text::font;
 use conrod_vulkano::RendererCreationError;
+use ketch_core::resource::resource_error::SceneFileError;
 
 use quick_error::quick_error; 
 
@@ -23,3 +24,26 @@ quick_error! {
         }
     }
 }
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum EditorEventError {
+        SceneFileError(err: SceneFileError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        NoActiveSceneError {
+            display("NoActiveSceneError: there is no active scene")
+        }
+        NoObjectError(id: u32) {
+            display("NoObjectError: there is no object with id {} in active scene", id)
+        }
+        MissingMeshError(name: String) {
+            display("MissingMeshError: there is no mesh named {} in asset manager", name)
+        }
+        MissingTextureError(name: String) {
+            display("MissingTextureError: there is no texture named {} in asset manager", name)
+        }
+    }
+}
@@ -1,27 +1,367 @@
-use crate::editor_event::EditorEvent::LightPositionChanged;
+use crate::editor_event::EditorEvent::{LightPositionChanged, SaveScene, OpenScene};
+use crate::editor_event::EditorEvent::{ObjectNameChanged, ObjectPositionChanged, ObjectRotationChanged, ObjectScaleChanged};
+use crate::editor_event::EditorEvent::{ObjectLightSourceChanged, ObjectMeshChanged, ObjectTextureChanged};
+use crate::editor_event::EditorEvent::{ObjectDuplicated, ObjectRemoved, ObjectsRestored, ActiveSceneChanged};
+use crate::editor_error::EditorEventError;
 use ketch_core::resource::AssetManager;
+use ketch_core::resource::object::Object;
+use ketch_core::resource::scene::Scene;
 use crate::Editor;
 use conrod_core::widget::id::Id;
 
+use std::path::PathBuf;
+
 pub enum EditorEvent {
     LightPositionChanged((f32, f32, f32)),
+    SaveScene(PathBuf),
+    OpenScene(PathBuf),
+    ObjectNameChanged(u32, String),
+    ObjectPositionChanged(u32, (f32, f32, f32)),
+    ObjectRotationChanged(u32, (f32, f32, f32)),
+    ObjectScaleChanged(u32, (f32, f32, f32)),
+    ObjectLightSourceChanged(u32, bool),
+    ObjectMeshChanged(u32, String),
+    /// Sets texture replacing mesh texture of the object, None makes the object use texture of its mesh again.
+    ObjectTextureChanged(u32, Option<String>),
+    ObjectDuplicated(u32),
+    ObjectRemoved(u32),
+    /// Adds back objects removed from the active scene, parents have to be before their children.
+    ObjectsRestored(Vec<Object>),
+    ActiveSceneChanged(String),
 }
 
 impl EditorEvent {
-    pub fn execute(self, asset_manager: &mut AssetManager) {
-        match self {
+    /// Executes event and returns event which undoes it.
+    /// Nothing is returned for events which can't be undone, like saving and opening scenes.
+    pub fn execute(self, asset_manager: &mut AssetManager) -> Result<Option<EditorEvent>, EditorEventError> {
+        let undo_event = match self {
             LightPositionChanged((x, y, z)) => EditorEvent::handle_light_position_changed(x, y, z, asset_manager),
+            SaveScene(path) => EditorEvent::handle_save_scene(path, asset_manager)?,
+            OpenScene(path) => EditorEvent::handle_open_scene(path, asset_manager)?,
+            ObjectMeshChanged(id, name) => EditorEvent::handle_object_mesh_changed(id, name, asset_manager)?,
+            ObjectTextureChanged(id, name) => EditorEvent::handle_object_texture_changed(id, name, asset_manager)?,
+            ActiveSceneChanged(name) => EditorEvent::handle_active_scene_changed(name, asset_manager),
+            event => {
+                let scene = asset_manager.active_scene_mut().ok_or(EditorEventError::NoActiveSceneError)?;
+                event.execute_in_scene(scene)?
+            },
+        };
+        Ok(undo_event)
+    }
+
+    /// Executes event which changes only objects of the given scene and returns event which undoes it.
+    /// Events which need assets or other scenes are executed only by `execute`.
+    fn execute_in_scene(self, scene: &mut Scene) -> Result<Option<EditorEvent>, EditorEventError> {
+        let undo_event = match self {
+            ObjectNameChanged(id, name) => EditorEvent::change_object(id, scene, |object| {
+                let undo_event = ObjectNameChanged(id, object.name().to_string());
+                object.set_name(name);
+                undo_event
+            })?,
+            ObjectPositionChanged(id, (x, y, z)) => EditorEvent::change_object(id, scene, |object| {
+                let undo_event = ObjectPositionChanged(id, object.position());
+                object.set_position(x, y, z);
+                undo_event
+            })?,
+            ObjectRotationChanged(id, (x, y, z)) => EditorEvent::change_object(id, scene, |object| {
+                let undo_event = ObjectRotationChanged(id, object.rotation_angles());
+                object.set_rotation_angles(x, y, z);
+                undo_event
+            })?,
+            ObjectScaleChanged(id, (x, y, z)) => EditorEvent::change_object(id, scene, |object| {
+                let undo_event = ObjectScaleChanged(id, object.scale());
+                object.set_scale_xyz(x, y, z);
+                undo_event
+            })?,
+            ObjectLightSourceChanged(id, value) => EditorEvent::change_object(id, scene, |object| {
+                let undo_event = ObjectLightSourceChanged(id, object.light_source());
+                object.set_light_source(value);
+                undo_event
+            })?,
+            ObjectDuplicated(id) => EditorEvent::handle_object_duplicated(id, scene)?,
+            ObjectRemoved(id) => EditorEvent::handle_object_removed(id, scene)?,
+            ObjectsRestored(objects) => EditorEvent::handle_objects_restored(objects, scene),
+            // these events need asset manager, they are executed by `execute` and never get here
+            LightPositionChanged(_) | SaveScene(_) | OpenScene(_) | ObjectMeshChanged(_, _) | ObjectTextureChanged(_, _) | ActiveSceneChanged(_) => None,
+        };
+        Ok(undo_event)
+    }
+
+    /// Returns true if after executing this event the active scene should be treated as unchanged,
+    /// because it was saved or opened.
+    pub fn syncs_scene_file(&self) -> bool {
+        match self {
+            SaveScene(_) | OpenScene(_) => true,
+            _ => false,
         }
     }
 
-    fn handle_light_position_changed(x: f32, y: f32, z: f32, asset_manager: &mut AssetManager) {
-        if let Some(scene) = asset_manager.active_scene_mut() { 
+    /// Returns true if objects or scenes shown in the editor change after executing this event.
+    pub fn syncs_editor(&self) -> bool {
+        match self {
+            ObjectNameChanged(_, _) | ObjectDuplicated(_) | ObjectRemoved(_) | ObjectsRestored(_) | ActiveSceneChanged(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if events recorded before this one can't be undone after executing it.
+    /// Recorded events refer to objects of the active scene, so they are forgotten when it's replaced.
+    pub fn clears_history(&self) -> bool {
+        match self {
+            OpenScene(_) | ActiveSceneChanged(_) => true,
+            _ => false,
+        }
+    }
+
+    fn handle_light_position_changed(x: f32, y: f32, z: f32, asset_manager: &mut AssetManager) -> Option<EditorEvent> {
+        if let Some(scene) = asset_manager.active_scene_mut() {
+            let undo_event = LightPositionChanged(scene.light_position());
             scene.set_light_position(x, y, z);
             if let Some(light_object) = scene.objects_mut().iter_mut().find(|x| x.light_source()) {
                 light_object.set_position(x, y, z);
             }
+            Some(undo_event)
+        } else {
+            None
+        }
+    }
+
+    fn handle_save_scene(path: PathBuf, asset_manager: &mut AssetManager) -> Result<Option<EditorEvent>, EditorEventError> {
+        let scene = asset_manager.active_scene().ok_or(EditorEventError::NoActiveSceneError)?;
+        asset_manager.save_scene(scene, path)?;
+        Ok(None)
+    }
+
+    fn handle_open_scene(path: PathBuf, asset_manager: &mut AssetManager) -> Result<Option<EditorEvent>, EditorEventError> {
+        let scene = asset_manager.load_scene(path)?;
+        asset_manager.set_active_scene(scene);
+        Ok(None)
+    }
+
+    /// Applies change to the object with given id from the scene and returns event which undoes it.
+    fn change_object<F>(id: u32, scene: &mut Scene, change: F) -> Result<Option<EditorEvent>, EditorEventError>
+        where F: FnOnce(&mut Object) -> EditorEvent
+    {
+        let object = scene.object_mut(id).ok_or(EditorEventError::NoObjectError(id))?;
+        Ok(Some(change(object)))
+    }
+
+    fn handle_object_mesh_changed(id: u32, name: String, asset_manager: &mut AssetManager) -> Result<Option<EditorEvent>, EditorEventError> {
+        let mesh = asset_manager.mesh(&name).ok_or(EditorEventError::MissingMeshError(name))?;
+        let scene = asset_manager.active_scene_mut().ok_or(EditorEventError::NoActiveSceneError)?;
+        let object = scene.object_mut(id).ok_or(EditorEventError::NoObjectError(id))?;
+        // mesh can't be taken away from an object, so giving mesh to an object without one can't be undone
+        let undo_event = object.mesh().map(|old_mesh| ObjectMeshChanged(id, old_mesh.read().unwrap().name().to_string()));
+        object.set_mesh(mesh);
+        Ok(undo_event)
+    }
+
+    /// Changes texture of the object only, objects sharing its mesh keep their textures, same as in scene files.
+    fn handle_object_texture_changed(id: u32, name: Option<String>, asset_manager: &mut AssetManager) -> Result<Option<EditorEvent>, EditorEventError> {
+        let texture = match name {
+            Some(name) => Some(asset_manager.texture(&name).ok_or(EditorEventError::MissingTextureError(name))?),
+            None => None,
+        };
+        let scene = asset_manager.active_scene_mut().ok_or(EditorEventError::NoActiveSceneError)?;
+        let object = scene.object_mut(id).ok_or(EditorEventError::NoObjectError(id))?;
+
+        let undo_event = ObjectTextureChanged(id, object.texture().map(|texture| texture.name().to_string()));
+        object.set_texture(texture);
+        Ok(Some(undo_event))
+    }
+
+    /// Adds copy of the object with a new id next to the original. Children of the object aren't copied.
+    fn handle_object_duplicated(id: u32, scene: &mut Scene) -> Result<Option<EditorEvent>, EditorEventError> {
+        let duplicate = scene.object(id).ok_or(EditorEventError::NoObjectError(id))?.clone();
+        let undo_event = ObjectRemoved(duplicate.id());
+        scene.add_object(duplicate);
+        Ok(Some(undo_event))
+    }
+
+    fn handle_object_removed(id: u32, scene: &mut Scene) -> Result<Option<EditorEvent>, EditorEventError> {
+        let removed = scene.remove_object_with_descendants(id);
+        if removed.is_empty() {
+            return Err(EditorEventError::NoObjectError(id));
+        }
+        Ok(Some(ObjectsRestored(removed)))
+    }
+
+    fn handle_objects_restored(objects: Vec<Object>, scene: &mut Scene) -> Option<EditorEvent> {
+        let undo_event = objects.first().map(|object| ObjectRemoved(object.id()));
+        for object in objects {
+            scene.add_object(object);
+        }
+        undo_event
+    }
+
+    fn handle_active_scene_changed(name: String, asset_manager: &mut AssetManager) -> Option<EditorEvent> {
+        if !asset_manager.scene_names().contains(&name.as_str()) {
+            return None;
         }
+        let undo_event = asset_manager.active_scene().map(|scene| ActiveSceneChanged(scene.name().to_string()));
+        asset_manager.change_active_scene(&name);
+        undo_event
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::mesh::Vertex;
+    use ketch_core::resource::object::ObjectBuilder;
+    use crate::tests::asset_manager;
+    use crate::unsaved_changes::UnsavedChangesTracker;
 
+    fn triangle_vertices() -> Vec<Vertex> {
+        vec![
+            Vertex { position: [0.0, 0.0, 0.0], normal: [0.0, 0.0, 1.0], tex_coord: [0.0, 0.0] },
+            Vertex { position: [1.0, 0.0, 0.0], normal: [0.0, 0.0, 1.0], tex_coord: [1.0, 0.0] },
+            Vertex { position: [0.0, 1.0, 0.0], normal: [0.0, 0.0, 1.0], tex_coord: [0.0, 1.0] },
+        ]
+    }
+
+    fn parent_and_child_scene() -> (Scene, u32, u32) {
+        let mut scene = Scene::new("test", Camera::new());
+        let parent = ObjectBuilder::new("parent").with_position(1.0, 2.0, 3.0).build();
+        let child = ObjectBuilder::new("child").build();
+        let (parent_id, child_id) = (parent.id(), child.id());
+        scene.add_object(parent);
+        assert!(scene.add_child_object(parent_id, child));
+        (scene, parent_id, child_id)
+    }
+
+    #[test]
+    fn inverse_of_transform_event_restores_transform() {
+        let (mut scene, parent_id, _) = parent_and_child_scene();
+
+        let inverse = ObjectPositionChanged(parent_id, (5.0, 0.0, 0.0)).execute_in_scene(&mut scene).unwrap().unwrap();
+        assert_eq!((5.0, 0.0, 0.0), scene.object(parent_id).unwrap().position());
+        let redo = inverse.execute_in_scene(&mut scene).unwrap().unwrap();
+        assert_eq!((1.0, 2.0, 3.0), scene.object(parent_id).unwrap().position());
+
+        ObjectScaleChanged(parent_id, (2.0, 2.0, 2.0)).execute_in_scene(&mut scene).unwrap().unwrap()
+                                                      .execute_in_scene(&mut scene).unwrap();
+        assert_eq!((1.0, 1.0, 1.0), scene.object(parent_id).unwrap().scale());
+
+        redo.execute_in_scene(&mut scene).unwrap();
+        assert_eq!((5.0, 0.0, 0.0), scene.object(parent_id).unwrap().position());
+    }
+
+    #[test]
+    fn inverse_of_inspector_events_restores_object() {
+        let (mut scene, parent_id, _) = parent_and_child_scene();
+
+        let inverse_events = vec![
+            ObjectNameChanged(parent_id, "renamed".to_string()).execute_in_scene(&mut scene).unwrap().unwrap(),
+            ObjectRotationChanged(parent_id, (0.5, 0.0, 0.0)).execute_in_scene(&mut scene).unwrap().unwrap(),
+            ObjectLightSourceChanged(parent_id, true).execute_in_scene(&mut scene).unwrap().unwrap(),
+        ];
+        let parent = scene.object(parent_id).unwrap();
+        assert_eq!("renamed", parent.name());
+        assert_eq!((0.5, 0.0, 0.0), parent.rotation_angles());
+        assert!(parent.light_source());
+
+        for inverse in inverse_events {
+            inverse.execute_in_scene(&mut scene).unwrap();
+        }
+        let parent = scene.object(parent_id).unwrap();
+        assert_eq!("parent", parent.name());
+        assert_eq!((0.0, 0.0, 0.0), parent.rotation_angles());
+        assert!(!parent.light_source());
+    }
+
+    #[test]
+    fn changing_texture_of_object_keeps_textures_of_objects_sharing_its_mesh() {
+        let mut asset_manager = asset_manager();
+        let mesh = asset_manager.create_mesh("triangle", triangle_vertices(), vec![0, 1, 2]);
+        let mesh_texture = mesh.read().unwrap().texture().name().to_string();
+        let texture = asset_manager.load_texture("other", concat!(env!("CARGO_MANIFEST_DIR"), "/../ketch-core/data/default.png"));
+        asset_manager.add_texture(texture);
+
+        let mut scene = Scene::new("test", Camera::new());
+        let first = ObjectBuilder::new("first").with_mesh(mesh.clone()).build();
+        let second = ObjectBuilder::new("second").with_mesh(mesh.clone()).build();
+        let (first_id, second_id) = (first.id(), second.id());
+        scene.add_object(first);
+        scene.add_object(second);
+        asset_manager.set_active_scene(scene);
+
+        let inverse = ObjectTextureChanged(first_id, Some("other".to_string())).execute(&mut asset_manager).unwrap().unwrap();
+        let scene = asset_manager.active_scene().unwrap();
+        assert_eq!("other", scene.object(first_id).unwrap().texture().unwrap().name());
+        assert!(scene.object(second_id).unwrap().texture().is_none());
+        assert_eq!(mesh_texture, mesh.read().unwrap().texture().name());
+
+        inverse.execute(&mut asset_manager).unwrap();
+        assert!(asset_manager.active_scene().unwrap().object(first_id).unwrap().texture().is_none());
+        assert!(ObjectTextureChanged(first_id, Some("missing".to_string())).execute(&mut asset_manager).is_err());
+    }
+
+    #[test]
+    fn inverse_of_removal_restores_object_with_children() {
+        let (mut scene, parent_id, child_id) = parent_and_child_scene();
+
+        let inverse = ObjectRemoved(parent_id).execute_in_scene(&mut scene).unwrap().unwrap();
+        assert!(scene.objects().is_empty());
+        let redo = inverse.execute_in_scene(&mut scene).unwrap().unwrap();
+        assert_eq!(Some(parent_id), scene.object(child_id).unwrap().parent());
+
+        redo.execute_in_scene(&mut scene).unwrap();
+        assert!(scene.objects().is_empty());
+    }
+
+    #[test]
+    fn inverse_of_duplication_removes_added_object() {
+        let (mut scene, parent_id, _) = parent_and_child_scene();
+
+        let inverse = ObjectDuplicated(parent_id).execute_in_scene(&mut scene).unwrap().unwrap();
+        assert_eq!(3, scene.objects().len());
+        inverse.execute_in_scene(&mut scene).unwrap();
+        assert_eq!(2, scene.objects().len());
+        assert!(scene.object(parent_id).is_some());
+    }
+
+    #[test]
+    fn changing_object_missing_from_scene_fails() {
+        let (mut scene, parent_id, _) = parent_and_child_scene();
+        ObjectRemoved(parent_id).execute_in_scene(&mut scene).unwrap();
+
+        assert!(ObjectPositionChanged(parent_id, (0.0, 0.0, 0.0)).execute_in_scene(&mut scene).is_err());
+    }
+
+    #[test]
+    fn switching_active_scene_keeps_unsaved_changes_of_previous_scene() {
+        let mut asset_manager = asset_manager();
+        let (first_scene, parent_id, _) = parent_and_child_scene();
+        asset_manager.add_scene(Scene::new("second", Camera::new()));
+        asset_manager.set_active_scene(first_scene);
+        let mut tracker = UnsavedChangesTracker::new();
+        assert!(!tracker.check(asset_manager.active_scene()));
+
+        // editor marks scene as changed after every executed event, unless the event syncs scene file
+        ObjectPositionChanged(parent_id, (5.0, 0.0, 0.0)).execute(&mut asset_manager).unwrap();
+        tracker.mark_changed();
+        assert!(tracker.check(asset_manager.active_scene()));
+
+        let event = ActiveSceneChanged("second".to_string());
+        assert!(!event.syncs_scene_file());
+        let switch_back = event.execute(&mut asset_manager).unwrap().unwrap();
+        tracker.mark_changed();
+        assert_eq!("second", asset_manager.active_scene().unwrap().name());
+        assert!(!tracker.check(asset_manager.active_scene()));
+
+        switch_back.execute(&mut asset_manager).unwrap();
+        tracker.mark_changed();
+        assert_eq!("test", asset_manager.active_scene().unwrap().name());
+        assert!(tracker.check(asset_manager.active_scene()));
+    }
+
+    #[test]
+    fn switching_scene_clears_history() {
+        // undo events of the previous scene refer to objects which aren't in the new active scene
+        assert!(ActiveSceneChanged("other".to_string()).clears_history());
+        assert!(OpenScene(PathBuf::from("scene.ron")).clears_history());
+        assert!(!ObjectRemoved(0).clears_history());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::editor_event::EditorEvent;
+
+use std::collections::VecDeque;
+
+/// Maximum number of events which can be undone, the oldest ones are forgotten first.
+pub const MAX_HISTORY_LENGTH: usize = 100;
+
+/// Stores events which undo and redo changes made in the editor.
+pub struct EditorHistory {
+    undo_events: VecDeque<EditorEvent>,
+    redo_events: Vec<EditorEvent>,
+    max_length: usize,
+}
+
+impl EditorHistory {
+    pub fn new(max_length: usize) -> Self {
+        EditorHistory {
+            undo_events: VecDeque::new(),
+            redo_events: Vec::new(),
+            max_length,
+        }
+    }
+
+    /// Records event undoing a new change, changes which were undone can't be redone after that.
+    pub fn record(&mut self, undo_event: EditorEvent) {
+        self.redo_events.clear();
+        self.push_undo(undo_event);
+    }
+
+    /// Pushes event undoing a change without clearing redo events, used when redoing.
+    pub fn push_undo(&mut self, undo_event: EditorEvent) {
+        self.undo_events.push_back(undo_event);
+        while self.undo_events.len() > self.max_length {
+            self.undo_events.pop_front();
+        }
+    }
+
+    pub fn push_redo(&mut self, redo_event: EditorEvent) {
+        self.redo_events.push(redo_event);
+    }
+
+    pub fn pop_undo(&mut self) -> Option<EditorEvent> {
+        self.undo_events.pop_back()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<EditorEvent> {
+        self.redo_events.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_events.clear();
+        self.redo_events.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light_position(event: Option<EditorEvent>) -> Option<(f32, f32, f32)> {
+        match event {
+            Some(EditorEvent::LightPositionChanged(position)) => Some(position),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn history_forgets_oldest_events() {
+        let mut history = EditorHistory::new(2);
+        history.record(EditorEvent::LightPositionChanged((1.0, 0.0, 0.0)));
+        history.record(EditorEvent::LightPositionChanged((2.0, 0.0, 0.0)));
+        history.record(EditorEvent::LightPositionChanged((3.0, 0.0, 0.0)));
+
+        assert_eq!(light_position(history.pop_undo()), Some((3.0, 0.0, 0.0)));
+        assert_eq!(light_position(history.pop_undo()), Some((2.0, 0.0, 0.0)));
+        assert!(history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn recording_clears_redo_events() {
+        let mut history = EditorHistory::new(MAX_HISTORY_LENGTH);
+        history.push_redo(EditorEvent::LightPositionChanged((1.0, 0.0, 0.0)));
+        history.push_undo(EditorEvent::LightPositionChanged((2.0, 0.0, 0.0)));
+        history.record(EditorEvent::LightPositionChanged((3.0, 0.0, 0.0)));
+
+        assert!(history.pop_redo().is_none());
+        assert_eq!(light_position(history.pop_undo()), Some((3.0, 0.0, 0.0)));
+        assert_eq!(light_position(history.pop_undo()), Some((2.0, 0.0, 0.0)));
+    }
+}
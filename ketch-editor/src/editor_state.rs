@@ -1,48 +1,106 @@
 use ketch_core::settings::Settings;
+use crate::gizmo::GizmoSettings;
+use ketch_core::input::input_map::{InputMap, Binding};
+use ketch_core::input::input_state::InputState;
+use ketch_core::input::input_event::VirtualKeyCode;
+
+const DEFAULT_SCENE_PATH: &str = "scene.ron";
 
 #[derive(Clone)]
 pub struct EditorState {
     pub run_game: bool,
+    pub run_game_requested: bool,
+    pub keep_play_changes: bool,
     pub x_light_text_box_content: String,
     pub y_light_text_box_content: String,
     pub z_light_text_box_content: String,
+    pub scene_path_text_box_content: String,
+    pub unsaved_changes: bool,
+    pub discard_changes_warning: bool,
+    pub selected_object: Option<u32>,
+    pub object_name_text_box_content: String,
+    pub object_position_text_box_contents: [String; 3],
+    pub object_rotation_text_box_contents: [String; 3],
+    pub object_scale_text_box_contents: [String; 3],
+    pub object_light_source: bool,
+    pub object_mesh_text_box_content: String,
+    pub object_texture_text_box_content: String,
+    pub active_scene_name: Option<String>,
+    pub scene_names: Vec<String>,
+    pub outliner_items: Vec<(u32, String)>,
+    pub gizmo_settings: GizmoSettings,
+    pub gizmo_paths: Vec<Vec<[f64; 2]>>,
 }
 
 impl EditorState {
     pub fn new() -> Self {
         EditorState {
             run_game: false,
+            run_game_requested: false,
+            keep_play_changes: false,
             x_light_text_box_content: String::from("0.0"),
             y_light_text_box_content: String::from("0.0"),
             z_light_text_box_content: String::from("0.0"),
+            scene_path_text_box_content: String::from(DEFAULT_SCENE_PATH),
+            unsaved_changes: false,
+            discard_changes_warning: false,
+            selected_object: None,
+            object_name_text_box_content: String::new(),
+            object_position_text_box_contents: [String::from("0.0"), String::from("0.0"), String::from("0.0")],
+            object_rotation_text_box_contents: [String::from("0.0"), String::from("0.0"), String::from("0.0")],
+            object_scale_text_box_contents: [String::from("1.0"), String::from("1.0"), String::from("1.0")],
+            object_light_source: false,
+            object_mesh_text_box_content: String::new(),
+            object_texture_text_box_content: String::new(),
+            active_scene_name: None,
+            scene_names: Vec::new(),
+            outliner_items: Vec::new(),
+            gizmo_settings: GizmoSettings::new(),
+            gizmo_paths: Vec::new(),
         }
     }
 }
 
 pub struct EditorInputState {
     pub mouse_delta_changed: bool,
+    pub left_mouse_button_pressed: bool,
     pub right_mouse_button_pressed: bool,
     pub camera_speed: f32,
     pub mouse_sensitivity: f32,
     pub mouse_delta: (f32, f32),
-    pub up: bool,
-    pub down: bool,
-    pub left: bool,
-    pub right: bool,
+    pub input_map: InputMap,
+    pub input_state: InputState,
+    pub cursor_position: (f64, f64),
+    pub pick_requested: bool,
+    pub undo_requested: bool,
+    pub redo_requested: bool,
 }
 
 impl EditorInputState {
     pub fn new() -> Self {
         EditorInputState {
             mouse_delta_changed: false,
+            left_mouse_button_pressed: false,
             right_mouse_button_pressed: false,
             camera_speed: 5.0,
             mouse_sensitivity: 0.2,
             mouse_delta: (0.0, 0.0),
-            up: false,
-            down: false,
-            left: false,
-            right: false,
+            input_map: camera_input_map(),
+            input_state: InputState::new(),
+            cursor_position: (0.0, 0.0),
+            pick_requested: false,
+            undo_requested: false,
+            redo_requested: false,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Returns bindings of keys moving the editor camera.
+fn camera_input_map() -> InputMap {
+    let mut input_map = InputMap::new();
+    input_map.bind_action("move_forward", Binding::Key(VirtualKeyCode::W));
+    input_map.bind_action("move_backward", Binding::Key(VirtualKeyCode::S));
+    input_map.bind_action("move_left", Binding::Key(VirtualKeyCode::A));
+    input_map.bind_action("move_right", Binding::Key(VirtualKeyCode::D));
+    input_map
+}
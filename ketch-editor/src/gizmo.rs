@@ -0,0 +1,330 @@
+use crate::editor_event::EditorEvent;
+use ketch_core::resource::scene::Scene;
+use ketch_core::resource::bounds::Ray;
+use ketch_core::resource::object::quat_to_rotation_angles;
+use nalgebra_glm::{U3, Mat4, Qua, Vec3, Vec4};
+use nalgebra_glm as glm;
+
+/// Length of handles relative to the distance between the camera and the object,
+/// so handles have the same size on the window no matter how far the object is.
+const HANDLE_LENGTH_FACTOR: f32 = 0.2;
+/// Maximum distance in physical pixels between the cursor and a handle which can still be grabbed.
+const HANDLE_GRAB_DISTANCE: f32 = 8.0;
+const ROTATION_HANDLE_SEGMENTS: usize = 32;
+const MIN_SCALE: f32 = 0.01;
+
+pub const TRANSLATION_SNAP_STEP: f32 = 0.5;
+pub const ROTATION_SNAP_STEP: f32 = std::f32::consts::PI / 12.0;
+pub const SCALE_SNAP_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoSettings {
+    pub mode: GizmoMode,
+    /// Moves and rotates object along its own axes instead of world axes. Scale always uses object axes.
+    pub local_space: bool,
+    /// Snaps position to grid, rotation to 15 degree steps and scale to 0.1 steps.
+    pub snapping: bool,
+}
+
+impl GizmoSettings {
+    pub fn new() -> Self {
+        GizmoSettings {
+            mode: GizmoMode::Translate,
+            local_space: false,
+            snapping: false,
+        }
+    }
+}
+
+/// Handles drawn over the selected object, which change its transform when dragged along x, y or z axis.
+pub struct Gizmo {
+    drag: Option<GizmoDrag>,
+}
+
+/// State of the object and gizmo from the moment the handle was grabbed.
+struct GizmoDrag {
+    object: u32,
+    axis: usize,
+    settings: GizmoSettings,
+    center: Vec3,
+    axis_direction: Vec3,
+    handle_length: f32,
+    start_value: f32,
+    last_value: f32,
+    position: Vec3,
+    rotation: Qua<f32>,
+    scale: Vec3,
+    parent_inverse: Mat4,
+}
+
+impl Gizmo {
+    pub fn new() -> Self {
+        Gizmo {
+            drag: None,
+        }
+    }
+
+    pub fn dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Returns handles of the object as lines on the window in physical pixels, one for each axis.
+    /// Parts of handles behind the camera are skipped.
+    pub fn handle_paths(scene: &Scene, id: u32, settings: &GizmoSettings, window_dimensions: (f32, f32)) -> Vec<Vec<(f32, f32)>> {
+        let (center, axes, handle_length) = match gizmo_frame(scene, id, settings) {
+            Some(frame) => frame,
+            None => return Vec::new(),
+        };
+        let (width, height) = window_dimensions;
+
+        axes.iter().map(|axis| {
+            let points = match settings.mode {
+                GizmoMode::Translate | GizmoMode::Scale => vec![center, center + axis * handle_length],
+                GizmoMode::Rotate => {
+                    let (u, v) = perpendicular_basis(axis);
+                    (0 ..= ROTATION_HANDLE_SEGMENTS).map(|segment| {
+                        let angle = 2.0 * std::f32::consts::PI * segment as f32 / ROTATION_HANDLE_SEGMENTS as f32;
+                        center + (u * angle.cos() + v * angle.sin()) * handle_length
+                    }).collect()
+                },
+            };
+            points.into_iter()
+                  .filter_map(|point| scene.camera().world_to_screen((point.x, point.y, point.z), width, height))
+                  .collect()
+        }).collect()
+    }
+
+    /// Grabs the handle under the cursor. Returns false if there is no handle under the cursor.
+    pub fn start_drag(&mut self, scene: &Scene, id: u32, settings: &GizmoSettings,
+                      cursor_position: (f32, f32), window_dimensions: (f32, f32)) -> bool {
+        let paths = Gizmo::handle_paths(scene, id, settings, window_dimensions);
+        let axis = match grabbed_axis(&paths, cursor_position) {
+            Some(axis) => axis,
+            None => return false,
+        };
+        let (object, (center, axes, handle_length)) = match (scene.object(id), gizmo_frame(scene, id, settings)) {
+            (Some(object), Some(frame)) => (object, frame),
+            _ => return false,
+        };
+        let parent_matrix = object.parent().and_then(|parent| scene.world_matrix(parent)).unwrap_or_else(Mat4::identity);
+        let (x, y, z) = object.position();
+        let (scale_x, scale_y, scale_z) = object.scale();
+
+        let mut drag = GizmoDrag {
+            object: id,
+            axis,
+            settings: *settings,
+            center,
+            axis_direction: axes[axis],
+            handle_length,
+            start_value: 0.0,
+            last_value: 0.0,
+            position: Vec3::new(x, y, z),
+            rotation: object.rotation(),
+            scale: Vec3::new(scale_x, scale_y, scale_z),
+            parent_inverse: parent_matrix.try_inverse().unwrap_or_else(Mat4::identity),
+        };
+
+        let (cursor_x, cursor_y) = cursor_position;
+        let ray = scene.camera().screen_ray(cursor_x, cursor_y, window_dimensions.0, window_dimensions.1);
+        match drag.value(&ray) {
+            Some(value) => {
+                drag.start_value = value;
+                drag.last_value = value;
+                self.drag = Some(drag);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns event changing transform of the dragged object to follow the cursor ray.
+    /// Nothing is returned when the cursor didn't move along the dragged axis.
+    pub fn drag(&mut self, ray: &Ray) -> Option<EditorEvent> {
+        let drag = self.drag.as_mut()?;
+        let value = drag.value(ray)?;
+        if value == drag.last_value {
+            return None;
+        }
+        drag.last_value = value;
+        Some(drag.transform_event(value - drag.start_value))
+    }
+
+    pub fn stop_drag(&mut self) {
+        self.drag = None;
+    }
+}
+
+impl GizmoDrag {
+    /// Returns position of the cursor along the dragged axis in world units, or angle around it in radians.
+    fn value(&self, ray: &Ray) -> Option<f32> {
+        match self.settings.mode {
+            GizmoMode::Translate | GizmoMode::Scale => ray.closest_point_on_line(&self.center, &self.axis_direction),
+            GizmoMode::Rotate => {
+                let (x, y, z) = ray.point_at(ray.intersects_plane(&self.center, &self.axis_direction)?);
+                let offset = Vec3::new(x, y, z) - self.center;
+                let (u, v) = perpendicular_basis(&self.axis_direction);
+                Some(glm::dot(&offset, &v).atan2(glm::dot(&offset, &u)))
+            },
+        }
+    }
+
+    fn transform_event(&self, delta: f32) -> EditorEvent {
+        match self.settings.mode {
+            GizmoMode::Translate => {
+                let delta = match (self.settings.snapping, self.settings.local_space) {
+                    // world axes are grid lines, so position itself is snapped
+                    (true, false) => snap(self.center[self.axis] + delta, TRANSLATION_SNAP_STEP) - self.center[self.axis],
+                    (true, true) => snap(delta, TRANSLATION_SNAP_STEP),
+                    (false, _) => delta,
+                };
+                let world_delta = self.axis_direction * delta;
+                let position = self.position + (self.parent_inverse * Vec4::new(world_delta.x, world_delta.y, world_delta.z, 0.0)).xyz();
+                EditorEvent::ObjectPositionChanged(self.object, (position.x, position.y, position.z))
+            },
+            GizmoMode::Rotate => {
+                // angle wraps around when the cursor goes around the center
+                let mut angle = delta;
+                if angle > std::f32::consts::PI {
+                    angle -= 2.0 * std::f32::consts::PI;
+                } else if angle < -std::f32::consts::PI {
+                    angle += 2.0 * std::f32::consts::PI;
+                }
+                if self.settings.snapping {
+                    angle = snap(angle, ROTATION_SNAP_STEP);
+                }
+                let axis = (self.parent_inverse * Vec4::new(self.axis_direction.x, self.axis_direction.y, self.axis_direction.z, 0.0)).xyz();
+                let rotation = glm::quat_angle_axis(angle, &glm::normalize(&axis)) * self.rotation;
+                EditorEvent::ObjectRotationChanged(self.object, quat_to_rotation_angles(&rotation))
+            },
+            GizmoMode::Scale => {
+                let mut scale = self.scale;
+                scale[self.axis] *= 1.0 + delta / self.handle_length;
+                if self.settings.snapping {
+                    scale[self.axis] = snap(scale[self.axis], SCALE_SNAP_STEP);
+                }
+                scale[self.axis] = scale[self.axis].max(MIN_SCALE);
+                EditorEvent::ObjectScaleChanged(self.object, (scale.x, scale.y, scale.z))
+            },
+        }
+    }
+}
+
+/// Returns world position of the object, directions of gizmo axes in world space and length of handles.
+fn gizmo_frame(scene: &Scene, id: u32, settings: &GizmoSettings) -> Option<(Vec3, [Vec3; 3], f32)> {
+    let world_matrix = scene.world_matrix(id)?;
+    let center = (world_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+
+    let world_axes = [Vec3::x(), Vec3::y(), Vec3::z()];
+    let mut axes = world_axes;
+    if settings.local_space || settings.mode == GizmoMode::Scale {
+        for (axis, world_axis) in axes.iter_mut().zip(world_axes.iter()) {
+            let local_axis = (world_matrix * Vec4::new(world_axis.x, world_axis.y, world_axis.z, 0.0)).xyz();
+            // objects scaled to zero keep world axes
+            if local_axis.norm() > 0.0 {
+                *axis = glm::normalize(&local_axis);
+            }
+        }
+    }
+
+    let handle_length = glm::distance(&scene.camera().position_vec3(), &center) * HANDLE_LENGTH_FACTOR;
+    Some((center, axes, handle_length))
+}
+
+/// Returns two unit vectors perpendicular to the axis and to each other.
+fn perpendicular_basis(axis: &Vec3) -> (Vec3, Vec3) {
+    let other = if axis.x.abs() < 0.9 { Vec3::x() } else { Vec3::y() };
+    let u = glm::normalize(&glm::cross::<f32, U3>(axis, &other));
+    let v = glm::cross::<f32, U3>(axis, &u);
+    (u, v)
+}
+
+/// Returns index of the handle nearest to the cursor, if it's close enough to be grabbed.
+fn grabbed_axis(paths: &[Vec<(f32, f32)>], cursor_position: (f32, f32)) -> Option<usize> {
+    paths.iter().enumerate()
+         .flat_map(|(axis, path)| path.windows(2).map(move |segment| (axis, distance_to_segment(cursor_position, segment[0], segment[1]))))
+         .filter(|(_, distance)| *distance <= HANDLE_GRAB_DISTANCE)
+         .fold(None, |nearest: Option<(usize, f32)>, (axis, distance)| match nearest {
+             Some((_, nearest_distance)) if nearest_distance <= distance => nearest,
+             _ => Some((axis, distance)),
+         })
+         .map(|(axis, _)| axis)
+}
+
+fn distance_to_segment(point: (f32, f32), start: (f32, f32), end: (f32, f32)) -> f32 {
+    let (segment_x, segment_y) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = segment_x * segment_x + segment_y * segment_y;
+    let t = if length_squared > 0.0 {
+        (((point.0 - start.0) * segment_x + (point.1 - start.1) * segment_y) / length_squared).max(0.0).min(1.0)
+    } else {
+        0.0
+    };
+    let (closest_x, closest_y) = (start.0 + t * segment_x, start.1 + t * segment_y);
+    ((point.0 - closest_x).powi(2) + (point.1 - closest_y).powi(2)).sqrt()
+}
+
+fn snap(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+
+    const WINDOW_DIMENSIONS: (f32, f32) = (800.0, 600.0);
+
+    fn test_scene() -> (Scene, u32) {
+        let mut scene = Scene::new("test", Camera::new());
+        let object = ObjectBuilder::new("object").build();
+        let id = object.id();
+        scene.add_object(object);
+        (scene, id)
+    }
+
+    fn drag_x_handle(settings: &GizmoSettings, to: f32) -> Option<EditorEvent> {
+        let (scene, id) = test_scene();
+        let mut gizmo = Gizmo::new();
+        let grab = scene.camera().world_to_screen((0.3, 0.0, 0.0), WINDOW_DIMENSIONS.0, WINDOW_DIMENSIONS.1).unwrap();
+        assert!(gizmo.start_drag(&scene, id, settings, grab, WINDOW_DIMENSIONS));
+
+        let (x, y) = scene.camera().world_to_screen((to, 0.0, 0.0), WINDOW_DIMENSIONS.0, WINDOW_DIMENSIONS.1).unwrap();
+        gizmo.drag(&scene.camera().screen_ray(x, y, WINDOW_DIMENSIONS.0, WINDOW_DIMENSIONS.1))
+    }
+
+    #[test]
+    fn dragging_translate_handle_moves_object_along_axis() {
+        match drag_x_handle(&GizmoSettings::new(), 1.0) {
+            Some(EditorEvent::ObjectPositionChanged(_, (x, y, z))) => {
+                assert!((x - 0.7).abs() < 0.001 && y.abs() < 0.001 && z.abs() < 0.001);
+            },
+            _ => panic!("dragging translate handle should move object"),
+        }
+    }
+
+    #[test]
+    fn snapping_rounds_position_to_grid() {
+        let settings = GizmoSettings { snapping: true, ..GizmoSettings::new() };
+        match drag_x_handle(&settings, 1.0) {
+            Some(EditorEvent::ObjectPositionChanged(_, (x, _, _))) => assert_eq!(TRANSLATION_SNAP_STEP, x),
+            _ => panic!("dragging translate handle should move object"),
+        }
+    }
+
+    #[test]
+    fn cursor_away_from_handles_does_not_grab_gizmo() {
+        let (scene, id) = test_scene();
+        let mut gizmo = Gizmo::new();
+
+        assert!(!gizmo.start_drag(&scene, id, &GizmoSettings::new(), (10.0, 10.0), WINDOW_DIMENSIONS));
+        assert!(!gizmo.dragging());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::editor_event::EditorEvent;
 use crate::editor_state::EditorState;
+use crate::gizmo::GizmoMode;
 use crate::widget_ids::Ids;
 use ketch_core::resource::AssetManager;
 use conrod_core::position::Positionable;
@@ -13,6 +14,7 @@ use conrod_core::position::Relative;
 use conrod_core::position::Place;
 use conrod_core::color;
 use conrod_core::Labelable;
+use conrod_core::Colorable;
 use conrod_core::widget;
 
 use crate::Editor;
@@ -22,14 +24,23 @@ use log::*;
 mod gui_event;
 
 use gui_event::light_text_box_event_execute;
+use gui_event::scene_path_text_box_event_execute;
+use gui_event::vector_text_box_event_execute;
+use gui_event::text_box_event_execute;
 
 impl Editor {
     pub fn update_gui(&mut self) {
         let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
         let mut ui = self.ui.set_widgets();
 
+        // gizmo is set first, so panels are drawn over it
+        gizmo_overlay(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
         run_button(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
         light_panel(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
+        file_panel(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
+        inspector_panel(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
+        outliner_panel(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
+        gizmo_panel(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
     }
 }
 
@@ -85,6 +96,363 @@ fn light_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
 
 }
 
+fn file_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+              _synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+              pending_editor_events: &mut Vec<EditorEvent>) {
+    const PANEL_TITLE: &str = "File";
+    const PANEL_WIDTH: f64 = 300.0;
+    const PANEL_HEIGHT: f64 = 150.0;
+
+    const PANEL_PADDING: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const TEXT_BOX_HEIGHT: f64 = 25.0;
+    const BUTTON_WIDTH: f64 = 135.0;
+    const BUTTON_HEIGHT: f64 = 25.0;
+
+    let panel_title = if current_editor_state.unsaved_changes {
+        format!("{}*", PANEL_TITLE)
+    } else {
+        PANEL_TITLE.to_string()
+    };
+
+    widget::Canvas::new().floating(true).bottom_left()
+                         .w_h(PANEL_WIDTH, PANEL_HEIGHT)
+                         .pad(PANEL_PADDING)
+                         .title_bar(&panel_title)
+                         .set(ids.file_panel_canvas, ui);
+
+    let scene_path_text_box = widget::TextBox::new(&current_editor_state.scene_path_text_box_content).mid_top_of(ids.file_panel_canvas)
+                                               .padded_w_of(ids.file_panel_canvas, PANEL_PADDING)
+                                               .h(TEXT_BOX_HEIGHT);
+
+    for event in scene_path_text_box.set(ids.scene_path_text_box, ui) {
+        scene_path_text_box_event_execute(event, current_editor_state);
+    }
+
+    for _press in widget::Button::new().label("Save")
+                                       .down_from(ids.scene_path_text_box, WIDGET_DISTANCE)
+                                       .align_left_of(ids.scene_path_text_box)
+                                       .wh([BUTTON_WIDTH, BUTTON_HEIGHT])
+                                       .set(ids.save_button, ui)
+    {
+        current_editor_state.discard_changes_warning = false;
+        pending_editor_events.push(EditorEvent::SaveScene(current_editor_state.scene_path_text_box_content.clone().into()));
+    }
+
+    for _press in widget::Button::new().label("Open")
+                                       .down_from(ids.scene_path_text_box, WIDGET_DISTANCE)
+                                       .align_right_of(ids.scene_path_text_box)
+                                       .wh([BUTTON_WIDTH, BUTTON_HEIGHT])
+                                       .set(ids.open_button, ui)
+    {
+        if current_editor_state.unsaved_changes && !current_editor_state.discard_changes_warning {
+            warn!("Active scene has unsaved changes, press Open again to discard them");
+            current_editor_state.discard_changes_warning = true;
+        } else {
+            current_editor_state.discard_changes_warning = false;
+            pending_editor_events.push(EditorEvent::OpenScene(current_editor_state.scene_path_text_box_content.clone().into()));
+        }
+    }
+
+    let status = if current_editor_state.discard_changes_warning {
+        "Unsaved changes will be lost, press Open again."
+    } else if current_editor_state.unsaved_changes {
+        "Scene has unsaved changes."
+    } else {
+        "Scene is saved."
+    };
+
+    widget::Text::new(status).down_from(ids.save_button, WIDGET_DISTANCE)
+                             .align_left_of(ids.save_button)
+                             .font_size(12)
+                             .set(ids.file_status_text, ui);
+}
+
+fn inspector_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                   synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+                   pending_editor_events: &mut Vec<EditorEvent>) {
+    const PANEL_TITLE: &str = "Inspector";
+    const PANEL_WIDTH: f64 = 300.0;
+    const PANEL_HEIGHT: f64 = 290.0;
+
+    const PANEL_PADDING: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const LABEL_WIDTH: f64 = 70.0;
+    const TEXT_BOX_WIDTH: f64 = 60.0;
+    const TEXT_BOX_HEIGHT: f64 = 25.0;
+    const WIDE_TEXT_BOX_WIDTH: f64 = 3.0 * TEXT_BOX_WIDTH + 2.0 * WIDGET_DISTANCE;
+
+    let id = match current_editor_state.selected_object {
+        Some(id) => id,
+        None => return,
+    };
+
+    widget::Canvas::new().floating(true).mid_right()
+                         .w_h(PANEL_WIDTH, PANEL_HEIGHT)
+                         .pad(PANEL_PADDING)
+                         .title_bar(PANEL_TITLE)
+                         .set(ids.inspector_canvas, ui);
+
+    widget::Text::new("name:").top_left_of(ids.inspector_canvas)
+                              .w(LABEL_WIDTH)
+                              .set(ids.inspector_name_label, ui);
+
+    let name_text_box = widget::TextBox::new(&current_editor_state.object_name_text_box_content).right_from(ids.inspector_name_label, WIDGET_DISTANCE)
+                                         .wh([WIDE_TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+
+    pending_editor_events.extend(name_text_box.set(ids.inspector_name_text_box, ui).into_iter()
+                                              .filter_map(|event| text_box_event_execute(event, &mut current_editor_state.object_name_text_box_content))
+                                              .map(|name| EditorEvent::ObjectNameChanged(id, name)));
+
+    let vector_rows = [
+        ("position:", ids.inspector_name_label, ids.inspector_position_label,
+         [ids.inspector_position_x_text_box, ids.inspector_position_y_text_box, ids.inspector_position_z_text_box]),
+        ("rotation:", ids.inspector_position_label, ids.inspector_rotation_label,
+         [ids.inspector_rotation_x_text_box, ids.inspector_rotation_y_text_box, ids.inspector_rotation_z_text_box]),
+        ("scale:", ids.inspector_rotation_label, ids.inspector_scale_label,
+         [ids.inspector_scale_x_text_box, ids.inspector_scale_y_text_box, ids.inspector_scale_z_text_box]),
+    ];
+
+    for (row, (label, previous_label_id, label_id, text_box_ids)) in vector_rows.iter().enumerate() {
+        widget::Text::new(label).down_from(*previous_label_id, WIDGET_DISTANCE + TEXT_BOX_HEIGHT / 2.0)
+                                .w(LABEL_WIDTH)
+                                .set(*label_id, ui);
+
+        let (synced_contents, current_contents) = match row {
+            0 => (&synced_editor_state.object_position_text_box_contents, &mut current_editor_state.object_position_text_box_contents),
+            1 => (&synced_editor_state.object_rotation_text_box_contents, &mut current_editor_state.object_rotation_text_box_contents),
+            _ => (&synced_editor_state.object_scale_text_box_contents, &mut current_editor_state.object_scale_text_box_contents),
+        };
+
+        for (index, text_box_id) in text_box_ids.iter().enumerate() {
+            let text_box = widget::TextBox::new(&current_contents[index]).wh([TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+            let text_box = match index {
+                0 => text_box.right_from(*label_id, WIDGET_DISTANCE),
+                _ => text_box.right_from(text_box_ids[index - 1], WIDGET_DISTANCE),
+            };
+
+            let changes: Vec<(f32, f32, f32)> = text_box.set(*text_box_id, ui).into_iter()
+                                                        .filter_map(|event| vector_text_box_event_execute(event, index, synced_contents, current_contents))
+                                                        .collect();
+            // rotation is edited in degrees
+            pending_editor_events.extend(changes.into_iter().map(|(x, y, z)| match row {
+                0 => EditorEvent::ObjectPositionChanged(id, (x, y, z)),
+                1 => EditorEvent::ObjectRotationChanged(id, (x.to_radians(), y.to_radians(), z.to_radians())),
+                _ => EditorEvent::ObjectScaleChanged(id, (x, y, z)),
+            }));
+        }
+    }
+
+    for value in widget::Toggle::new(current_editor_state.object_light_source).label("light source")
+                                                                              .down_from(ids.inspector_scale_label, WIDGET_DISTANCE + TEXT_BOX_HEIGHT / 2.0)
+                                                                              .align_left_of(ids.inspector_scale_label)
+                                                                              .wh([LABEL_WIDTH + WIDGET_DISTANCE + WIDE_TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT])
+                                                                              .set(ids.inspector_light_source_toggle, ui)
+    {
+        current_editor_state.object_light_source = value;
+        pending_editor_events.push(EditorEvent::ObjectLightSourceChanged(id, value));
+    }
+
+    widget::Text::new("mesh:").down_from(ids.inspector_light_source_toggle, WIDGET_DISTANCE + TEXT_BOX_HEIGHT / 2.0)
+                              .w(LABEL_WIDTH)
+                              .set(ids.inspector_mesh_label, ui);
+
+    let mesh_text_box = widget::TextBox::new(&current_editor_state.object_mesh_text_box_content).right_from(ids.inspector_mesh_label, WIDGET_DISTANCE)
+                                         .wh([WIDE_TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+
+    pending_editor_events.extend(mesh_text_box.set(ids.inspector_mesh_text_box, ui).into_iter()
+                                              .filter_map(|event| text_box_event_execute(event, &mut current_editor_state.object_mesh_text_box_content))
+                                              .map(|name| EditorEvent::ObjectMeshChanged(id, name)));
+
+    widget::Text::new("texture:").down_from(ids.inspector_mesh_label, WIDGET_DISTANCE + TEXT_BOX_HEIGHT / 2.0)
+                                 .w(LABEL_WIDTH)
+                                 .set(ids.inspector_texture_label, ui);
+
+    let texture_text_box = widget::TextBox::new(&current_editor_state.object_texture_text_box_content).right_from(ids.inspector_texture_label, WIDGET_DISTANCE)
+                                            .wh([WIDE_TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+
+    pending_editor_events.extend(texture_text_box.set(ids.inspector_texture_text_box, ui).into_iter()
+                                                 .filter_map(|event| text_box_event_execute(event, &mut current_editor_state.object_texture_text_box_content))
+                                                 // empty name brings back texture of the mesh
+                                                 .map(|name| EditorEvent::ObjectTextureChanged(id, Some(name).filter(|name| !name.is_empty()))));
+}
+
+fn outliner_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                  _synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+                  pending_editor_events: &mut Vec<EditorEvent>) {
+    const PANEL_TITLE: &str = "Outliner";
+    const PANEL_WIDTH: f64 = 300.0;
+    const PANEL_HEIGHT: f64 = 330.0;
+
+    const PANEL_PADDING: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const ITEM_HEIGHT: f64 = 25.0;
+    const LIST_HEIGHT: f64 = 150.0;
+    const BUTTON_WIDTH: f64 = 135.0;
+
+    widget::Canvas::new().floating(true).mid_left()
+                         .w_h(PANEL_WIDTH, PANEL_HEIGHT)
+                         .pad(PANEL_PADDING)
+                         .title_bar(PANEL_TITLE)
+                         .set(ids.outliner_canvas, ui);
+
+    // active scene is always the first one
+    let scene_names: Vec<String> = current_editor_state.active_scene_name.iter()
+                                                                         .chain(current_editor_state.scene_names.iter())
+                                                                         .cloned()
+                                                                         .collect();
+
+    let selected_scene = widget::DropDownList::new(&scene_names, Some(0)).mid_top_of(ids.outliner_canvas)
+                                                                         .padded_w_of(ids.outliner_canvas, PANEL_PADDING)
+                                                                         .h(ITEM_HEIGHT)
+                                                                         .set(ids.outliner_scene_list, ui);
+
+    if let Some(index) = selected_scene.filter(|index| *index > 0) {
+        pending_editor_events.push(EditorEvent::ActiveSceneChanged(scene_names[index].clone()));
+    }
+
+    let (mut items, scrollbar) = widget::List::flow_down(current_editor_state.outliner_items.len())
+                                             .item_size(ITEM_HEIGHT)
+                                             .scrollbar_on_top()
+                                             .down_from(ids.outliner_scene_list, WIDGET_DISTANCE)
+                                             .padded_w_of(ids.outliner_canvas, PANEL_PADDING)
+                                             .h(LIST_HEIGHT)
+                                             .set(ids.outliner_object_list, ui);
+
+    while let Some(item) = items.next(ui) {
+        let (id, label) = &current_editor_state.outliner_items[item.i];
+        let color = if current_editor_state.selected_object == Some(*id) {
+            color::LIGHT_ORANGE
+        } else {
+            color::LIGHT_CHARCOAL
+        };
+
+        let id = *id;
+        for _press in item.set(widget::Button::new().label(label).color(color), ui) {
+            current_editor_state.selected_object = Some(id);
+        }
+    }
+
+    if let Some(scrollbar) = scrollbar {
+        scrollbar.set(ui);
+    }
+
+    let id = match current_editor_state.selected_object {
+        Some(id) => id,
+        None => return,
+    };
+
+    let name_text_box = widget::TextBox::new(&current_editor_state.object_name_text_box_content).down_from(ids.outliner_object_list, WIDGET_DISTANCE)
+                                         .padded_w_of(ids.outliner_canvas, PANEL_PADDING)
+                                         .h(ITEM_HEIGHT);
+
+    pending_editor_events.extend(name_text_box.set(ids.outliner_name_text_box, ui).into_iter()
+                                              .filter_map(|event| text_box_event_execute(event, &mut current_editor_state.object_name_text_box_content))
+                                              .map(|name| EditorEvent::ObjectNameChanged(id, name)));
+
+    for _press in widget::Button::new().label("Duplicate")
+                                       .down_from(ids.outliner_name_text_box, WIDGET_DISTANCE)
+                                       .align_left_of(ids.outliner_name_text_box)
+                                       .wh([BUTTON_WIDTH, ITEM_HEIGHT])
+                                       .set(ids.outliner_duplicate_button, ui)
+    {
+        pending_editor_events.push(EditorEvent::ObjectDuplicated(id));
+    }
+
+    for _press in widget::Button::new().label("Delete")
+                                       .down_from(ids.outliner_name_text_box, WIDGET_DISTANCE)
+                                       .align_right_of(ids.outliner_name_text_box)
+                                       .wh([BUTTON_WIDTH, ITEM_HEIGHT])
+                                       .set(ids.outliner_delete_button, ui)
+    {
+        pending_editor_events.push(EditorEvent::ObjectRemoved(id));
+    }
+}
+
+fn gizmo_overlay(ids: &Ids, ui: &mut conrod_core::UiCell,
+                 _synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+                 _pending_editor_events: &mut Vec<EditorEvent>) {
+    const HANDLE_THICKNESS: f64 = 3.0;
+
+    let path_ids = [ids.gizmo_x_path, ids.gizmo_y_path, ids.gizmo_z_path];
+    let colors = [color::RED, color::GREEN, color::BLUE];
+
+    for ((path, path_id), color) in current_editor_state.gizmo_paths.iter().zip(path_ids.iter()).zip(colors.iter()) {
+        if path.len() < 2 {
+            continue;
+        }
+        // clicks on handles are passed to the window, so they can be grabbed by the editor
+        widget::PointPath::abs(path.iter().cloned()).color(*color)
+                                                    .thickness(HANDLE_THICKNESS)
+                                                    .graphics_for(ui.window)
+                                                    .set(*path_id, ui);
+    }
+}
+
+fn gizmo_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+               _synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+               _pending_editor_events: &mut Vec<EditorEvent>) {
+    const PANEL_TITLE: &str = "Gizmo";
+    const PANEL_WIDTH: f64 = 300.0;
+    const PANEL_HEIGHT: f64 = 110.0;
+
+    const PANEL_PADDING: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const BUTTON_WIDTH: f64 = 86.0;
+    const TOGGLE_WIDTH: f64 = 135.0;
+    const ITEM_HEIGHT: f64 = 25.0;
+
+    widget::Canvas::new().floating(true).bottom_right()
+                         .w_h(PANEL_WIDTH, PANEL_HEIGHT)
+                         .pad(PANEL_PADDING)
+                         .title_bar(PANEL_TITLE)
+                         .set(ids.gizmo_canvas, ui);
+
+    let mode_buttons = [
+        (GizmoMode::Translate, "Translate", ids.gizmo_translate_button),
+        (GizmoMode::Rotate, "Rotate", ids.gizmo_rotate_button),
+        (GizmoMode::Scale, "Scale", ids.gizmo_scale_button),
+    ];
+
+    for (index, (mode, label, button_id)) in mode_buttons.iter().enumerate() {
+        let color = if current_editor_state.gizmo_settings.mode == *mode {
+            color::LIGHT_ORANGE
+        } else {
+            color::LIGHT_CHARCOAL
+        };
+
+        let button = widget::Button::new().label(label).color(color).wh([BUTTON_WIDTH, ITEM_HEIGHT]);
+        let button = match index {
+            0 => button.top_left_of(ids.gizmo_canvas),
+            _ => button.right_from(mode_buttons[index - 1].2, WIDGET_DISTANCE),
+        };
+
+        for _press in button.set(*button_id, ui) {
+            current_editor_state.gizmo_settings.mode = *mode;
+        }
+    }
+
+    for value in widget::Toggle::new(current_editor_state.gizmo_settings.local_space).label("local space")
+                                                                                     .down_from(ids.gizmo_translate_button, WIDGET_DISTANCE)
+                                                                                     .wh([TOGGLE_WIDTH, ITEM_HEIGHT])
+                                                                                     .set(ids.gizmo_local_space_toggle, ui)
+    {
+        current_editor_state.gizmo_settings.local_space = value;
+    }
+
+    for value in widget::Toggle::new(current_editor_state.gizmo_settings.snapping).label("snap to grid")
+                                                                                  .right_from(ids.gizmo_local_space_toggle, WIDGET_DISTANCE)
+                                                                                  .wh([TOGGLE_WIDTH, ITEM_HEIGHT])
+                                                                                  .set(ids.gizmo_snapping_toggle, ui)
+    {
+        current_editor_state.gizmo_settings.snapping = value;
+    }
+}
+
 fn run_button(ids: &Ids, ui: &mut conrod_core::UiCell, 
                _synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
                _pending_editor_events: &mut Vec<EditorEvent>) {
@@ -92,11 +460,22 @@ fn run_button(ids: &Ids, ui: &mut conrod_core::UiCell,
     const BUTTON_WIDTH: f64 = 300.0;
     const BUTTON_HEIGHT: f64 = 150.0;
 
+    const WIDGET_DISTANCE: f64 = 10.0;
+    const TOGGLE_HEIGHT: f64 = 25.0;
+
     for _press in widget::Button::new().label(BUTTON_TITLE)
                                        .top_right()
                                        .wh([BUTTON_WIDTH, BUTTON_HEIGHT])
                                        .set(ids.run_button, ui) 
     {
-        current_editor_state.run_game = true;
+        current_editor_state.run_game_requested = true;
+    }
+
+    for value in widget::Toggle::new(current_editor_state.keep_play_changes).label("keep changes made while running")
+                                                                            .down_from(ids.run_button, WIDGET_DISTANCE)
+                                                                            .wh([BUTTON_WIDTH, TOGGLE_HEIGHT])
+                                                                            .set(ids.keep_play_changes_toggle, ui)
+    {
+        current_editor_state.keep_play_changes = value;
     }
 }
\ No newline at end of file
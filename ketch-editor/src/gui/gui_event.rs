@@ -49,4 +49,52 @@ pub fn light_text_box_event_execute(event: text_box::Event,
             None
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn scene_path_text_box_event_execute(event: text_box::Event, current_editor_state: &mut EditorState) {
+    if let text_box::Event::Update(new_val) = event {
+        current_editor_state.scene_path_text_box_content = new_val;
+        current_editor_state.discard_changes_warning = false;
+    }
+}
+
+/// Updates one of three text boxes editing a vector and returns the whole vector on enter.
+/// Text boxes which can't be parsed are reverted to the synced value.
+pub fn vector_text_box_event_execute(event: text_box::Event,
+                                     updated_text_box: usize,
+                                     synced_contents: &[String; 3],
+                                     current_contents: &mut [String; 3]) -> Option<(f32, f32, f32)> {
+    const AXES: [&str; 3] = ["x", "y", "z"];
+
+    match event {
+        text_box::Event::Enter => {
+            let mut values = [0.0; 3];
+            for (index, value) in values.iter_mut().enumerate() {
+                *value = match current_contents[index].parse() {
+                    Ok(parsed) => parsed,
+                    Err(err) => {
+                        error!("Couldn't parse {} text box: {}", AXES[index], err);
+                        current_contents[index] = synced_contents[index].clone();
+                        current_contents[index].parse().unwrap_or(0.0)
+                    }
+                };
+            }
+            Some((values[0], values[1], values[2]))
+        },
+        text_box::Event::Update(new_val) => {
+            current_contents[updated_text_box] = new_val;
+            None
+        }
+    }
+}
+
+/// Updates text box content and returns it on enter.
+pub fn text_box_event_execute(event: text_box::Event, current_content: &mut String) -> Option<String> {
+    match event {
+        text_box::Event::Enter => Some(current_content.clone()),
+        text_box::Event::Update(new_val) => {
+            *current_content = new_val;
+            None
+        }
+    }
+}
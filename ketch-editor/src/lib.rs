@@ -10,7 +10,12 @@ use ketch_core::settings::Settings;
 use std::time::Duration;
 use crate::editor_event::EditorEvent;
 use crate::editor_error::EditorCreationError;
+use crate::editor_history::{EditorHistory, MAX_HISTORY_LENGTH};
+use crate::gizmo::Gizmo;
+use crate::unsaved_changes::UnsavedChangesTracker;
 use ketch_core::resource::AssetManager;
+use ketch_core::resource::scene::Scene;
+use ketch_core::resource::object::Object;
 use vulkano::swapchain::Surface;
 use vulkano::device::Queue;
 use std::sync::Arc;
@@ -26,11 +31,16 @@ use editor_state::EditorState;
 use crate::widget_ids::Ids;
 use conrod_core::Ui;
 
+use log::*;
+
 mod widget_ids;
 mod editor_state;
 mod editor_error;
 mod gui;
 mod editor_event;
+mod editor_history;
+mod gizmo;
+mod unsaved_changes;
 
 pub struct Editor {
     ui: Ui,
@@ -42,6 +52,13 @@ pub struct Editor {
     current_editor_state: EditorState,
     editor_input_state: EditorInputState,
     pending_editor_events: Vec<EditorEvent>,
+    history: EditorHistory,
+    gizmo: Gizmo,
+    /// Undoes the whole gizmo drag, recorded in history when the drag ends.
+    gizmo_undo_event: Option<EditorEvent>,
+    /// Active scene from the moment the game was started, restored when going back to the editor.
+    play_snapshot: Option<Scene>,
+    unsaved_changes_tracker: UnsavedChangesTracker,
 }
 
 impl Editor {
@@ -80,6 +97,11 @@ impl Editor {
                 editor_input_state: EditorInputState::new(),
 
                 pending_editor_events: Vec::new(),
+                history: EditorHistory::new(MAX_HISTORY_LENGTH),
+                gizmo: Gizmo::new(),
+                gizmo_undo_event: None,
+                play_snapshot: None,
+                unsaved_changes_tracker: UnsavedChangesTracker::new(),
             }
         )
     }
@@ -119,20 +141,25 @@ impl Editor {
     }
 
     fn handle_camera_input(&mut self, input_events: Vec<InputEvent>, input_system: &mut InputSystem) {
+        self.editor_input_state.input_state.update(&input_events);
         input_events.into_iter().for_each(|event| {
             match event {
-                InputEvent::KeyboardInput { keycode, state } => match keycode {
-                    VirtualKeyCode::W if state == Pressed => self.editor_input_state.up = true,
-                    VirtualKeyCode::S if state == Pressed => self.editor_input_state.down = true,
-                    VirtualKeyCode::A if state == Pressed => self.editor_input_state.left = true,
-                    VirtualKeyCode::D if state == Pressed => self.editor_input_state.right = true,
-                    VirtualKeyCode::W if state == Released => self.editor_input_state.up = false,
-                    VirtualKeyCode::S if state == Released => self.editor_input_state.down = false,
-                    VirtualKeyCode::A if state == Released => self.editor_input_state.left = false,
-                    VirtualKeyCode::D if state == Released => self.editor_input_state.right = false,
-                    _ => (),
+                // modifiers come with the key event, so they don't stay pressed when window loses focus
+                InputEvent::KeyboardInput { keycode: VirtualKeyCode::Z, state: Pressed, modifiers } if modifiers.ctrl => {
+                    if modifiers.shift {
+                        self.editor_input_state.redo_requested = true;
+                    } else {
+                        self.editor_input_state.undo_requested = true;
+                    }
                 },
                 InputEvent::MouseInput { button, state } => match button {
+                    MouseButton::Left if state == Pressed => {
+                        self.editor_input_state.left_mouse_button_pressed = true;
+                        if !self.editor_input_state.right_mouse_button_pressed {
+                            self.editor_input_state.pick_requested = true;
+                        }
+                    },
+                    MouseButton::Left if state == Released => self.editor_input_state.left_mouse_button_pressed = false,
                     MouseButton::Right if state == Pressed => {
                         self.editor_input_state.right_mouse_button_pressed = true;
                         input_system.grab_cursor(true);
@@ -145,6 +172,9 @@ impl Editor {
                     }
                     _ => (),
                 }
+                InputEvent::CursorMoved { x, y } => {
+                    self.editor_input_state.cursor_position = (x, y);
+                },
                 InputEvent::MouseMotion { delta } => {
                     self.editor_input_state.mouse_delta_changed = true;
                     self.editor_input_state.mouse_delta = (delta.0 as f32, delta.1 as f32);
@@ -160,6 +190,16 @@ impl Editor {
             input_events.into_iter().filter_map(|event| conrod_winit::convert_event(event, window))
                                     .for_each(|event| self.ui.handle_event(event));
         }
+        // clicks on the gui don't select objects
+        let window = self.ui.window;
+        if self.ui.global_input().current.widget_under_mouse.filter(|widget| *widget != window).is_some() {
+            self.editor_input_state.pick_requested = false;
+        }
+        // text boxes have their own undo
+        if self.ui.global_input().current.widget_capturing_keyboard.is_some() {
+            self.editor_input_state.undo_requested = false;
+            self.editor_input_state.redo_requested = false;
+        }
         if self.ui.global_input().events().next().is_some() {
             self.update_gui();
         }
@@ -227,25 +267,74 @@ impl Editor {
             editor_state.y_light_text_box_content = light_y.to_string();
             editor_state.z_light_text_box_content = light_z.to_string();
         }
+        editor_state.scene_path_text_box_content = self.current_editor_state.scene_path_text_box_content.clone();
+        editor_state.gizmo_settings = self.current_editor_state.gizmo_settings;
+        editor_state.keep_play_changes = self.current_editor_state.keep_play_changes;
+        editor_state.gizmo_paths = self.current_editor_state.gizmo_paths.clone();
+        editor_state.selected_object = self.current_editor_state.selected_object.filter(|id| {
+            asset_manager.active_scene().and_then(|scene| scene.object(*id)).is_some()
+        });
+        if let Some(scene) = asset_manager.active_scene() {
+            editor_state.active_scene_name = Some(scene.name().to_string());
+            editor_state.outliner_items = scene.objects().iter().map(|object| {
+                (object.id(), format!("{}{} ({})", "    ".repeat(object_depth(scene, object)), object.name(), object.id()))
+            }).collect();
+        }
+        editor_state.scene_names = asset_manager.scene_names().into_iter().map(String::from).collect();
+        if let Some(object) = editor_state.selected_object.and_then(|id| asset_manager.active_scene()?.object(id)) {
+            let (x, y, z) = object.position();
+            editor_state.object_position_text_box_contents = [x.to_string(), y.to_string(), z.to_string()];
+            let (x, y, z) = object.rotation_angles();
+            editor_state.object_rotation_text_box_contents = [x.to_degrees().to_string(), y.to_degrees().to_string(), z.to_degrees().to_string()];
+            let (x, y, z) = object.scale();
+            editor_state.object_scale_text_box_contents = [x.to_string(), y.to_string(), z.to_string()];
+            editor_state.object_name_text_box_content = object.name().to_string();
+            editor_state.object_light_source = object.light_source();
+            if let Some(mesh) = object.mesh() {
+                editor_state.object_mesh_text_box_content = mesh.read().unwrap().name().to_string();
+            }
+            if let Some(texture) = object.texture() {
+                editor_state.object_texture_text_box_content = texture.name().to_string();
+            }
+        }
+        editor_state.unsaved_changes = self.current_editor_state.unsaved_changes;
         self.synced_editor_state = editor_state.clone();
         self.current_editor_state = editor_state;
     }
 
+    /// Returns id of the object selected by clicking in the viewport.
+    pub fn selected_object(&self) -> Option<u32> {
+        self.current_editor_state.selected_object
+    }
+
     pub fn run_game(&self) -> bool {
         self.current_editor_state.run_game
     }
 
-    pub fn set_run_game(&mut self, run_game: bool, asset_manager: Option<&AssetManager>) {
-        if !run_game {
-            assert!(asset_manager.is_some(), "Asset manager has to be supplied when going back to the editor to sync game state with the editor!");
-            self.sync_editor(asset_manager.unwrap());
+    /// Starts or stops the game. Active scene is snapshotted when the game starts and restored when it stops,
+    /// unless changes made while playing are kept.
+    pub fn set_run_game(&mut self, run_game: bool, asset_manager: &mut AssetManager) {
+        if run_game == self.current_editor_state.run_game {
+            return;
+        }
+        if run_game {
+            self.play_snapshot = start_play(asset_manager);
+        } else {
+            stop_play(self.play_snapshot.take(), self.current_editor_state.keep_play_changes, asset_manager);
+            self.unsaved_changes_tracker.mark_changed();
+            self.sync_editor(asset_manager);
         }
         self.current_editor_state.run_game = run_game;
     }
 
-    fn update_camera(&mut self, asset_manager: &mut AssetManager, update_time_delta: Duration) {
+    /// Moves and rotates camera while right mouse button is pressed. Returns true if the camera was changed.
+    fn update_camera(&mut self, asset_manager: &mut AssetManager, update_time_delta: Duration) -> bool {
+        let input_map = &self.editor_input_state.input_map;
+        let input_state = &self.editor_input_state.input_state;
+        let moved = ["move_forward", "move_backward", "move_left", "move_right"].iter().any(|action| input_map.pressed(input_state, action));
         match asset_manager.active_scene_mut() {
             Some(ref mut active_scene) if self.editor_input_state.right_mouse_button_pressed => {
+                let changed = moved || self.editor_input_state.mouse_delta_changed;
                 let camera = active_scene.camera_mut();
                 if self.editor_input_state.mouse_delta_changed {
                     let (x_delta, y_delta) = self.editor_input_state.mouse_delta;
@@ -257,35 +346,313 @@ impl Editor {
                     camera.set_pitch(current_pitch + (-y_delta) * self.editor_input_state.mouse_sensitivity);
                     self.editor_input_state.mouse_delta_changed = false;
                 }
-                if self.editor_input_state.up {
+                if self.editor_input_state.input_map.pressed(&self.editor_input_state.input_state, "move_forward") {
                     camera.move_camera(Direction::Up, self.editor_input_state.camera_speed * (update_time_delta.as_millis() as f32 / 1000.0));
                 }
-                if self.editor_input_state.down {
+                if self.editor_input_state.input_map.pressed(&self.editor_input_state.input_state, "move_backward") {
                     camera.move_camera(Direction::Down, self.editor_input_state.camera_speed * (update_time_delta.as_millis() as f32 / 1000.0));
                 }
-                if self.editor_input_state.left {
+                if self.editor_input_state.input_map.pressed(&self.editor_input_state.input_state, "move_left") {
                     camera.move_camera(Direction::Left, self.editor_input_state.camera_speed * (update_time_delta.as_millis() as f32 / 1000.0));
                 }
-                if self.editor_input_state.right {
+                if self.editor_input_state.input_map.pressed(&self.editor_input_state.input_state, "move_right") {
                     camera.move_camera(Direction::Right, self.editor_input_state.camera_speed * (update_time_delta.as_millis() as f32 / 1000.0));
                 }
+                changed
             }
-            _ => (),
+            _ => false,
         }
     } 
 
+    /// Grabs gizmo handle under the cursor instead of picking an object and moves the dragged handle.
+    /// Returns true if transform of the selected object was changed.
+    fn drag_gizmo(&mut self, asset_manager: &mut AssetManager) -> bool {
+        let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
+        let window_dimensions = (window_dimensions.width as f32, window_dimensions.height as f32);
+        let dpi = ketch_core::renderer::get_window_dpi(self.surface.window());
+        let (cursor_x, cursor_y) = self.editor_input_state.cursor_position;
+        let cursor_position = ((cursor_x * dpi) as f32, (cursor_y * dpi) as f32);
+        let settings = self.current_editor_state.gizmo_settings;
+
+        if self.editor_input_state.pick_requested && !self.gizmo.dragging() {
+            if let (Some(scene), Some(id)) = (asset_manager.active_scene(), self.current_editor_state.selected_object) {
+                if self.gizmo.start_drag(scene, id, &settings, cursor_position, window_dimensions) {
+                    self.editor_input_state.pick_requested = false;
+                }
+            }
+        }
+
+        let gizmo = &mut self.gizmo;
+        let event = asset_manager.active_scene().and_then(|scene| {
+            gizmo.drag(&scene.camera().screen_ray(cursor_position.0, cursor_position.1, window_dimensions.0, window_dimensions.1))
+        });
+        let changed = match event.map(|event| event.execute(asset_manager)) {
+            Some(Ok(undo_event)) => {
+                if self.gizmo_undo_event.is_none() {
+                    self.gizmo_undo_event = undo_event;
+                }
+                true
+            },
+            Some(Err(err)) => {
+                error!("Couldn't execute gizmo event: {}", err);
+                false
+            },
+            None => false,
+        };
+
+        if self.gizmo.dragging() && !self.editor_input_state.left_mouse_button_pressed {
+            self.gizmo.stop_drag();
+            if let Some(undo_event) = self.gizmo_undo_event.take() {
+                self.history.record(undo_event);
+            }
+        }
+        changed
+    }
+
+    /// Projects gizmo handles of the selected object on the window. Returns true if they moved.
+    fn update_gizmo_paths(&mut self, asset_manager: &AssetManager) -> bool {
+        let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
+        let (width, height) = (window_dimensions.width, window_dimensions.height);
+        let (ui_width, ui_height) = (self.ui.win_w, self.ui.win_h);
+
+        let paths = match (asset_manager.active_scene(), self.current_editor_state.selected_object) {
+            (Some(scene), Some(id)) => Gizmo::handle_paths(scene, id, &self.current_editor_state.gizmo_settings, (width as f32, height as f32)),
+            _ => Vec::new(),
+        };
+        // conrod places the origin in the middle of the window with y axis pointing up
+        let paths: Vec<Vec<[f64; 2]>> = paths.into_iter().map(|path| {
+            path.into_iter().map(|(x, y)| [x as f64 / width * ui_width - ui_width / 2.0, ui_height / 2.0 - y as f64 / height * ui_height]).collect()
+        }).collect();
+
+        let moved = paths != self.current_editor_state.gizmo_paths;
+        self.current_editor_state.gizmo_paths = paths;
+        moved
+    }
+
+    /// Selects object under the cursor. Clicking where there is no object clears the selection.
+    fn pick_object(&mut self, asset_manager: &AssetManager) {
+        if !self.editor_input_state.pick_requested {
+            return;
+        }
+        self.editor_input_state.pick_requested = false;
+
+        let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
+        let dpi = ketch_core::renderer::get_window_dpi(self.surface.window());
+        let (cursor_x, cursor_y) = self.editor_input_state.cursor_position;
+
+        let selected_object = asset_manager.active_scene().and_then(|scene| {
+            let ray = scene.camera().screen_ray((cursor_x * dpi) as f32, (cursor_y * dpi) as f32,
+                                                window_dimensions.width as f32, window_dimensions.height as f32);
+            scene.pick_object(&ray)
+        });
+
+        if selected_object != self.current_editor_state.selected_object {
+            match selected_object.and_then(|id| asset_manager.active_scene()?.object(id)) {
+                Some(object) => info!("Selected object {} ({})", object.name(), object.id()),
+                None => info!("Selection cleared"),
+            }
+        }
+        self.current_editor_state.selected_object = selected_object;
+    }
+
+    /// Executes requested undo or redo and returns true if something was changed.
+    fn undo_or_redo(&mut self, asset_manager: &mut AssetManager) -> bool {
+        let undo_requested = self.editor_input_state.undo_requested;
+        let redo_requested = self.editor_input_state.redo_requested;
+        self.editor_input_state.undo_requested = false;
+        self.editor_input_state.redo_requested = false;
+
+        let event = if undo_requested {
+            self.history.pop_undo()
+        } else if redo_requested {
+            self.history.pop_redo()
+        } else {
+            None
+        };
+
+        let syncs_scene_file = event.as_ref().map_or(false, |event| event.syncs_scene_file());
+        match event.map(|event| event.execute(asset_manager)) {
+            Some(Ok(inverse_event)) => {
+                if syncs_scene_file {
+                    self.mark_scene_file_synced(asset_manager);
+                }
+                if let Some(inverse_event) = inverse_event {
+                    if undo_requested {
+                        self.history.push_redo(inverse_event);
+                    } else {
+                        self.history.push_undo(inverse_event);
+                    }
+                }
+                true
+            },
+            Some(Err(err)) => {
+                error!("Couldn't {} editor event: {}", if undo_requested { "undo" } else { "redo" }, err);
+                false
+            },
+            None => false,
+        }
+    }
+
+    /// Remembers active scene as the one stored in scene file and syncs editor with it.
+    fn mark_scene_file_synced(&mut self, asset_manager: &AssetManager) {
+        self.unsaved_changes_tracker.mark_saved(asset_manager.active_scene());
+        self.sync_editor(asset_manager);
+    }
+
+    /// Checks if active scene differs from the scene which was last saved or opened.
+    fn update_unsaved_changes(&mut self, asset_manager: &AssetManager) {
+        self.current_editor_state.unsaved_changes = self.unsaved_changes_tracker.check(asset_manager.active_scene());
+    }
+
     pub fn update(&mut self, asset_manager: &mut AssetManager, update_time_delta: Duration) {
-        self.update_camera(asset_manager, update_time_delta);
-        self.pending_editor_events.drain(..).for_each(|event| event.execute(asset_manager));
+        // game is started here, so the scene is snapshotted before the game updates it
+        if self.current_editor_state.run_game_requested {
+            self.current_editor_state.run_game_requested = false;
+            self.set_run_game(true, asset_manager);
+            return;
+        }
+        let camera_changed = self.update_camera(asset_manager, update_time_delta);
+        let gizmo_changed_object = self.drag_gizmo(asset_manager);
+        self.pick_object(asset_manager);
+        let pending_editor_events: Vec<EditorEvent> = self.pending_editor_events.drain(..).collect();
+        let mut scene_changed = camera_changed || gizmo_changed_object;
+        let mut syncs_editor = gizmo_changed_object;
+        for event in pending_editor_events {
+            let syncs_scene_file = event.syncs_scene_file();
+            let event_syncs_editor = event.syncs_editor();
+            let clears_history = event.clears_history();
+            match event.execute(asset_manager) {
+                Ok(undo_event) => {
+                    scene_changed = true;
+                    if clears_history {
+                        self.history.clear();
+                    }
+                    if let Some(undo_event) = undo_event {
+                        self.history.record(undo_event);
+                    }
+                    if syncs_scene_file {
+                        self.mark_scene_file_synced(asset_manager);
+                    }
+                    syncs_editor |= event_syncs_editor;
+                },
+                Err(err) => error!("Couldn't execute editor event: {}", err),
+            }
+        }
+        let undone_or_redone = self.undo_or_redo(asset_manager);
+        scene_changed |= undone_or_redone;
+        syncs_editor |= undone_or_redone;
+        if scene_changed {
+            self.unsaved_changes_tracker.mark_changed();
+        }
+        // inspector and outliner show values of the new selection or changed scene
+        let gizmo_moved = self.update_gizmo_paths(asset_manager);
+        if syncs_editor || self.current_editor_state.selected_object != self.synced_editor_state.selected_object {
+            self.sync_editor(asset_manager);
+            self.update_gui();
+        } else if gizmo_moved {
+            self.update_gui();
+        }
+        self.update_unsaved_changes(asset_manager);
     }
 }
 
+/// Returns snapshot of the active scene taken when the game starts.
+fn start_play(asset_manager: &AssetManager) -> Option<Scene> {
+    asset_manager.active_scene().map(Scene::snapshot)
+}
 
+/// Restores snapshot taken when the game started, unless changes made while playing are kept.
+fn stop_play(play_snapshot: Option<Scene>, keep_play_changes: bool, asset_manager: &mut AssetManager) {
+    if let Some(snapshot) = play_snapshot {
+        if !keep_play_changes {
+            restore_play_snapshot(snapshot, asset_manager);
+        }
+    }
+}
+
+/// Replaces scene which was active when the game started with its snapshot,
+/// even if the game changed active scene to another one.
+fn restore_play_snapshot(snapshot: Scene, asset_manager: &mut AssetManager) {
+    if asset_manager.active_scene().map(|scene| scene.name()) != Some(snapshot.name()) {
+        asset_manager.change_active_scene(snapshot.name());
+    }
+    asset_manager.set_active_scene(snapshot);
+}
+
+/// Returns number of ancestors of the object, used to indent objects in the outliner.
+fn object_depth(scene: &Scene, object: &Object) -> usize {
+    let mut depth = 0;
+    let mut parent = object.parent();
+    while let Some(id) = parent {
+        if depth >= scene.objects().len() {
+            break;
+        }
+        depth += 1;
+        parent = scene.object(id).and_then(|parent| parent.parent());
+    }
+    depth
+}
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use ketch_core::renderer::headless::HeadlessRenderer;
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+
+    /// Creates asset manager with device of headless renderer, so it needs vulkan driver same as headless renderer tests.
+    pub(crate) fn asset_manager() -> AssetManager {
+        let renderer = HeadlessRenderer::new(&Settings::new("test", 600.0, 400.0)).unwrap();
+        AssetManager::new(renderer.queues(), renderer.device())
+    }
+
+    /// Returns asset manager with active scene containing one cube and id of the cube.
+    fn asset_manager_with_cube() -> (AssetManager, u32) {
+        let mut asset_manager = asset_manager();
+        let mut scene = Scene::new("test", Camera::new());
+        let cube = ObjectBuilder::new("cube").build();
+        let id = cube.id();
+        scene.add_object(cube);
+        asset_manager.set_active_scene(scene);
+        (asset_manager, id)
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn stopping_game_restores_or_keeps_scene_changed_while_playing() {
+        for &keep_play_changes in &[false, true] {
+            let (mut asset_manager, id) = asset_manager_with_cube();
+
+            let play_snapshot = start_play(&asset_manager);
+            asset_manager.active_scene_mut().unwrap().object_mut(id).unwrap().set_position(1.0, 2.0, 3.0);
+            stop_play(play_snapshot, keep_play_changes, &mut asset_manager);
+
+            let position = asset_manager.active_scene().unwrap().object(id).unwrap().position();
+            if keep_play_changes {
+                assert_eq!((1.0, 2.0, 3.0), position);
+            } else {
+                assert_eq!((0.0, 0.0, 0.0), position);
+            }
+        }
+    }
+
+    #[test]
+    fn stopping_game_restores_scene_active_when_game_started() {
+        let (mut asset_manager, id) = asset_manager_with_cube();
+        asset_manager.add_scene(Scene::new("level", Camera::new()));
+
+        let play_snapshot = start_play(&asset_manager);
+        asset_manager.active_scene_mut().unwrap().remove_object(id);
+        asset_manager.change_active_scene("level");
+        stop_play(play_snapshot, false, &mut asset_manager);
+
+        let scene = asset_manager.active_scene().unwrap();
+        assert_eq!("test", scene.name());
+        assert!(scene.object(id).is_some());
+        assert_eq!(vec!["level"], asset_manager.scene_names());
+    }
 }
@@ -0,0 +1,123 @@
+use ketch_core::resource::scene::Scene;
+use ketch_core::resource::scene_file::SceneFile;
+
+use std::collections::HashMap;
+
+/// Tracks if active scene differs from the scene which was last saved or opened.
+/// Saved scene is remembered per scene name, so switching active scene keeps unsaved changes of the others.
+/// Scene file of the active scene is built and compared only after the scene was marked as changed
+/// or another scene became active, not on every editor update.
+pub struct UnsavedChangesTracker {
+    saved_scenes: HashMap<String, SceneFile>,
+    checked_scene: Option<String>,
+    scene_changed: bool,
+    unsaved_changes: bool,
+}
+
+impl UnsavedChangesTracker {
+    pub fn new() -> Self {
+        UnsavedChangesTracker {
+            saved_scenes: HashMap::new(),
+            checked_scene: None,
+            scene_changed: false,
+            unsaved_changes: false,
+        }
+    }
+
+    /// Remembers scene as the one stored in scene file.
+    pub fn mark_saved(&mut self, scene: Option<&Scene>) {
+        if let Some(scene) = scene {
+            self.saved_scenes.insert(scene.name().to_string(), SceneFile::from_scene(scene));
+        }
+        self.checked_scene = scene.map(|scene| scene.name().to_string());
+        self.scene_changed = false;
+        self.unsaved_changes = false;
+    }
+
+    /// Marks active scene as possibly changed, so it's compared with the saved scene on the next check.
+    pub fn mark_changed(&mut self) {
+        self.scene_changed = true;
+    }
+
+    /// Returns true if active scene differs from its saved scene.
+    /// Scenes which weren't saved or opened are remembered as saved the first time they are active.
+    pub fn check(&mut self, active_scene: Option<&Scene>) -> bool {
+        let scene = match active_scene {
+            Some(scene) => scene,
+            None => {
+                self.checked_scene = None;
+                self.unsaved_changes = false;
+                return false;
+            },
+        };
+        if self.checked_scene.as_ref().map(String::as_str) != Some(scene.name()) {
+            self.checked_scene = Some(scene.name().to_string());
+            self.scene_changed = true;
+        }
+        if !self.saved_scenes.contains_key(scene.name()) {
+            self.mark_saved(Some(scene));
+        } else if self.scene_changed {
+            self.scene_changed = false;
+            self.unsaved_changes = self.saved_scenes.get(scene.name()) != Some(&SceneFile::from_scene(scene));
+        }
+        self.unsaved_changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+
+    fn test_scene(name: &str) -> Scene {
+        let mut scene = Scene::new(name, Camera::new());
+        scene.add_object(ObjectBuilder::new("cube").build());
+        scene
+    }
+
+    #[test]
+    fn scene_is_compared_only_after_it_was_marked_as_changed() {
+        let mut tracker = UnsavedChangesTracker::new();
+        let mut scene = test_scene("test");
+        assert!(!tracker.check(Some(&scene)));
+
+        scene.objects_mut()[0].set_position(1.0, 0.0, 0.0);
+        assert!(!tracker.check(Some(&scene)));
+        tracker.mark_changed();
+        assert!(tracker.check(Some(&scene)));
+        assert!(tracker.check(Some(&scene)));
+
+        scene.objects_mut()[0].set_position(0.0, 0.0, 0.0);
+        tracker.mark_changed();
+        assert!(!tracker.check(Some(&scene)));
+    }
+
+    #[test]
+    fn saved_scene_has_no_unsaved_changes() {
+        let mut tracker = UnsavedChangesTracker::new();
+        let mut scene = test_scene("test");
+        tracker.mark_saved(Some(&scene));
+
+        scene.set_light_position(1.0, 2.0, 3.0);
+        tracker.mark_changed();
+        assert!(tracker.check(Some(&scene)));
+
+        tracker.mark_saved(Some(&scene));
+        assert!(!tracker.check(Some(&scene)));
+    }
+
+    #[test]
+    fn unsaved_changes_are_remembered_per_scene() {
+        let mut tracker = UnsavedChangesTracker::new();
+        let mut first_scene = test_scene("first");
+        let second_scene = test_scene("second");
+        assert!(!tracker.check(Some(&first_scene)));
+
+        first_scene.objects_mut()[0].set_position(1.0, 0.0, 0.0);
+        tracker.mark_changed();
+        assert!(tracker.check(Some(&first_scene)));
+        assert!(!tracker.check(Some(&second_scene)));
+        assert!(tracker.check(Some(&first_scene)));
+    }
+}
@@ -14,5 +14,46 @@ widget_ids! {
         z_light_label,
         z_light_text_box,
         run_button,
+        keep_play_changes_toggle,
+        file_panel_canvas,
+        scene_path_text_box,
+        save_button,
+        open_button,
+        file_status_text,
+        inspector_canvas,
+        inspector_name_label,
+        inspector_name_text_box,
+        inspector_position_label,
+        inspector_position_x_text_box,
+        inspector_position_y_text_box,
+        inspector_position_z_text_box,
+        inspector_rotation_label,
+        inspector_rotation_x_text_box,
+        inspector_rotation_y_text_box,
+        inspector_rotation_z_text_box,
+        inspector_scale_label,
+        inspector_scale_x_text_box,
+        inspector_scale_y_text_box,
+        inspector_scale_z_text_box,
+        inspector_light_source_toggle,
+        inspector_mesh_label,
+        inspector_mesh_text_box,
+        inspector_texture_label,
+        inspector_texture_text_box,
+        outliner_canvas,
+        outliner_scene_list,
+        outliner_object_list,
+        outliner_name_text_box,
+        outliner_duplicate_button,
+        outliner_delete_button,
+        gizmo_x_path,
+        gizmo_y_path,
+        gizmo_z_path,
+        gizmo_canvas,
+        gizmo_translate_button,
+        gizmo_rotate_button,
+        gizmo_scale_button,
+        gizmo_local_space_toggle,
+        gizmo_snapping_toggle,
     }
 }
\ No newline at end of file
@@ -7,6 +7,7 @@ use ketch_core::renderer::{Renderer};
 use ketch_core::settings::Settings;
 use ketch_core::input::InputSystem;
 use ketch_core::input;
+use ketch_core::input::input_recording::InputRecording;
 
 use winit::Event;
 use winit::WindowEvent;
@@ -16,6 +17,7 @@ use winit::ElementState;
 pub use ketch_core::renderer::{get_window_dimensions, get_window_dpi};
 
 use std::time::{Duration, Instant};
+use std::path::PathBuf;
 
 use fps_counter::FPSCounter;
 
@@ -29,6 +31,15 @@ struct Opts {
     /// Activate GUI Editor
     #[structopt(short = "g", long = "gui-editor")]
     gui_editor: bool,
+    /// Record input passed to the game and save it to file on exit
+    #[structopt(long = "record-input", parse(from_os_str))]
+    record_input: Option<PathBuf>,
+    /// Replay input recorded to file in place of live input
+    #[structopt(long = "replay-input", parse(from_os_str))]
+    replay_input: Option<PathBuf>,
+    /// Exit when input replay finishes
+    #[structopt(long = "exit-after-replay")]
+    exit_after_replay: bool,
 }
 
 /// A struct representing the top level of this engine.
@@ -39,6 +50,8 @@ pub struct Engine {
     input_system: InputSystem,
     editor: Option<Editor>,
     settings: Settings,
+    record_input_path: Option<PathBuf>,
+    exit_after_replay: bool,
 }
 
 impl Engine {
@@ -68,6 +81,20 @@ impl Engine {
         } else {
             None
         };
+
+        if opts.record_input.is_some() {
+            input_system.start_recording();
+        }
+        let mut exit_after_replay = false;
+        if let Some(path) = &opts.replay_input {
+            match InputRecording::load(path) {
+                Ok(recording) => {
+                    input_system.start_replay(recording);
+                    exit_after_replay = opts.exit_after_replay;
+                },
+                Err(e) => error!("Couldn't load input recording: {}", e),
+            }
+        }
         
         Engine {
             renderer,
@@ -75,6 +102,8 @@ impl Engine {
             input_system,
             settings,
             editor,
+            record_input_path: opts.record_input,
+            exit_after_replay,
         }
     }
 
@@ -95,12 +124,13 @@ impl Engine {
 
     fn handle_input<S: EventHandler>(&mut self, game: &mut S) {
         let pending_events = self.input_system.fetch_pending_events();
+        let gamepad_events = self.input_system.take_gamepad_events();
         let mut esc_pressed = false;
 
         for event in pending_events.iter() {
             match event {
                 Event::WindowEvent { event, .. } => match event {
-                    WindowEvent::CloseRequested => std::process::exit(0),
+                    WindowEvent::CloseRequested => self.exit(),
                     WindowEvent::Resized(_window_size) => self.renderer.force_recreate_swapchain(),
                     WindowEvent::HiDpiFactorChanged(_dpi) => self.renderer.force_recreate_swapchain(),
                     WindowEvent::KeyboardInput { input, .. } => match input {
@@ -122,14 +152,14 @@ impl Engine {
                 if editor.run_game() && esc_pressed {
                     self.input_system.grab_cursor(false);
                     self.input_system.hide_cursor(false);
-                    editor.set_run_game(false, Some(&self.asset_manager));
+                    editor.set_run_game(false, &mut self.asset_manager);
                 } else if editor.run_game() && !esc_pressed {
-                    game.process_input(&mut self.input_system, input::convert_to_input_events(pending_events));
+                    process_game_input(&mut self.input_system, game, game_input_events(pending_events, gamepad_events));
                 } else {
                     editor.handle_input(pending_events, &mut self.input_system);
                 }
             },
-            None => game.process_input(&mut self.input_system, input::convert_to_input_events(pending_events)),
+            None => process_game_input(&mut self.input_system, game, game_input_events(pending_events, gamepad_events)),
         }  
     }
 
@@ -137,12 +167,28 @@ impl Engine {
         match &mut self.editor {
             Some(editor) => {
                 if editor.run_game() {
-                    game.update(&self.settings, &mut self.asset_manager, time_per_update);
+                    update_game(&self.settings, &mut self.asset_manager, &mut self.input_system, game, time_per_update);
                 } else {
                     editor.update(&mut self.asset_manager, time_per_update);
                 }
             },
-            None => game.update(&self.settings, &mut self.asset_manager, time_per_update),
+            None => update_game(&self.settings, &mut self.asset_manager, &mut self.input_system, game, time_per_update),
+        }
+    }
+
+    /// Saves input recording, if it was started, and exits the process.
+    fn exit(&mut self) -> ! {
+        self.save_input_recording();
+        std::process::exit(0)
+    }
+
+    /// Stops input recording and saves it, if it was started from command line.
+    fn save_input_recording(&mut self) {
+        if let (Some(path), Some(recording)) = (&self.record_input_path, self.input_system.stop_recording()) {
+            match recording.save(path) {
+                Ok(()) => info!("Input recording saved to {}", path.display()),
+                Err(e) => error!("Couldn't save input recording: {}", e),
+            }
         }
     }
 
@@ -150,6 +196,8 @@ impl Engine {
         let mut fps_counter = FPSCounter::new();
         let log_fps_frequency = self.settings.log_fps_frequency();
         let time_per_update = self.settings.time_per_update();
+        let max_updates_per_frame = self.settings.max_updates_per_frame();
+        let min_frame_time = self.settings.max_frame_rate().map(|fps| Duration::from_secs(1) / fps);
 
         let mut last_fps_counter_log = Instant::now();
         let mut previous_time = Instant::now();
@@ -162,16 +210,32 @@ impl Engine {
         }
 
         loop {
+            // frame rate is capped before the next frame, so frames which failed to render are capped too
+            if let Some(min_frame_time) = min_frame_time {
+                let frame_time = previous_time.elapsed();
+                if frame_time < min_frame_time {
+                    std::thread::sleep(min_frame_time - frame_time);
+                }
+            }
+
             let elapsed = previous_time.elapsed();
             previous_time = Instant::now();
             lag += elapsed;
             
             self.handle_input(&mut game);
 
-            while lag >= time_per_update {
+            let (updates, remaining_lag) = fixed_updates(lag, time_per_update, max_updates_per_frame);
+            for _ in 0..updates {
                 self.update(&mut game, time_per_update);
+            }
+            lag = remaining_lag;
 
-                lag -= time_per_update;
+            if self.editor.as_ref().map_or(true, |editor| editor.run_game()) {
+                game.interpolate(&self.settings, &mut self.asset_manager, interpolation_alpha(lag, time_per_update));
+            }
+
+            if self.exit_after_replay && !self.input_system.replaying() {
+                self.exit();
             }
 
             let mut command_buffer = match self.renderer.create_command_buffer() {
@@ -188,6 +252,11 @@ impl Engine {
                 }
             }
 
+            let highlighted_object = self.editor.as_ref()
+                                                .filter(|editor| !editor.run_game())
+                                                .and_then(|editor| editor.selected_object());
+            self.renderer.set_highlighted_object(highlighted_object);
+
             let (image_num, acquire_future, mut command_buffer) = match self.renderer.render_scene(command_buffer, &mut self.asset_manager) {
                 Ok(res) => res,
                 Err(err) => {
@@ -206,7 +275,9 @@ impl Engine {
                 Ok(()) => {
                     let fps = fps_counter.tick();
                     if last_fps_counter_log.elapsed() >= log_fps_frequency {
-                        info!("Current FPS: {}", fps);
+                        let render_stats = self.renderer.render_stats();
+                        info!("Current FPS: {} (drawn objects: {}, culled objects: {}, draw calls: {})",
+                              fps, render_stats.drawn_objects(), render_stats.culled_objects(), render_stats.draw_calls());
                         last_fps_counter_log = Instant::now();
                     }
                 },
@@ -218,8 +289,104 @@ impl Engine {
     }
 }
 
+impl Drop for Engine {
+    /// Saves input recording when the engine is dropped without exiting, for example when the game panics,
+    /// as recordings are most useful for reproducing crashes.
+    fn drop(&mut self) {
+        self.save_input_recording();
+    }
+}
+
 pub trait EventHandler {
     fn process_input(&mut self, input_system: &mut InputSystem, input_events: Vec<InputEvent>);
     fn update(&mut self, settings: &Settings, asset_manager: &mut AssetManager, elapsed_time: Duration);
     fn init(&mut self, settings: &Settings, asset_manager: &mut AssetManager);
-}
\ No newline at end of file
+    /// Called before rendering each frame with the fraction of time per update which passed since the last update,
+    /// ranging from 0 to 1. It can be used to interpolate objects between their previous and current state.
+    fn interpolate(&mut self, _settings: &Settings, _asset_manager: &mut AssetManager, _alpha: f32) {}
+}
+
+/// Passes live input to the game, records it and updates polled input state. Live input is ignored while recorded input is replayed.
+fn process_game_input<S: EventHandler>(input_system: &mut InputSystem, game: &mut S, input_events: Vec<InputEvent>) {
+    if input_system.replaying() {
+        return;
+    }
+    input_system.record_events(&input_events);
+    input_system.update_state(&input_events);
+    game.process_input(input_system, input_events);
+}
+
+/// Runs one fixed update of the game, passing replayed input recorded before it first.
+fn update_game<S: EventHandler>(settings: &Settings, asset_manager: &mut AssetManager, input_system: &mut InputSystem, game: &mut S, time_per_update: Duration) {
+    for input_events in input_system.replayed_frames() {
+        input_system.update_state(&input_events);
+        game.process_input(input_system, input_events);
+    }
+    game.update(settings, asset_manager, time_per_update);
+    input_system.advance_tick();
+}
+
+/// Converts window events to InputEvents and appends gamepad events after them.
+fn game_input_events(pending_events: Vec<Event>, gamepad_events: Vec<InputEvent>) -> Vec<InputEvent> {
+    let mut input_events = input::convert_to_input_events(pending_events);
+    input_events.extend(gamepad_events);
+    input_events
+}
+
+/// Returns number of fixed updates which catch up with the lag and the lag left after them.
+/// When more than `max_updates` would be needed, the rest of the lag is skipped, so the game
+/// doesn't fall further behind when updates take longer than time per update.
+fn fixed_updates(lag: Duration, time_per_update: Duration, max_updates: u32) -> (u32, Duration) {
+    let mut lag = lag;
+    let mut updates = 0;
+    while lag >= time_per_update {
+        if updates == max_updates {
+            debug!("Skipping {:?} of game updates, because they take longer than time per update", lag);
+            return (updates, Duration::new(0, 0));
+        }
+        lag -= time_per_update;
+        updates += 1;
+    }
+    (updates, lag)
+}
+
+/// Returns fraction of time per update which passed since the last update, lag left after updates is shorter than it.
+fn interpolation_alpha(lag: Duration, time_per_update: Duration) -> f32 {
+    let alpha = (lag.as_secs_f64() / time_per_update.as_secs_f64()) as f32;
+    // lag just below time per update could be rounded to 1
+    alpha.min(1.0 - std::f32::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_catch_up_with_lag_and_keep_the_rest() {
+        let time_per_update = Duration::from_millis(16);
+
+        assert_eq!((0, Duration::from_millis(10)), fixed_updates(Duration::from_millis(10), time_per_update, 5));
+        assert_eq!((2, Duration::from_millis(3)), fixed_updates(Duration::from_millis(35), time_per_update, 5));
+        assert_eq!((5, Duration::from_millis(0)), fixed_updates(Duration::from_millis(80), time_per_update, 5));
+    }
+
+    #[test]
+    fn lag_above_max_updates_is_skipped() {
+        let time_per_update = Duration::from_millis(16);
+
+        assert_eq!((5, Duration::new(0, 0)), fixed_updates(Duration::from_millis(96), time_per_update, 5));
+        assert_eq!((1, Duration::new(0, 0)), fixed_updates(Duration::from_secs(10), time_per_update, 1));
+    }
+
+    #[test]
+    fn interpolation_alpha_is_below_one() {
+        let time_per_update = Duration::from_millis(16);
+
+        for lag in &[Duration::new(0, 0), Duration::from_millis(8), time_per_update - Duration::from_nanos(1)] {
+            let (_, lag) = fixed_updates(*lag, time_per_update, 5);
+            let alpha = interpolation_alpha(lag, time_per_update);
+            assert!(alpha >= 0.0 && alpha < 1.0, "alpha {} for lag {:?}", alpha, lag);
+        }
+        assert_eq!(0.5, interpolation_alpha(Duration::from_millis(8), time_per_update));
+    }
+}
@@ -0,0 +1,70 @@
+use image::{Rgba, RgbaImage};
+
+use std::path::Path;
+
+/// Compares two images channel by channel. Pixels are equal when no channel differs by more than the tolerance.
+/// Returns None if images match, otherwise returns an image where mismatched pixels are red.
+pub fn compare_images(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Option<RgbaImage> {
+    if actual.dimensions() != expected.dimensions() {
+        return Some(RgbaImage::from_pixel(actual.width(), actual.height(), Rgba([255, 0, 0, 255])));
+    }
+
+    let mut mismatch = false;
+    let diff = RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let actual_pixel = actual.get_pixel(x, y);
+        let expected_pixel = expected.get_pixel(x, y);
+
+        let pixel_matches = actual_pixel.data.iter()
+                                             .zip(expected_pixel.data.iter())
+                                             .all(|(a, e)| (*a as i16 - *e as i16).abs() <= tolerance as i16);
+
+        if pixel_matches {
+            let [r, g, b, _] = expected_pixel.data;
+            let luma = ((r as u32 + g as u32 + b as u32) / 3 / 4) as u8;
+            Rgba([luma, luma, luma, 255])
+        } else {
+            mismatch = true;
+            Rgba([255, 0, 0, 255])
+        }
+    });
+
+    if mismatch {
+        Some(diff)
+    } else {
+        None
+    }
+}
+
+/// Environment variable which makes missing reference images be created from captured frames.
+const UPDATE_REFERENCES_VAR: &str = "UPDATE_REFERENCES";
+
+/// Asserts that captured frame matches the reference image.
+/// Missing reference image fails the assertion, unless UPDATE_REFERENCES=1 is set, in which case
+/// the captured frame is saved as the new reference.
+/// On failure the captured frame and the diff image are written next to the reference image.
+pub fn assert_image_matches_reference<P: AsRef<Path>>(actual: &RgbaImage, reference_path: P, tolerance: u8) {
+    let reference_path = reference_path.as_ref();
+
+    if !reference_path.exists() {
+        if std::env::var(UPDATE_REFERENCES_VAR).map(|value| value != "1").unwrap_or(true) {
+            panic!("Reference image {} doesn't exist, run tests with {}=1 to create it from the captured frame",
+                   reference_path.display(), UPDATE_REFERENCES_VAR);
+        }
+        if let Some(parent) = reference_path.parent() {
+            std::fs::create_dir_all(parent).expect("Couldn't create reference image directory!");
+        }
+        actual.save(reference_path).expect("Couldn't save new reference image!");
+        return;
+    }
+
+    let expected = image::open(reference_path).expect("Couldn't load reference image!").to_rgba();
+
+    if let Some(diff) = compare_images(actual, &expected, tolerance) {
+        let actual_path = reference_path.with_extension("actual.png");
+        let diff_path = reference_path.with_extension("diff.png");
+        actual.save(&actual_path).expect("Couldn't save captured frame!");
+        diff.save(&diff_path).expect("Couldn't save diff image!");
+        panic!("Captured frame doesn't match reference image {}, see {} and {}",
+               reference_path.display(), actual_path.display(), diff_path.display());
+    }
+}
@@ -1 +1,2 @@
-pub mod model;
\ No newline at end of file
+pub mod model;
+pub mod image_comparison;
\ No newline at end of file
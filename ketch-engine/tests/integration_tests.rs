@@ -2,13 +2,18 @@ use ketch_engine::Engine;
 use ketch_core::settings::Settings;
 use ketch_core::input::InputSystem;
 use ketch_core::renderer::Renderer;
+use ketch_core::renderer::headless::HeadlessRenderer;
 use ketch_core::resource::AssetManager;
 use ketch_core::resource::camera::Camera;
 use ketch_core::resource::scene::Scene;
 use ketch_core::resource::object::ObjectBuilder;
+use ketch_core::resource::light::Light;
 
 mod common;
 
+use common::image_comparison;
+use image::{Rgba, RgbaImage};
+
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::path::Path;
@@ -111,3 +116,366 @@ fn render_simple_cube_with_texture() {
     assert!(renderer.execute_command_buffer(image_num, acquire_future, command_buffer).is_ok());
 }
 
+
+#[test]
+fn headless_render_renders_empty_frame_without_error() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+    let command_buffer_result = renderer.create_command_buffer();
+    assert!(command_buffer_result.is_ok());
+    let command_buffer = command_buffer_result.unwrap();
+    let render_result = renderer.render_scene(command_buffer, &mut asset_manager);
+    assert!(render_result.is_ok());
+
+    assert!(renderer.execute_command_buffer(render_result.unwrap()).is_ok());
+}
+
+#[test]
+fn headless_render_simple_cube_with_texture() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    let texture = asset_manager.load_texture("test_texture", Path::new("tests/common/data/rust_logo.png"));
+    asset_manager.add_texture(texture.clone());
+    mesh.write().unwrap().set_texture(texture);
+    asset_manager.add_mesh(mesh);
+    let camera = Camera::new();
+    asset_manager.set_active_scene(Scene::new("test_scene", camera));
+    let object = ObjectBuilder::new("test_object").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    asset_manager.active_scene_mut().unwrap().add_object(object);
+
+    let command_buffer_result = renderer.create_command_buffer();
+    assert!(command_buffer_result.is_ok());
+    let command_buffer = command_buffer_result.unwrap();
+    let render_result = renderer.render_scene(command_buffer, &mut asset_manager);
+    assert!(render_result.is_ok());
+
+    assert!(renderer.execute_command_buffer(render_result.unwrap()).is_ok());
+}
+
+#[test]
+fn identical_images_match() {
+    let image = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    assert!(image_comparison::compare_images(&image, &image.clone(), 0).is_none());
+}
+
+#[test]
+fn images_within_tolerance_match() {
+    let image = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let other_image = RgbaImage::from_pixel(4, 4, Rgba([12, 18, 30, 255]));
+    assert!(image_comparison::compare_images(&image, &other_image, 2).is_none());
+}
+
+#[test]
+fn images_outside_tolerance_produce_diff() {
+    let image = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let mut other_image = image.clone();
+    other_image.put_pixel(1, 2, Rgba([100, 20, 30, 255]));
+
+    let diff = image_comparison::compare_images(&image, &other_image, 2).unwrap();
+    assert_eq!(&Rgba([255, 0, 0, 255]), diff.get_pixel(1, 2));
+    assert_ne!(&Rgba([255, 0, 0, 255]), diff.get_pixel(0, 0));
+}
+
+#[test]
+fn images_with_different_dimensions_dont_match() {
+    let image = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let other_image = RgbaImage::from_pixel(4, 5, Rgba([10, 20, 30, 255]));
+    assert!(image_comparison::compare_images(&image, &other_image, 255).is_some());
+}
+
+#[test]
+fn headless_render_lit_cube_matches_reference_image() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    let texture = asset_manager.load_texture("test_texture", Path::new("tests/common/data/rust_logo.png"));
+    asset_manager.add_texture(texture.clone());
+    mesh.write().unwrap().set_texture(texture);
+    asset_manager.add_mesh(mesh);
+    asset_manager.set_active_scene(Scene::new("test_scene", Camera::new()));
+
+    let cube = ObjectBuilder::new("cube").with_rotation_angle(0.5, 0.5, 0.0)
+                                         .with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                         .build();
+    let mut light_object = ObjectBuilder::new("light_object").with_scale(0.2, 0.2, 0.2)
+                                                             .with_position(1.2, 1.0, 1.0)
+                                                             .with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                                             .build();
+    light_object.set_light_source(true);
+
+    let scene = asset_manager.active_scene_mut().unwrap();
+    scene.add_object(cube);
+    scene.add_object(light_object);
+    scene.set_light_position(1.2, 1.0, 1.0);
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+
+    let frame = renderer.capture_frame().unwrap();
+    assert_eq!((600, 400), frame.dimensions());
+    image_comparison::assert_image_matches_reference(&frame, "tests/common/data/reference/lit_cube.png", 2);
+}
+
+#[test]
+fn saved_scene_is_loaded_with_meshes_and_textures_from_asset_manager() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    let texture = asset_manager.load_texture("test_texture", Path::new("tests/common/data/rust_logo.png"));
+    asset_manager.add_texture(texture.clone());
+    mesh.write().unwrap().set_texture(texture);
+    asset_manager.add_mesh(mesh);
+    let other_texture = asset_manager.load_texture("other_texture", Path::new("tests/common/data/rust_logo.png"));
+    asset_manager.add_texture(other_texture.clone());
+
+    let mut camera = Camera::new();
+    camera.set_position(1.0, 2.0, 3.0);
+    let mut scene = Scene::new("saved_scene", camera);
+    scene.set_light_position(1.2, 1.0, 1.0);
+    scene.add_object(ObjectBuilder::new("cube").with_rotation_angle(0.5, 0.5, 0.0)
+                                               .with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                               .build());
+    scene.add_object(ObjectBuilder::new("empty").with_position(0.0, 1.0, 0.0).build());
+    let mut retextured_cube = ObjectBuilder::new("retextured_cube").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    retextured_cube.set_texture(Some(other_texture));
+    scene.add_object(retextured_cube);
+
+    let path = std::env::temp_dir().join("ketch_saved_scene.ron");
+    asset_manager.save_scene(&scene, &path).unwrap();
+    let loaded_scene = asset_manager.load_scene(&path).unwrap();
+
+    assert_eq!("saved_scene", loaded_scene.name());
+    assert_eq!((1.0, 2.0, 3.0), loaded_scene.camera().position());
+    assert_eq!((1.2, 1.0, 1.0), loaded_scene.light_position());
+    assert_eq!(3, loaded_scene.objects().len());
+
+    let cube = &loaded_scene.objects()[0];
+    assert_eq!((0.5, 0.5, 0.0), cube.rotation_angles());
+    let cube_mesh = cube.mesh().unwrap();
+    assert_eq!("test_mesh", cube_mesh.read().unwrap().name());
+    assert_eq!("test_texture", cube_mesh.read().unwrap().texture().name());
+    assert!(cube.texture().is_none());
+    assert!(loaded_scene.objects()[1].mesh().is_none());
+    assert_eq!("other_texture", loaded_scene.objects()[2].texture().unwrap().name());
+    assert_eq!("test_texture", asset_manager.mesh("test_mesh").unwrap().read().unwrap().texture().name());
+}
+
+#[test]
+fn headless_render_scene_with_more_lights_than_max_lights() {
+    let mut settings = Settings::new("test", 600.0, 400.0);
+    settings.set_max_lights(2);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    asset_manager.add_mesh(mesh);
+    asset_manager.set_active_scene(Scene::new("test_scene", Camera::new()));
+
+    let cube = ObjectBuilder::new("cube").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+
+    let scene = asset_manager.active_scene_mut().unwrap();
+    scene.add_object(cube);
+    scene.set_light_position(1.2, 1.0, 1.0);
+    scene.add_light(Light::directional(0.0, -1.0, -1.0));
+    scene.add_light(Light::spot((0.0, 2.0, 0.0), (0.0, -1.0, 0.0)));
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    assert!(renderer.execute_command_buffer(command_buffer).is_ok());
+}
+
+#[test]
+fn headless_render_shadow_of_cube_darkens_floor() {
+    let mut settings = Settings::new("test", 600.0, 400.0);
+    settings.set_shadow_map_resolution(512);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    asset_manager.add_mesh(mesh);
+
+    let mut camera = Camera::new();
+    camera.set_position(0.0, 3.0, 6.0);
+    camera.set_pitch(-30.0);
+    asset_manager.set_active_scene(Scene::new("test_scene", camera));
+
+    let cube = ObjectBuilder::new("cube").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    let floor = ObjectBuilder::new("floor").with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                           .with_position(0.0, -1.0, 0.0)
+                                           .with_scale(10.0, 0.1, 10.0)
+                                           .build();
+
+    let scene = asset_manager.active_scene_mut().unwrap();
+    scene.clear_lights();
+    scene.add_light(Light::directional(0.0, -1.0, 0.0));
+    scene.add_object(cube);
+    scene.add_object(floor);
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+    let shadowed_frame = renderer.capture_frame().unwrap();
+
+    asset_manager.active_scene_mut().unwrap().objects_mut()[1].set_receive_shadows(false);
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+    let lit_frame = renderer.capture_frame().unwrap();
+
+    let brightness = |image: &RgbaImage| image.pixels().map(|pixel| pixel[0] as u64 + pixel[1] as u64 + pixel[2] as u64).sum::<u64>();
+    assert!(brightness(&shadowed_frame) < brightness(&lit_frame));
+}
+
+#[test]
+fn headless_render_object_material_overrides_mesh_material() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    let dark = asset_manager.create_material("dark");
+    dark.write().unwrap().set_base_color(0.1, 0.1, 0.1);
+    mesh.write().unwrap().set_material(Some(dark.clone()));
+    asset_manager.add_mesh(mesh);
+    asset_manager.add_material(dark);
+
+    let glowing = asset_manager.create_material("glowing");
+    glowing.write().unwrap().set_emissive_color(1.0, 0.0, 0.0);
+    asset_manager.add_material(glowing);
+
+    asset_manager.set_active_scene(Scene::new("test_scene", Camera::new()));
+    let cube = ObjectBuilder::new("cube").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    let scene = asset_manager.active_scene_mut().unwrap();
+    scene.add_object(cube);
+    scene.set_light_position(1.2, 1.0, 1.0);
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+    let dark_frame = renderer.capture_frame().unwrap();
+
+    let glowing = asset_manager.material("glowing");
+    asset_manager.active_scene_mut().unwrap().objects_mut()[0].set_material(glowing);
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+    let glowing_frame = renderer.capture_frame().unwrap();
+
+    let red = |image: &RgbaImage| image.pixels().map(|pixel| pixel[0] as u64).sum::<u64>();
+    assert!(red(&dark_frame) < red(&glowing_frame));
+}
+
+#[test]
+fn headless_render_culls_objects_outside_of_camera_frustum() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    asset_manager.add_mesh(mesh);
+    asset_manager.set_active_scene(Scene::new("test_scene", Camera::new()));
+
+    let visible = ObjectBuilder::new("visible").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    let behind = ObjectBuilder::new("behind").with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                             .with_position(0.0, 0.0, 10.0)
+                                             .build();
+    let beside = ObjectBuilder::new("beside").with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                             .with_position(50.0, 0.0, 0.0)
+                                             .build();
+
+    let scene = asset_manager.active_scene_mut().unwrap();
+    scene.add_object(visible);
+    scene.add_object(behind);
+    scene.add_object(beside);
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+
+    assert_eq!(1, renderer.render_stats().drawn_objects());
+    assert_eq!(2, renderer.render_stats().culled_objects());
+}
+
+#[test]
+fn headless_render_draws_objects_sharing_mesh_with_one_draw_call() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let mut renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    asset_manager.add_mesh(mesh);
+    let glowing = asset_manager.create_material("glowing");
+    glowing.write().unwrap().set_emissive_color(1.0, 0.0, 0.0);
+    asset_manager.add_material(glowing);
+
+    let mut camera = Camera::new();
+    camera.set_position(0.0, 0.0, 10.0);
+    asset_manager.set_active_scene(Scene::new("test_scene", camera));
+
+    let mut objects = (0 .. 4).map(|i| ObjectBuilder::new("cube").with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                                                 .with_position(i as f32 * 2.0 - 3.0, 0.0, 0.0)
+                                                                 .build())
+                              .collect::<Vec<_>>();
+    objects[3].set_material(asset_manager.material("glowing"));
+
+    let scene = asset_manager.active_scene_mut().unwrap();
+    for object in objects {
+        scene.add_object(object);
+    }
+
+    let command_buffer = renderer.create_command_buffer().unwrap();
+    let command_buffer = renderer.render_scene(command_buffer, &mut asset_manager).unwrap();
+    renderer.execute_command_buffer(command_buffer).unwrap();
+
+    assert_eq!(4, renderer.render_stats().drawn_objects());
+    assert_eq!(2, renderer.render_stats().draw_calls());
+}
+
+#[test]
+fn picking_returns_nearest_object_under_cursor() {
+    let settings = Settings::new("test", 600.0, 400.0);
+
+    let renderer = HeadlessRenderer::new(&settings).unwrap();
+    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    asset_manager.add_mesh(mesh);
+
+    let mut camera = Camera::new();
+    camera.set_position(0.0, 0.0, 10.0);
+    let mut scene = Scene::new("test_scene", camera);
+
+    let far = ObjectBuilder::new("far").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    let near = ObjectBuilder::new("near").with_mesh(asset_manager.mesh("test_mesh").unwrap())
+                                         .with_position(0.0, 0.0, 4.0)
+                                         .build();
+    let near_id = near.id();
+    scene.add_object(far);
+    scene.add_object(near);
+
+    let center_ray = scene.camera().screen_ray(300.0, 200.0, 600.0, 400.0);
+    assert_eq!(Some(near_id), scene.pick_object(&center_ray));
+
+    let corner_ray = scene.camera().screen_ray(0.0, 0.0, 600.0, 400.0);
+    assert_eq!(None, scene.pick_object(&corner_ray));
+}